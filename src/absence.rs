@@ -0,0 +1,191 @@
+//! Absences like sick days, vacation and public holidays, which have no goal. They are stored in
+//! "<data_file>.absences", so every profile has its own. Vacation days from the older
+//! "<data_file>.vacation" file are read as vacation and moved over with the next change.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{parse_date_or_date_time, DateOrDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbsenceKind {
+    Sick,
+    Vacation,
+    Holiday,
+}
+
+impl FromStr for AbsenceKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sick" => Ok(Self::Sick),
+            "vacation" => Ok(Self::Vacation),
+            "holiday" => Ok(Self::Holiday),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: sick, vacation, holiday",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AbsenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Sick => "sick",
+            Self::Vacation => "vacation",
+            Self::Holiday => "holiday",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Absence {
+    pub kind: AbsenceKind,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum AbsenceCommand {
+    /// add absent days, e.g. `tt absence add sick 2024-03-02..2024-03-04`
+    Add {
+        /// possible values: "sick", "vacation", "holiday"
+        kind: AbsenceKind,
+        /// a day or a range of days like "2024-03-02..2024-03-04"
+        days: String,
+    },
+
+    /// remove absent days of any kind
+    Remove {
+        /// a day or a range of days like "2024-03-02..2024-03-04"
+        days: String,
+    },
+
+    /// list all absent days
+    List,
+}
+
+fn absence_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.absences", path))
+}
+
+fn vacation_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.vacation", path))
+}
+
+/// reads the absences belonging to the data file
+pub fn read(path: &str) -> Result<Vec<Absence>> {
+    let absence_path = absence_path(path);
+    if absence_path.exists() {
+        return Ok(serde_json::from_str(&std::fs::read_to_string(
+            absence_path,
+        )?)?);
+    }
+    let vacation_path = vacation_path(path);
+    if !vacation_path.exists() {
+        return Ok(Vec::new());
+    }
+    let days: Vec<NaiveDate> = serde_json::from_str(&std::fs::read_to_string(vacation_path)?)?;
+    Ok(days
+        .into_iter()
+        .map(|date| Absence {
+            kind: AbsenceKind::Vacation,
+            date,
+        })
+        .collect())
+}
+
+pub fn write(path: &str, absences: &mut Vec<Absence>) -> Result<()> {
+    absences.sort_by_key(|absence| absence.date);
+    std::fs::write(absence_path(path), serde_json::to_string_pretty(absences)?)?;
+    let vacation_path = vacation_path(path);
+    if vacation_path.exists() {
+        std::fs::remove_file(vacation_path)?;
+    }
+    Ok(())
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    Ok(match parse_date_or_date_time(s)? {
+        DateOrDateTime::Date(date) => date,
+        DateOrDateTime::DateTime(date_time) => date_time.date(),
+    })
+}
+
+/// the days from `from` to `to` [default: same as from]
+pub fn date_range(from: &str, to: Option<&str>) -> Result<Vec<NaiveDate>> {
+    let from = parse_date(from)?;
+    let to = to.map(parse_date).transpose()?.unwrap_or(from);
+    if to < from {
+        return Err(anyhow!("The last day is before the first day!"));
+    }
+    let days = (to - from).num_days();
+    Ok((0..=days).map(|i| from + Duration::days(i)).collect())
+}
+
+/// the days of a single day or a range like "2024-03-02..2024-03-04"
+fn parse_days(days: &str) -> Result<Vec<NaiveDate>> {
+    match days.split_once("..") {
+        Some((from, to)) => date_range(from.trim(), Some(to.trim())),
+        None => date_range(days, None),
+    }
+}
+
+/// replaces the absences on `days` with ones of `kind`
+pub fn add(absences: &mut Vec<Absence>, kind: AbsenceKind, days: &[NaiveDate]) {
+    absences.retain(|absence| !days.contains(&absence.date));
+    absences.extend(days.iter().map(|&date| Absence { kind, date }));
+}
+
+/// removes the absences on `days` and returns how many were removed
+fn remove(absences: &mut Vec<Absence>, days: &[NaiveDate]) -> usize {
+    let count = absences.len();
+    absences.retain(|absence| !days.contains(&absence.date));
+    count - absences.len()
+}
+
+pub fn absence(path: &str, command: AbsenceCommand) -> Result<()> {
+    let mut absences = read(path)?;
+    match command {
+        AbsenceCommand::Add { kind, days } => {
+            let days = parse_days(&days)?;
+            add(&mut absences, kind, &days);
+            println!("Added {} {} days.", days.len(), kind);
+        }
+        AbsenceCommand::Remove { days } => {
+            let removed = remove(&mut absences, &parse_days(&days)?);
+            println!("Removed {} absent days.", removed);
+        }
+        AbsenceCommand::List => {
+            for absence in &absences {
+                println!("{}  {}", absence.date.format("%a %Y-%m-%d"), absence.kind);
+            }
+            return Ok(());
+        }
+    }
+    write(path, &mut absences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_days() {
+        let date = |day| NaiveDate::from_ymd(2024, 3, day);
+        assert_eq!(
+            vec![date(2), date(3), date(4)],
+            parse_days("2024-03-02..2024-03-04").unwrap()
+        );
+        assert_eq!(vec![date(2)], parse_days("2024-03-02").unwrap());
+        assert!(parse_days("2024-03-04..2024-03-02").is_err());
+    }
+}
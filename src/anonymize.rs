@@ -0,0 +1,106 @@
+//! `tt export --anonymize`, which replaces everything that was typed in, like descriptions,
+//! projects, tags and notes, with numbered placeholders and keeps the times. The same text always
+//! gets the same placeholder, so the structure of the data stays intact, e.g. for a bug report or
+//! for publishing statistics without the names of clients.
+
+use uuid::Uuid;
+
+use std::collections::HashMap;
+
+use crate::TrackingEvent;
+
+/// numbers the distinct values of one kind, in the order they first appear
+#[derive(Default)]
+struct Placeholders {
+    names: HashMap<String, String>,
+}
+
+impl Placeholders {
+    fn get(&mut self, kind: &str, value: &str) -> String {
+        let next = self.names.len() + 1;
+        self.names
+            .entry(value.to_string())
+            .or_insert_with(|| format!("{} {}", kind, next))
+            .clone()
+    }
+
+    fn replace(&mut self, kind: &str, value: &Option<String>) -> Option<String> {
+        value.as_deref().map(|value| self.get(kind, value))
+    }
+}
+
+pub fn anonymize(data: &[TrackingEvent]) -> Vec<TrackingEvent> {
+    let mut descriptions = Placeholders::default();
+    let mut projects = Placeholders::default();
+    let mut tags = Placeholders::default();
+    let mut hosts = Placeholders::default();
+    let mut users = Placeholders::default();
+    let mut notes = 0;
+    data.iter()
+        .map(|event| {
+            let mut event = event.clone();
+            let data = event.data_mut();
+            // ids created before they were random are derived from the content
+            data.id = Uuid::new_v4();
+            data.description = descriptions.replace("description", &data.description);
+            data.project = projects.replace("project", &data.project);
+            data.tags = data.tags.iter().map(|tag| tags.get("tag", tag)).collect();
+            for note in &mut data.notes {
+                notes += 1;
+                note.text = format!("note {}", notes);
+            }
+            data.host = hosts.replace("host", &data.host);
+            data.user = users.replace("user", &data.user);
+            event
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_anonymize() {
+        let event = |description: &str, project: Option<&str>, h| {
+            TrackingEvent::Start(TrackingData {
+                project: project.map(String::from),
+                tags: vec!["acme".to_string(), "call".to_string()],
+                ..TrackingData::new(
+                    Some(description.to_string()),
+                    Utc.ymd(2024, 3, 4).and_hms(h, 0, 0),
+                )
+            })
+        };
+        let data = vec![
+            event("call with acme", Some("acme"), 8),
+            event("fix bug", None, 9),
+            event("call with acme", Some("acme"), 10),
+        ];
+        let anonymized = anonymize(&data);
+        let fields = |event: &TrackingEvent| {
+            let data = event.data();
+            (
+                data.description.clone().unwrap(),
+                data.project.clone(),
+                data.tags.join(","),
+                data.time,
+            )
+        };
+        assert_eq!(
+            (
+                "description 1".to_string(),
+                Some("project 1".to_string()),
+                "tag 1,tag 2".to_string(),
+                data[0].time(true)
+            ),
+            fields(&anonymized[0])
+        );
+        assert_eq!("description 2", fields(&anonymized[1]).0);
+        assert_eq!(None, fields(&anonymized[1]).1);
+        assert_eq!(fields(&anonymized[0]).0, fields(&anonymized[2]).0);
+        assert_ne!(data[0].data().id, anonymized[0].data().id);
+    }
+}
@@ -0,0 +1,171 @@
+//! Archiving old entries into a file per year next to the data file (e.g. `timetracking-2022.bin`,
+//! or `.json` without the binary feature), which keeps the data file small. Commands which
+//! summarize the tracked time load the archives again, if the range they show needs them.
+
+use anyhow::{Context, Result};
+use chrono::prelude::*;
+
+use std::path::{Path, PathBuf};
+
+use crate::storage::{read_data, write_data};
+use crate::{Bounds, TrackingEvent};
+
+#[cfg(feature = "binary")]
+const EXTENSION: &str = "bin";
+#[cfg(not(feature = "binary"))]
+const EXTENSION: &str = "json";
+
+fn stem(data_path: &Path) -> String {
+    data_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn archive_path(data_path: &Path, year: i32) -> PathBuf {
+    data_path.with_file_name(format!("{}-{}.{}", stem(data_path), year, EXTENSION))
+}
+
+/// the archives next to the data file with their years, the oldest first
+pub fn archives(data_path: &Path) -> Result<Vec<(i32, PathBuf)>> {
+    let dir = match data_path.parent() {
+        Some(dir) if dir.as_os_str().is_empty() => Path::new("."),
+        Some(dir) => dir,
+        None => return Ok(Vec::new()),
+    };
+    let prefix = format!("{}-", stem(data_path));
+    let suffix = format!(".{}", EXTENSION);
+    let mut archives = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let year = name
+            .strip_prefix(&prefix)
+            .and_then(|name| name.strip_suffix(&suffix))
+            .and_then(|year| year.parse().ok());
+        if let Some(year) = year {
+            archives.push((year, path));
+        }
+    }
+    archives.sort();
+    Ok(archives)
+}
+
+/// the number of events before `before`. an interval which is still running at `before` stays in
+/// the data file as a whole.
+fn split_index(data: &[TrackingEvent], before: NaiveDate) -> usize {
+    let mut index = data
+        .iter()
+        .position(|event| event.local_date() >= before)
+        .unwrap_or(data.len());
+    while index > 0 && data[index - 1].is_start() {
+        index -= 1;
+    }
+    index
+}
+
+/// moves the events before `before` into the archives of their years. returns whether the data
+/// was changed.
+pub fn archive(data: &mut Vec<TrackingEvent>, data_path: &str, before: NaiveDate) -> Result<bool> {
+    let data_path = Path::new(data_path);
+    let index = split_index(data, before);
+    if index == 0 {
+        println!("Nothing to archive.");
+        return Ok(false);
+    }
+    let archived: Vec<_> = data.drain(..index).collect();
+    let mut years: Vec<_> = archived
+        .iter()
+        .map(|event| event.local_date().year())
+        .collect();
+    // the offsets of the events can differ around new year
+    years.sort_unstable();
+    years.dedup();
+    for year in years {
+        let path = archive_path(data_path, year);
+        let mut events = if path.exists() {
+            read_data(&path).with_context(|| format!("could not read \"{}\"", path.display()))?
+        } else {
+            Vec::new()
+        };
+        let count = events.len();
+        events.extend(
+            archived
+                .iter()
+                .filter(|event| event.local_date().year() == year)
+                .cloned(),
+        );
+        events.sort_by_key(|event| event.time(true));
+        events.dedup();
+        write_data(&path, &events)?;
+        println!(
+            "Moved {} entries into \"{}\".",
+            events.len() - count,
+            path.display()
+        );
+    }
+    Ok(true)
+}
+
+/// the events of the archives which are needed for the range `bounds`, followed by `data`.
+/// archives are only read if the data file doesn't reach back far enough.
+pub fn load(
+    data_path: &str,
+    (since, until): Bounds,
+    data: Vec<TrackingEvent>,
+) -> Result<Vec<TrackingEvent>> {
+    match (since, data.first()) {
+        (Some(since), Some(first)) if first.time(true) <= since => return Ok(data),
+        _ => {}
+    }
+    let mut events = Vec::new();
+    for (year, path) in archives(Path::new(data_path))? {
+        let in_range = !matches!(since, Some(since) if year < since.year())
+            && !matches!(until, Some(until) if year > until.year());
+        if in_range {
+            events.extend(
+                read_data(&path)
+                    .with_context(|| format!("could not read \"{}\"", path.display()))?,
+            );
+        }
+    }
+    events.extend(data);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_split_index() {
+        let event = |start: bool, day: u32| {
+            let data = TrackingData {
+                offset: Some(0),
+                ..TrackingData::new(None, Utc.ymd(2021, 12, day).and_hms(12, 0, 0))
+            };
+            if start {
+                TrackingEvent::Start(data)
+            } else {
+                TrackingEvent::Stop(data)
+            }
+        };
+        let data = vec![
+            event(true, 1),
+            event(false, 2),
+            event(true, 3),
+            event(false, 5),
+        ];
+        assert_eq!(2, split_index(&data, NaiveDate::from_ymd(2021, 12, 4)));
+        assert_eq!(4, split_index(&data, NaiveDate::from_ymd(2021, 12, 6)));
+        assert_eq!(0, split_index(&data, NaiveDate::from_ymd(2021, 12, 1)));
+        assert_eq!(
+            PathBuf::from(format!("/data/timetracking-2021.{}", EXTENSION)),
+            archive_path(Path::new("/data/timetracking.bin"), 2021)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Stopping sessions which are still running at the end of the day, so forgetting to stop on
+//! Friday evening doesn't add the whole weekend to the tracked time.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+
+use crate::natural_time::parse_time;
+use crate::settings::Settings;
+use crate::{TrackingData, TrackingEvent};
+
+/// the time of day from `auto_stop_at`, `None` if it's disabled
+fn parse_auto_stop_at(settings: &Settings) -> Result<Option<NaiveTime>> {
+    let auto_stop_at = settings.auto_stop_at.trim();
+    if auto_stop_at.is_empty() {
+        return Ok(None);
+    }
+    parse_time(auto_stop_at).map(Some).ok_or_else(|| {
+        anyhow!(
+            "invalid auto_stop_at \"{}\", expected a time like \"18:30\"",
+            auto_stop_at
+        )
+    })
+}
+
+/// the first time after `start` at which the session gets stopped. a session started after the
+/// end of the day runs until the end of the next day.
+fn stop_time(start: DateTime<Utc>, end_of_day: NaiveTime) -> Option<DateTime<Utc>> {
+    let start = start.with_timezone(&Local);
+    let mut day = start.date();
+    if start.time() >= end_of_day {
+        day += Duration::days(1);
+    }
+    day.and_time(end_of_day)
+        .map(|stop| stop.with_timezone(&Utc))
+}
+
+/// inserts a stop at `auto_stop_at`, if the running session is still open past it. returns whether
+/// a stop was inserted.
+pub fn check(settings: &Settings, data: &mut Vec<TrackingEvent>) -> Result<bool> {
+    let end_of_day = match parse_auto_stop_at(settings)? {
+        Some(end_of_day) => end_of_day,
+        None => return Ok(false),
+    };
+    let start = match data.last() {
+        Some(event) if event.is_start() => event.time(true),
+        _ => return Ok(false),
+    };
+    match stop_time(start, end_of_day) {
+        Some(stop) if stop <= Utc::now() => {
+            data.push(TrackingEvent::Stop(TrackingData::new(
+                Some("auto stop".to_string()),
+                stop,
+            )));
+            eprintln!(
+                "Warning: Time tracking was still running at {}, inserted a stop at {}.",
+                settings.auto_stop_at.trim(),
+                stop.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_time() {
+        let end_of_day = NaiveTime::from_hms(18, 30, 0);
+        let start = Local.ymd(2021, 4, 2).and_hms(9, 0, 0);
+        assert_eq!(
+            Some(Local.ymd(2021, 4, 2).and_hms(18, 30, 0).with_timezone(&Utc)),
+            stop_time(start.with_timezone(&Utc), end_of_day)
+        );
+        let start = Local.ymd(2021, 4, 2).and_hms(20, 0, 0);
+        assert_eq!(
+            Some(Local.ymd(2021, 4, 3).and_hms(18, 30, 0).with_timezone(&Utc)),
+            stop_time(start.with_timezone(&Utc), end_of_day)
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Rotating copies of the data file, which are created before every change, so a corrupted
+//! file or a bad cleanup can be recovered.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use structopt::StructOpt;
+
+use std::path::{Path, PathBuf};
+
+use crate::storage::read_data;
+use crate::TrackingEvent;
+
+#[derive(Debug, StructOpt)]
+pub enum BackupCommand {
+    /// list all backups, newest first
+    List,
+
+    /// create a backup of the data file now
+    Create,
+}
+
+/// the directory containing the backups of the given data file
+fn backup_dir(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.backups", path))
+}
+
+/// all backups of the data file, newest first
+fn backups(path: &str) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir(path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    // the names start with the timestamp, so they sort chronologically
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// copies the data file into the backup directory and removes the oldest backups, so at most
+/// `keep` backups remain. if `keep` is 0, no backups are removed
+pub fn create(path: &str, keep: usize) -> Result<()> {
+    if !Path::new(path).exists() {
+        return Ok(());
+    }
+    let dir = backup_dir(path);
+    std::fs::create_dir_all(&dir)?;
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S%.3f");
+    std::fs::copy(path, dir.join(format!("{}-{}", timestamp, file_name)))?;
+
+    if keep > 0 {
+        for old in backups(path)?.iter().skip(keep) {
+            std::fs::remove_file(old)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn backup(path: &str, keep: usize, command: BackupCommand) -> Result<()> {
+    match command {
+        BackupCommand::List => {
+            let backups = backups(path)?;
+            if backups.is_empty() {
+                println!("No backups found!");
+            }
+            for (i, backup) in backups.iter().enumerate() {
+                let metadata = std::fs::metadata(backup)?;
+                let modified: DateTime<Local> = metadata.modified()?.into();
+                println!(
+                    "({}) {} {:>10} bytes",
+                    i,
+                    modified.format("%Y-%m-%d %H:%M:%S"),
+                    metadata.len()
+                );
+            }
+        }
+        BackupCommand::Create => {
+            create(path, keep)?;
+            println!("Backup created.");
+        }
+    }
+    Ok(())
+}
+
+/// the file of the backup with the given number, as shown by `tt backup list`
+pub fn backup_path(path: &str, index: usize) -> Result<PathBuf> {
+    backups(path)?
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| anyhow!("There is no backup with number {}!", index))
+}
+
+/// reads the data of the backup with the given number, as shown by `tt backup list`
+pub fn restore(path: &str, index: usize) -> Result<Vec<TrackingEvent>> {
+    read_data(backup_path(path, index)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::write_data;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_rotation_and_restore() {
+        let dir = std::env::temp_dir().join(format!("tt-test-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.bin").to_string_lossy().to_string();
+        let versions: Vec<Vec<_>> = (1..=3)
+            .map(|hour| {
+                vec![TrackingEvent::Start(TrackingData::new(
+                    None,
+                    Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0),
+                ))]
+            })
+            .collect();
+        for version in &versions {
+            write_data(&path, version).unwrap();
+            create(&path, 2).unwrap();
+            // the names of the backups only differ in the milliseconds
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(2, backups(&path).unwrap().len());
+        assert_eq!(versions[2], restore(&path, 0).unwrap());
+        assert_eq!(versions[1], restore(&path, 1).unwrap());
+        assert!(restore(&path, 2).is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,142 @@
+//! Reminders to take a break. Once time was tracked for longer than `watch.break_reminder_after`
+//! without a pause, a desktop notification is sent, and again every `watch.break_reminder_snooze`
+//! until the next pause. `tt snooze` postpones it. `tt watch` and `tt daemon` check this while they
+//! run, and the time until which it stays quiet is kept next to the data file
+//! ("<data_file>.break-reminder"), so they don't both remind.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+
+use std::path::PathBuf;
+
+use crate::natural_time::parse_duration;
+use crate::settings::Settings;
+use crate::storage::read_tail;
+use crate::{eta, format_hours_minutes, notification, strict, TrackingEvent};
+
+/// how often `tt daemon` checks whether to remind
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// a stop and a start which are closer together than this, like when switching tasks, are no pause
+const SHORTEST_PAUSE_MINUTES: i64 = 5;
+
+fn state_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.break-reminder", path))
+}
+
+/// until when there is no reminder, because there just was one or it was snoozed
+fn quiet_until(path: &str) -> Option<DateTime<Utc>> {
+    let text = std::fs::read_to_string(state_path(path)).ok()?;
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn set_quiet_until(path: &str, until: DateTime<Utc>) -> Result<()> {
+    std::fs::write(
+        state_path(path),
+        until.to_rfc3339_opts(SecondsFormat::Secs, true),
+    )?;
+    Ok(())
+}
+
+/// since when time is tracked without a pause, or `None` if nothing is tracked right now
+fn working_since(data: &[TrackingEvent]) -> Option<DateTime<Utc>> {
+    let mut events = data.iter().rev();
+    let mut since = match events.next() {
+        Some(event) if event.is_start() => event.time(true),
+        _ => return None,
+    };
+    while let Some(event) = events.next() {
+        if event.is_start() {
+            since = event.time(true);
+            continue;
+        }
+        // the end of the interval before
+        if since - event.time(true) >= Duration::minutes(SHORTEST_PAUSE_MINUTES) {
+            break;
+        }
+        match events.next() {
+            Some(start) if start.is_start() => since = start.time(true),
+            _ => break,
+        }
+    }
+    Some(since)
+}
+
+/// reminds to take a break, if it's time for one
+pub fn check(settings: &Settings, path: &str) -> Result<()> {
+    let after = match settings.watch.break_reminder_after {
+        Some(after) => after,
+        None => return Ok(()),
+    };
+    let now = Utc::now();
+    let data = read_tail(path, now - Duration::days(1))?;
+    let since = match working_since(&data) {
+        Some(since) if now - since >= after => since,
+        _ => return Ok(()),
+    };
+    if quiet_until(path).is_some_and(|until| until > now) {
+        return Ok(());
+    }
+    let message = format!(
+        "You worked for {} without a pause. Time for a break! `tt snooze` postpones this reminder.",
+        format_hours_minutes(now - since)
+    );
+    println!("{}", message);
+    notification::send(&message);
+    set_quiet_until(path, now + settings.watch.break_reminder_snooze)?;
+    strict::record_notice(settings, path, &message)
+}
+
+/// postpones the break reminder by `duration`, or by `break_reminder_snooze` from the config
+pub fn snooze(settings: &Settings, path: &str, duration: Option<String>) -> Result<()> {
+    let duration = match duration {
+        Some(duration) => parse_duration(&duration).ok_or_else(|| {
+            anyhow!(
+                "invalid duration \"{}\", expected a duration like \"30m\"",
+                duration
+            )
+        })?,
+        None => settings.watch.break_reminder_snooze,
+    };
+    let until = Utc::now()
+        .checked_add_signed(duration)
+        .ok_or_else(|| anyhow!("can't snooze the break reminder for that long"))?;
+    set_quiet_until(path, until)?;
+    let message = format!(
+        "The break reminder is snoozed until {}.",
+        eta::format(until)
+    );
+    println!("{}", message);
+    strict::record_notice(settings, path, &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_working_since() {
+        let at = |h, m| Utc.ymd(2024, 3, 4).and_hms(h, m, 0);
+        let start = |h, m| TrackingEvent::Start(TrackingData::new(None, at(h, m)));
+        let stop = |h, m| TrackingEvent::Stop(TrackingData::new(None, at(h, m)));
+
+        assert_eq!(None, working_since(&[]));
+        assert_eq!(None, working_since(&[start(8, 0), stop(9, 0)]));
+        assert_eq!(Some(at(8, 0)), working_since(&[start(8, 0)]));
+        // switching tasks is no pause, lunch is
+        let data = [
+            start(8, 0),
+            stop(12, 0),
+            start(12, 45),
+            stop(14, 0),
+            start(14, 2),
+            start(15, 0),
+        ];
+        assert_eq!(Some(at(12, 45)), working_since(&data));
+        assert_eq!(Some(at(12, 45)), working_since(&data[2..]));
+        assert_eq!(Some(at(14, 2)), working_since(&data[4..]));
+    }
+}
@@ -0,0 +1,188 @@
+//! A month or week grid with the tracked time of every day, colored by whether the daily goal was
+//! met.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use iif::iif;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::color::{paint, Style};
+use crate::settings::Settings;
+use crate::{
+    format_hours_minutes, get_data_as_days, parse_date_or_date_time, summarize_day,
+    CalculationOptions, DateOrDateTime, TrackingEvent,
+};
+
+/// the width of a day in the grid
+const CELL_WIDTH: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarPeriod {
+    Month,
+    Week,
+}
+
+impl FromStr for CalendarPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "month" => Ok(Self::Month),
+            "week" => Ok(Self::Week),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: month, week",
+                s
+            )),
+        }
+    }
+}
+
+/// the first and last day of the period containing `date` [default: today]
+pub fn range(
+    settings: &Settings,
+    period: CalendarPeriod,
+    date: &Option<String>,
+) -> Result<(NaiveDate, NaiveDate)> {
+    let date = match date.as_deref().map(parse_date_or_date_time).transpose()? {
+        Some(DateOrDateTime::Date(date)) => date,
+        Some(DateOrDateTime::DateTime(date_time)) => date_time.date(),
+        None => Local::today().naive_local(),
+    };
+    Ok(match period {
+        CalendarPeriod::Week => {
+            let start = settings.start_of_week(date);
+            (start, start + Duration::days(6))
+        }
+        CalendarPeriod::Month => {
+            let start = NaiveDate::from_ymd(date.year(), date.month(), 1);
+            let next_month = match date.month() {
+                12 => NaiveDate::from_ymd(date.year() + 1, 1, 1),
+                month => NaiveDate::from_ymd(date.year(), month + 1, 1),
+            };
+            (start, next_month.pred())
+        }
+    })
+}
+
+/// met goals are green, partially met ones yellow and days without any tracked time red. days
+/// without a goal and days which aren't over yet aren't colored.
+fn style(work_time: Duration, goal: Duration, over: bool) -> Option<Style> {
+    if goal <= Duration::zero() {
+        return iif!(work_time > Duration::zero(), Some(Style::Active), None);
+    }
+    if work_time >= goal {
+        Some(Style::Active)
+    } else if !over {
+        None
+    } else if work_time > Duration::zero() {
+        Some(Style::Warning)
+    } else {
+        Some(Style::Error)
+    }
+}
+
+fn cell(text: &str, style: Option<Style>) -> String {
+    let text = format!("{:>width$}", text, width = CELL_WIDTH);
+    match style {
+        Some(style) => paint(text, style),
+        None => text,
+    }
+}
+
+pub fn calendar(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    period: CalendarPeriod,
+    date: &Option<String>,
+    options: CalculationOptions,
+) -> Result<()> {
+    let (first_day, last_day) = range(settings, period, date)?;
+    let mut work_times = HashMap::new();
+    for day in get_data_as_days(data) {
+        if let Some(event) = day.first() {
+            let work_time = summarize_day(settings, &day, options).work_time;
+            let total = work_times
+                .entry(event.local_date())
+                .or_insert_with(Duration::zero);
+            *total += work_time;
+        }
+    }
+
+    let title = match period {
+        CalendarPeriod::Month => first_day.format("%B %Y").to_string(),
+        CalendarPeriod::Week => format!(
+            "{} - {}",
+            first_day.format("%Y-%m-%d"),
+            last_day.format("%Y-%m-%d")
+        ),
+    };
+    println!(
+        "{}",
+        paint(
+            format!("{:^width$}", title, width = CELL_WIDTH * 7),
+            Style::Header
+        )
+    );
+    let start = settings.start_of_week(first_day);
+    let header: String = (0..7)
+        .map(|day| {
+            let weekday = (start + Duration::days(day)).format("%a").to_string();
+            format!("{:>width$}", weekday, width = CELL_WIDTH)
+        })
+        .collect();
+    println!("{}", header);
+
+    let today = Local::today().naive_local();
+    let mut week_start = start;
+    while week_start <= last_day {
+        let days: Vec<_> = (0..7)
+            .map(|day| week_start + Duration::days(day))
+            .map(|date| iif!(date < first_day || date > last_day, None, Some(date)))
+            .collect();
+        let mut numbers = String::new();
+        let mut hours = String::new();
+        for date in days {
+            let date = match date {
+                Some(date) => date,
+                None => {
+                    numbers.push_str(&cell("", None));
+                    hours.push_str(&cell("", None));
+                    continue;
+                }
+            };
+            let work_time = work_times
+                .get(&date)
+                .copied()
+                .unwrap_or_else(Duration::zero);
+            let style = style(work_time, settings.goal_for_date(date), date < today);
+            numbers.push_str(&cell(&date.day().to_string(), style));
+            let text = iif!(
+                work_time > Duration::zero(),
+                format_hours_minutes(work_time),
+                "-".to_string()
+            );
+            hours.push_str(&cell(&text, style));
+        }
+        println!("{}", numbers);
+        println!("{}", hours);
+        week_start += Duration::weeks(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style() {
+        let hours = Duration::hours;
+        assert_eq!(Some(Style::Active), style(hours(8), hours(8), true));
+        assert_eq!(Some(Style::Warning), style(hours(4), hours(8), true));
+        assert_eq!(Some(Style::Error), style(hours(0), hours(8), true));
+        assert_eq!(None, style(hours(4), hours(8), false));
+        assert_eq!(None, style(hours(0), hours(0), true));
+    }
+}
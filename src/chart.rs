@@ -0,0 +1,213 @@
+//! Charts of the tracked time for `tt chart`, which is only available with the chart feature: a
+//! bar chart of the hours per day next to a pie chart of the time per project. The extension of
+//! the output file decides whether it is written as svg or png.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::settings::Settings;
+use crate::{
+    filter_events, get_data_as_days, summarize_day, CalculationOptions, FilterData, TrackingEvent,
+};
+
+/// the size of the whole image in pixels
+const SIZE: (u32, u32) = (1200, 500);
+
+/// the colors of the projects, repeated if there are more projects
+const COLORS: [RGBColor; 8] = [
+    RGBColor(31, 119, 180),
+    RGBColor(255, 127, 14),
+    RGBColor(44, 160, 44),
+    RGBColor(214, 39, 40),
+    RGBColor(148, 103, 189),
+    RGBColor(140, 86, 75),
+    RGBColor(227, 119, 194),
+    RGBColor(127, 127, 127),
+];
+
+/// the entries in `range` (e.g. "month"), like the filter of `tt show`
+pub fn filter(range: &str, project: &Option<String>) -> FilterData {
+    FilterData {
+        filter: Some(range.to_string()),
+        project: project.clone(),
+        ..FilterData::default()
+    }
+}
+
+fn hours(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / 3600.0
+}
+
+/// the work time of every day from the first to the last day of the data, including the days
+/// without any tracked time in between
+fn hours_per_day(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<(NaiveDate, f64)> {
+    let mut work_times = BTreeMap::new();
+    for day in get_data_as_days(data) {
+        if let Some(event) = day.first() {
+            let work_time = summarize_day(settings, &day, options).work_time;
+            *work_times.entry(event.local_date()).or_insert(0.0) += hours(work_time);
+        }
+    }
+    let (mut date, last) = match (work_times.keys().next(), work_times.keys().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Vec::new(),
+    };
+    let mut days = Vec::new();
+    while date <= last {
+        days.push((date, work_times.get(&date).copied().unwrap_or(0.0)));
+        date = date.succ();
+    }
+    days
+}
+
+/// the hours of every project, rounded with the rounding of the project. the biggest comes first.
+fn hours_per_project(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<(String, f64)> {
+    let include_seconds = options.include_seconds;
+    let mut projects: Vec<(String, f64)> = Vec::new();
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let stop = data_iterator
+            .find(|e| e.is_stop())
+            .map_or_else(Utc::now, |stop| stop.time(include_seconds));
+        let project = &start.data().project;
+        let mut duration =
+            match options.limit_interval(settings, stop - start.time(include_seconds)) {
+                Some(duration) => duration,
+                None => continue,
+            };
+        if !options.no_round {
+            duration = settings.for_project(project).rounding.apply(duration);
+        }
+        let name = project.clone().unwrap_or_else(|| "no project".to_string());
+        match projects.iter_mut().find(|(p, _)| *p == name) {
+            Some((_, total)) => *total += hours(duration),
+            None => projects.push((name, hours(duration))),
+        }
+    }
+    projects.sort_by(|a, b| b.1.total_cmp(&a.1));
+    projects
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    days: &[(NaiveDate, f64)],
+    projects: &[(String, f64)],
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let (left, right) = root.split_horizontally(SIZE.0 * 2 / 3);
+
+    let title = match (days.first(), days.last()) {
+        (Some((first, _)), Some((last, _))) if first != last => format!(
+            "Hours per day, {} - {}",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d")
+        ),
+        _ => "Hours per day".to_string(),
+    };
+    let max = days.iter().map(|(_, hours)| *hours).fold(1.0, f64::max);
+    let mut chart = ChartBuilder::on(&left)
+        .caption(title, ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        // the segmented range includes its end
+        .build_cartesian_2d((0..days.len() as u32 - 1).into_segmented(), 0.0..max * 1.1)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .y_desc("Hours")
+        .x_labels(days.len().min(16))
+        .x_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(i) => days
+                .get(*i as usize)
+                .map_or_else(String::new, |(date, _)| date.format("%m-%d").to_string()),
+            _ => String::new(),
+        })
+        .draw()?;
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(COLORS[0].filled())
+            .margin(2)
+            .data(
+                days.iter()
+                    .enumerate()
+                    .map(|(i, (_, hours))| (i as u32, *hours)),
+            ),
+    )?;
+
+    let right = right.titled("Time per project", ("sans-serif", 24))?;
+    if !projects.is_empty() {
+        // the pie is drawn in pixels of the whole image
+        let (x, y) = right.get_base_pixel();
+        let (width, height) = right.dim_in_pixel();
+        let center = (x + width as i32 / 2, y + height as i32 / 2);
+        let radius = f64::from(width.min(height)) * 0.25;
+        let sizes: Vec<_> = projects.iter().map(|(_, hours)| *hours).collect();
+        let colors: Vec<_> = (0..projects.len())
+            .map(|i| COLORS[i % COLORS.len()])
+            .collect();
+        let labels: Vec<_> = projects
+            .iter()
+            .map(|(project, hours)| format!("{} ({:.1}h)", project, hours))
+            .collect();
+        let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+        pie.start_angle(-90.0);
+        pie.label_style(("sans-serif", 14).into_font().color(&BLACK));
+        right.draw(&pie)?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+/// draws the chart of the entries matching `filter` into `output`
+pub fn chart(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    output: &Path,
+    options: CalculationOptions,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let days = hours_per_day(settings, &filtered_data, options);
+    if days.is_empty() {
+        return Err(anyhow!("There are no entries to draw!"));
+    }
+    let projects = hours_per_project(settings, &filtered_data, options);
+    let result = match output.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => draw(
+            SVGBackend::new(output, SIZE).into_drawing_area(),
+            &days,
+            &projects,
+        ),
+        Some("png") => draw(
+            BitMapBackend::new(output, SIZE).into_drawing_area(),
+            &days,
+            &projects,
+        ),
+        _ => {
+            return Err(anyhow!(
+                "invalid output \"{}\", expected a file ending in .svg or .png",
+                output.display()
+            ))
+        }
+    };
+    result.map_err(|e| anyhow!("could not draw the chart: {}", e))?;
+    println!("Wrote the chart to {}", output.display());
+    Ok(())
+}
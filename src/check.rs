@@ -0,0 +1,159 @@
+//! Validation of the data, e.g. to notice problems after a sync or after editing an export.
+
+use anyhow::{anyhow, Result};
+use chrono::Duration;
+
+use std::fmt;
+
+use crate::settings::Settings;
+use crate::{format_hours_minutes, to_human_readable, TrackingEvent};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Problem {
+    UnmatchedStart,
+    StopWithoutStart,
+    StopBeforeStart,
+    ZeroLength,
+    TooLong(Duration),
+    DuplicateTime,
+    OutOfOrder,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnmatchedStart => write!(f, "start without a stop"),
+            Self::StopWithoutStart => write!(f, "stop without a start"),
+            Self::StopBeforeStart => write!(f, "stop before its start"),
+            Self::ZeroLength => write!(f, "interval without length"),
+            Self::TooLong(duration) => {
+                write!(f, "interval of {} hours", format_hours_minutes(*duration))
+            }
+            Self::DuplicateTime => write!(f, "same time as the previous event"),
+            Self::OutOfOrder => write!(f, "before the previous event"),
+        }
+    }
+}
+
+/// returns the problems in the data with the number of the event they belong to. intervals
+/// longer than `max_hours` are reported, unless it is 0.
+fn find_problems(data: &[TrackingEvent], max_hours: u32) -> Vec<(usize, Problem)> {
+    let mut problems = Vec::new();
+    let mut running: Option<usize> = None;
+    for (i, event) in data.iter().enumerate() {
+        let time = event.time(true);
+        let mut stops_previous = false;
+        if event.is_start() {
+            if let Some(start) = running {
+                problems.push((start, Problem::UnmatchedStart));
+            }
+            running = Some(i);
+        } else {
+            match running.take() {
+                None => problems.push((i, Problem::StopWithoutStart)),
+                Some(start) => {
+                    stops_previous = start + 1 == i;
+                    let duration = time - data[start].time(true);
+                    if duration < Duration::zero() {
+                        problems.push((i, Problem::StopBeforeStart));
+                    } else if duration == Duration::zero() {
+                        problems.push((start, Problem::ZeroLength));
+                    } else if max_hours > 0 && duration > Duration::hours(i64::from(max_hours)) {
+                        problems.push((start, Problem::TooLong(duration)));
+                    }
+                }
+            }
+        }
+
+        let previous = match i.checked_sub(1) {
+            Some(previous) => data[previous].time(true),
+            None => continue,
+        };
+        // the time of a stop right after its start is already checked above
+        if time < previous && !stops_previous {
+            problems.push((i, Problem::OutOfOrder));
+        } else if time == previous && !stops_previous {
+            problems.push((i, Problem::DuplicateTime));
+        }
+    }
+    problems.sort_by_key(|(i, _)| *i);
+    problems
+}
+
+/// prints the problems in the data. returns an error if there are any, so scripts can notice them.
+pub fn check(settings: &Settings, data: &[TrackingEvent], max_hours: Option<u32>) -> Result<()> {
+    let problems = find_problems(data, max_hours.unwrap_or(settings.max_session_hours));
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+
+    let width = problems
+        .iter()
+        .map(|(i, _)| i.to_string().len())
+        .max()
+        .unwrap_or_default();
+    for (i, problem) in &problems {
+        let event = &data[*i];
+        println!(
+            "({:>width$}) {}: {}",
+            i,
+            problem,
+            to_human_readable(event.name(), event.data()),
+            width = width
+        );
+    }
+    Err(anyhow!("Found {} problems in the data!", problems.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    fn start(h: u32, m: u32) -> TrackingEvent {
+        TrackingEvent::Start(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, m, 0),
+        ))
+    }
+
+    fn stop(h: u32, m: u32) -> TrackingEvent {
+        TrackingEvent::Stop(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, m, 0),
+        ))
+    }
+
+    #[test]
+    fn test_find_problems() {
+        assert!(find_problems(&[start(8, 0), stop(9, 0), start(10, 0)], 12).is_empty());
+
+        let data = vec![
+            stop(1, 0),
+            start(2, 0),
+            start(3, 0),
+            stop(3, 0),
+            start(4, 0),
+            stop(17, 0),
+            start(18, 0),
+            stop(17, 30),
+            start(19, 0),
+            stop(20, 0),
+            stop(20, 0),
+        ];
+        assert_eq!(
+            vec![
+                (0, Problem::StopWithoutStart),
+                (1, Problem::UnmatchedStart),
+                (2, Problem::ZeroLength),
+                (4, Problem::TooLong(Duration::hours(13))),
+                (7, Problem::StopBeforeStart),
+                (10, Problem::StopWithoutStart),
+                (10, Problem::DuplicateTime),
+            ],
+            find_problems(&data, 12)
+        );
+    }
+}
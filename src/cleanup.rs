@@ -0,0 +1,298 @@
+//! Resolving repeated start or stop events, e.g. after forgetting to stop before starting again
+//! or after merging the changes of two machines.
+
+use anyhow::{anyhow, Result};
+use iif::iif;
+
+use std::io;
+use std::str::FromStr;
+
+use crate::{to_human_readable, TrackingEvent};
+
+/// how repeated events get resolved without asking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupStrategy {
+    KeepFirst,
+    KeepLast,
+    Merge,
+    Drop,
+}
+
+impl FromStr for CleanupStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keep-first" => Ok(Self::KeepFirst),
+            "keep-last" => Ok(Self::KeepLast),
+            "merge" => Ok(Self::Merge),
+            "drop" => Ok(Self::Drop),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: keep-first, keep-last, merge, drop",
+                s
+            )),
+        }
+    }
+}
+
+/// combines repeated events into one. repeated starts become the first start and repeated stops
+/// the last stop, with the description and project of the other events if it has none and the
+/// tags of all of them.
+fn merge(conflicting: &[&TrackingEvent]) -> TrackingEvent {
+    let mut events = conflicting.to_vec();
+    if events[0].is_stop() {
+        events.reverse();
+    }
+    let mut merged = events[0].clone();
+    let data = merged.data_mut();
+    for event in &events[1..] {
+        let other = event.data();
+        if data.description.is_none() {
+            data.description = other.description.clone();
+        }
+        if data.project.is_none() {
+            data.project = other.project.clone();
+        }
+        for tag in &other.tags {
+            if !data.tags.contains(tag) {
+                data.tags.push(tag.clone());
+            }
+        }
+        for note in &other.notes {
+            if !data.notes.contains(note) {
+                data.notes.push(note.clone());
+            }
+        }
+    }
+    merged
+}
+
+impl CleanupStrategy {
+    /// the events which are kept of repeated start or stop events
+    fn resolve(self, conflicting: &[&TrackingEvent]) -> Vec<TrackingEvent> {
+        match self {
+            Self::KeepFirst => conflicting
+                .first()
+                .map(|e| (*e).clone())
+                .into_iter()
+                .collect(),
+            Self::KeepLast => conflicting
+                .last()
+                .map(|e| (*e).clone())
+                .into_iter()
+                .collect(),
+            Self::Merge => vec![merge(conflicting)],
+            Self::Drop => Vec::new(),
+        }
+    }
+}
+
+/// whether two start or two stop events follow each other, which has to be cleaned up
+pub fn has_conflicts(data: &[TrackingEvent]) -> bool {
+    data.windows(2)
+        .any(|pair| pair[0].is_start() == pair[1].is_start())
+}
+
+/// asks which of the repeated events to keep. keeps all of them, if the answer is skip.
+fn ask(conflicting: &[&TrackingEvent]) -> Vec<TrackingEvent> {
+    loop {
+        println!();
+        println!("Please enter the number of the entry to keep (<num>|skip) [default: skip]: ");
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let text = input.trim();
+                if text == "skip" || text.is_empty() {
+                    return conflicting.iter().map(|e| (*e).clone()).collect();
+                } else {
+                    let parsed: Result<usize, _> = text.parse();
+                    match parsed {
+                        Ok(n) => match conflicting.get(n) {
+                            Some(value) => return vec![(*value).clone()],
+                            None => println!("Please use one of the numbers given above!"),
+                        },
+                        Err(_) => println!("Could not parse number!"),
+                    }
+                }
+            }
+            Err(_) => println!("Could not read from stdin!"),
+        }
+    }
+}
+
+/// prints which of the repeated events were kept and which were removed
+fn print_resolution(conflicting: &[&TrackingEvent], kept: &[TrackingEvent]) {
+    for event in conflicting {
+        let action = iif!(kept.contains(event), "keep  ", "remove");
+        println!(
+            "  {} {}",
+            action,
+            to_human_readable(event.name(), event.data())
+        );
+    }
+    for event in kept.iter().filter(|e| !conflicting.contains(e)) {
+        println!("  add    {}", to_human_readable(event.name(), event.data()));
+    }
+}
+
+/// the events without conflicts and the groups of repeated start or stop events
+fn find_conflicts(data: &[TrackingEvent]) -> (Vec<&TrackingEvent>, Vec<Vec<&TrackingEvent>>) {
+    let mut cleaned = Vec::with_capacity(data.len());
+
+    let mut conflicting = Vec::new();
+
+    let mut is_start = None;
+
+    let mut all_conflicting = Vec::new();
+
+    for e in data {
+        match is_start {
+            None => {
+                is_start = Some(e.is_start());
+                cleaned.push(e);
+            }
+            Some(true) => {
+                if e.is_start() {
+                    if conflicting.is_empty() {
+                        if let Some(last_cleaned_e) = cleaned.pop() {
+                            conflicting.push(last_cleaned_e);
+                        }
+                    }
+                    conflicting.push(e);
+                } else {
+                    if !conflicting.is_empty() {
+                        all_conflicting.push(conflicting);
+                        conflicting = Vec::new();
+                    }
+                    cleaned.push(e);
+                    is_start.replace(false);
+                }
+            }
+            Some(false) => {
+                if e.is_stop() {
+                    if conflicting.is_empty() {
+                        if let Some(last_cleaned_e) = cleaned.pop() {
+                            conflicting.push(last_cleaned_e);
+                        }
+                    }
+                    conflicting.push(e);
+                } else {
+                    if !conflicting.is_empty() {
+                        all_conflicting.push(conflicting);
+                        conflicting = Vec::new();
+                    }
+                    cleaned.push(e);
+                    is_start.replace(true);
+                }
+            }
+        }
+    }
+    if !conflicting.is_empty() {
+        all_conflicting.push(conflicting);
+    }
+    (cleaned, all_conflicting)
+}
+
+/// resolves repeated start or stop events with `strategy` or by asking which one to keep
+pub fn cleanup(data: &[TrackingEvent], strategy: Option<CleanupStrategy>) -> Vec<TrackingEvent> {
+    let (cleaned, all_conflicting) = find_conflicts(data);
+    let mut cleaned: Vec<_> = cleaned.into_iter().cloned().collect();
+    let mut removed = 0;
+    for conflicting in &all_conflicting {
+        let event_type = iif!(
+            conflicting
+                .first()
+                .expect("Nothing first tracking event founded")
+                .is_start(),
+            "start",
+            "stop"
+        );
+        println!("Repeated {} events found:", event_type);
+        let kept = match strategy {
+            Some(strategy) => {
+                let kept = strategy.resolve(conflicting);
+                print_resolution(conflicting, &kept);
+                kept
+            }
+            None => {
+                for (i, event) in conflicting.iter().enumerate() {
+                    println!("({}) {}", i, to_human_readable(event.name(), event.data()));
+                }
+                ask(conflicting)
+            }
+        };
+        removed += conflicting.len().saturating_sub(kept.len());
+        cleaned.extend(kept);
+    }
+
+    if strategy.is_some() {
+        println!(
+            "Resolved {} conflicts, removing {} events.",
+            all_conflicting.len(),
+            removed
+        );
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    fn event(start: bool, h: u32, description: Option<&str>, tags: &[&str]) -> TrackingEvent {
+        let data = TrackingData {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..TrackingData::new(
+                description.map(String::from),
+                Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+            )
+        };
+        iif!(start, TrackingEvent::Start(data), TrackingEvent::Stop(data))
+    }
+
+    #[test]
+    fn test_cleanup_strategies() {
+        let data = vec![
+            event(true, 8, None, &["a"]),
+            event(true, 9, Some("b"), &["b"]),
+            event(false, 10, None, &[]),
+            event(false, 11, Some("c"), &[]),
+        ];
+
+        let cleaned = cleanup(&data, Some(CleanupStrategy::KeepFirst));
+        assert_eq!(vec![data[0].clone(), data[2].clone()], cleaned);
+
+        let cleaned = cleanup(&data, Some(CleanupStrategy::KeepLast));
+        assert_eq!(vec![data[1].clone(), data[3].clone()], cleaned);
+
+        let cleaned = cleanup(&data, Some(CleanupStrategy::Merge));
+        assert_eq!(
+            vec![event(true, 8, Some("b"), &["a", "b"]), data[3].clone()],
+            cleaned
+        );
+
+        assert!(cleanup(&data, Some(CleanupStrategy::Drop)).is_empty());
+        assert!("keep".parse::<CleanupStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let start = |h| event(true, h, None, &[]);
+        let stop = |h| event(false, h, None, &[]);
+        let data = vec![start(8), start(9), stop(10), start(11), stop(12), stop(13)];
+        let (cleaned, conflicts) = find_conflicts(&data);
+        assert_eq!(vec![&data[2], &data[3]], cleaned);
+        assert_eq!(
+            vec![vec![&data[0], &data[1]], vec![&data[4], &data[5]]],
+            conflicts
+        );
+
+        // repeated events at the end of the data aren't lost
+        let data = vec![start(8), stop(9), start(10), start(11)];
+        let (cleaned, conflicts) = find_conflicts(&data);
+        assert_eq!(vec![&data[0], &data[1]], cleaned);
+        assert_eq!(vec![vec![&data[2], &data[3]]], conflicts);
+    }
+}
@@ -0,0 +1,134 @@
+//! Detecting jumps of the system clock, e.g. after an NTP correction or a changed timezone, when
+//! new events are written. If the last stored event is in the future, the events made now would be
+//! sorted in before it and the durations would be negative, so they are moved after it instead,
+//! kept with a warning or refused, depending on the `clock_jumps` setting.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use iif::iif;
+use serde::Deserialize;
+
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::settings::Settings;
+use crate::TrackingEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockJumps {
+    /// ask whether to move or keep the events, refuse them without a terminal
+    Ask,
+    /// keep the times of the events
+    Keep,
+    /// move the events to the time of the last stored event
+    Move,
+}
+
+static ALLOW_OUT_OF_ORDER: AtomicBool = AtomicBool::new(false);
+
+/// with `--allow-out-of-order`, the events keep their times without asking
+pub fn init(allow_out_of_order: bool) {
+    ALLOW_OUT_OF_ORDER.store(allow_out_of_order, Ordering::Relaxed);
+}
+
+/// the time of the last stored event, if it is after `now`, and the positions of the added events
+/// which are before it. only events added at the end count, events inserted with `--at` already
+/// are where they belong
+fn out_of_order(
+    original_data: &[TrackingEvent],
+    data: &[TrackingEvent],
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, Vec<usize>)> {
+    if !data.starts_with(original_data) {
+        return None;
+    }
+    let last = original_data.last()?.time(true);
+    if last <= now {
+        return None;
+    }
+    let positions: Vec<_> = (original_data.len()..data.len())
+        .filter(|i| data[*i].time(true) < last)
+        .collect();
+    iif!(positions.is_empty(), None, Some((last, positions)))
+}
+
+/// what to do with the events, asked in a terminal
+fn ask() -> Result<ClockJumps> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Use --allow-out-of-order to add the events anyway, or set clock_jumps to \"move\" or \"keep\" in the config."
+        ));
+    }
+    println!("Move the new events after it (m), keep their times (k) or abort (a)? [default: m]: ");
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    match input.trim() {
+        "" | "m" | "move" => Ok(ClockJumps::Move),
+        "k" | "keep" => Ok(ClockJumps::Keep),
+        _ => Err(anyhow!("Nothing changed.")),
+    }
+}
+
+/// checks the events added to `original_data` before they are written
+pub fn resolve(
+    settings: &Settings,
+    original_data: &[TrackingEvent],
+    data: &mut [TrackingEvent],
+) -> Result<()> {
+    let (last, positions) = match out_of_order(original_data, data, Utc::now()) {
+        Some(out_of_order) => out_of_order,
+        None => return Ok(()),
+    };
+    eprintln!(
+        "Warning: The last entry is at {}, which is in the future. Did the system clock jump back?",
+        last.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+    );
+    let action = if ALLOW_OUT_OF_ORDER.load(Ordering::Relaxed) {
+        ClockJumps::Keep
+    } else {
+        match settings.clock_jumps {
+            ClockJumps::Ask => ask()?,
+            action => action,
+        }
+    };
+    if action == ClockJumps::Move {
+        for i in positions {
+            data[i].data_mut().time = last;
+        }
+        println!(
+            "Moved the new entries to {}.",
+            last.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::Duration;
+
+    #[test]
+    fn test_out_of_order() {
+        let now = Utc.ymd(2021, 4, 1).and_hms(10, 0, 0);
+        let start = TrackingEvent::Start(TrackingData::new(None, now + Duration::hours(1)));
+        let stop = TrackingEvent::Stop(TrackingData::new(None, now));
+        let original_data = vec![start.clone()];
+
+        let data = vec![start.clone(), stop.clone()];
+        assert_eq!(
+            Some((start.time(true), vec![1])),
+            out_of_order(&original_data, &data, now)
+        );
+        // the last entry is not in the future, so the clock didn't jump
+        assert_eq!(
+            None,
+            out_of_order(&original_data, &data, now + Duration::hours(2))
+        );
+        // inserted with --at
+        let data = vec![stop, start];
+        assert_eq!(None, out_of_order(&original_data, &data, now));
+    }
+}
@@ -0,0 +1,84 @@
+//! Colored terminal output. Everything that gets colored goes through `paint`, which leaves the
+//! text alone if colors are disabled by the `color` setting, `NO_COLOR` or a redirected stdout.
+
+use owo_colors::OwoColorize;
+use serde::Deserialize;
+
+use std::fmt::Display;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// what the colored text means
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// running time tracking or a reached goal
+    Active,
+    /// something which needs attention, like a missing stop or a missed goal
+    Error,
+    /// a partially met goal
+    Warning,
+    /// entries of previous days
+    Dim,
+    /// headers and totals
+    Header,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// `always` and `never` override NO_COLOR, like a command line flag would
+fn enabled(choice: ColorChoice, no_color: bool, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color && terminal,
+    }
+}
+
+pub fn init(choice: ColorChoice) {
+    let no_color = matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty());
+    ENABLED.store(
+        enabled(choice, no_color, io::stdout().is_terminal()),
+        Ordering::Relaxed,
+    );
+}
+
+fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// the text with the color of `style`, or the plain text if colors are disabled. padding has to
+/// be applied before, because the escape codes count as characters.
+pub fn paint<T: Display>(text: T, style: Style) -> String {
+    if !is_enabled() {
+        return text.to_string();
+    }
+    match style {
+        Style::Active => text.green().to_string(),
+        Style::Error => text.red().to_string(),
+        Style::Warning => text.yellow().to_string(),
+        Style::Dim => text.dimmed().to_string(),
+        Style::Header => text.bold().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled() {
+        assert!(enabled(ColorChoice::Auto, false, true));
+        assert!(!enabled(ColorChoice::Auto, true, true));
+        assert!(!enabled(ColorChoice::Auto, false, false));
+        assert!(enabled(ColorChoice::Always, true, false));
+        assert!(!enabled(ColorChoice::Never, false, true));
+    }
+}
@@ -0,0 +1,280 @@
+//! Shell completion, which suggests the recently used descriptions for `tt start`, so long ticket
+//! names don't have to be typed again. The values of `--project`, `--profile` and the filter of
+//! commands like `tt show` are completed with the output of the hidden `tt complete` command, so
+//! the projects and profiles from the config and the data file are suggested as well.
+
+use anyhow::{anyhow, Result};
+use structopt::clap::{App, Shell};
+
+use std::str::FromStr;
+
+use crate::settings::Settings;
+use crate::TrackingEvent;
+
+/// how many recent descriptions the completion suggests
+const SUGGESTIONS: usize = 50;
+
+/// the keywords of the filter of commands like `tt show`
+const FILTER_KEYWORDS: [&str; 8] = [
+    "today",
+    "yesterday",
+    "week",
+    "last-week",
+    "month",
+    "last-month",
+    "year",
+    "all",
+];
+
+/// the commands with a filter, whose first argument gets completed with the filter keywords
+const FILTER_COMMANDS: &str = "show list report summary overtime earnings stats delete";
+
+#[derive(Debug, Clone, Copy)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for CompletionShell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: bash, zsh, fish",
+                s
+            )),
+        }
+    }
+}
+
+/// what `tt complete` lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionValues {
+    Projects,
+    Profiles,
+    Filters,
+}
+
+impl FromStr for CompletionValues {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "projects" => Ok(Self::Projects),
+            "profiles" => Ok(Self::Profiles),
+            "filters" => Ok(Self::Filters),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: projects, profiles, filters",
+                s
+            )),
+        }
+    }
+}
+
+/// the values the completion suggests: the projects of the data file, the most recent first,
+/// followed by the ones only defined in the config, the profiles of the config or the filter
+/// keywords
+pub fn values(settings: &Settings, data: &[TrackingEvent], kind: CompletionValues) -> Vec<String> {
+    match kind {
+        CompletionValues::Projects => {
+            let mut projects = recent(data, usize::MAX, true);
+            let mut configured: Vec<_> = settings
+                .projects
+                .keys()
+                .filter(|project| !projects.contains(project))
+                .cloned()
+                .collect();
+            configured.sort();
+            projects.extend(configured);
+            projects
+        }
+        CompletionValues::Profiles => {
+            let mut profiles: Vec<_> = settings.profiles.keys().cloned().collect();
+            profiles.sort();
+            profiles
+        }
+        CompletionValues::Filters => FILTER_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+    }
+}
+
+/// the last `number` distinct descriptions or projects of the started events, the most recent
+/// first
+pub fn recent(data: &[TrackingEvent], number: usize, projects: bool) -> Vec<String> {
+    let mut recent: Vec<String> = Vec::new();
+    for event in data.iter().rev().filter(|e| e.is_start()) {
+        if recent.len() >= number {
+            break;
+        }
+        let value = if projects {
+            &event.data().project
+        } else {
+            &event.data().description
+        };
+        if let Some(value) = value {
+            if !recent.contains(value) {
+                recent.push(value.clone());
+            }
+        }
+    }
+    recent
+}
+
+/// completes the description of `tt start` with the output of `tt recent` and everything else
+/// with the generated completion
+fn dynamic_completion(shell: CompletionShell) -> String {
+    match shell {
+        CompletionShell::Bash => format!(
+            r#"
+_tt_recent() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local kind=""
+    case "$prev" in
+        --project) kind=projects ;;
+        --profile) kind=profiles ;;
+        # -p is the profile before the command and the project of `tt start` after it
+        -p) if [[ $COMP_CWORD -eq 2 ]]; then kind=profiles; else kind=projects; fi ;;
+    esac
+    if [[ -z "$kind" && $COMP_CWORD -eq 2 && "$cur" != -* && " {commands} " == *" ${{COMP_WORDS[1]}} "* ]]; then
+        kind=filters
+    fi
+    if [[ -n "$kind" ]]; then
+        local IFS=$'\n'
+        COMPREPLY=($(tt complete "$kind" 2>/dev/null | while read -r line; do
+            [[ "$line" == "$cur"* ]] && printf '%q\n' "$line"
+        done))
+        return 0
+    fi
+    if [[ "${{COMP_WORDS[1]}}" == "start" && "$cur" != -* ]]; then
+        local IFS=$'\n'
+        COMPREPLY=($(tt recent --number {suggestions} 2>/dev/null | while read -r line; do
+            [[ "$line" == "$cur"* ]] && printf '%q\n' "$line"
+        done))
+        return 0
+    fi
+    _tt "$@"
+}}
+
+complete -F _tt_recent -o bashdefault -o default tt
+"#,
+            suggestions = SUGGESTIONS,
+            commands = FILTER_COMMANDS
+        ),
+        CompletionShell::Zsh => format!(
+            r#"
+_tt_recent() {{
+    local kind
+    case ${{words[CURRENT-1]}} in
+        --project) kind=projects ;;
+        --profile) kind=profiles ;;
+        # -p is the profile before the command and the project of `tt start` after it
+        -p) if (( CURRENT == 3 )); then kind=profiles; else kind=projects; fi ;;
+    esac
+    if [[ -z $kind && $CURRENT -eq 3 && $PREFIX != -* && " {commands} " == *" ${{words[2]}} "* ]]; then
+        kind=filters
+    fi
+    if [[ -n $kind ]]; then
+        local -a values
+        values=("${{(@f)$(tt complete $kind 2>/dev/null)}}")
+        compadd -a values && return 0
+    fi
+    if [[ $CURRENT -gt 2 && ${{words[2]}} == start && $PREFIX != -* ]]; then
+        local -a descriptions
+        descriptions=("${{(@f)$(tt recent --number {suggestions} 2>/dev/null)}}")
+        compadd -a descriptions && return 0
+    fi
+    _tt_generated "$@"
+}}
+
+compdef _tt_recent tt
+"#,
+            suggestions = SUGGESTIONS,
+            commands = FILTER_COMMANDS
+        ),
+        CompletionShell::Fish => format!(
+            r#"
+complete -c tt -n "__fish_seen_subcommand_from start" -f -a "(tt recent --number {suggestions} 2>/dev/null)"
+complete -c tt -l project -x -a "(tt complete projects 2>/dev/null)"
+complete -c tt -l profile -x -a "(tt complete profiles 2>/dev/null)"
+complete -c tt -n "__fish_seen_subcommand_from {commands}" -f -a "(tt complete filters 2>/dev/null)"
+"#,
+            suggestions = SUGGESTIONS,
+            commands = FILTER_COMMANDS
+        ),
+    }
+}
+
+/// the completion script for `shell`
+fn script(mut app: App, shell: CompletionShell) -> String {
+    let generated_shell = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+    };
+    let mut generated = Vec::new();
+    app.gen_completions_to("tt", generated_shell, &mut generated);
+    let mut generated = String::from_utf8_lossy(&generated).to_string();
+    match shell {
+        CompletionShell::Bash => {
+            // replaced by the registration in the dynamic part
+            generated = generated.replace("complete -F _tt -o bashdefault -o default tt", "");
+        }
+        CompletionShell::Zsh => {
+            // the generated function gets wrapped, so the script can still be autoloaded as _tt
+            generated = generated
+                .replace("_tt() {", "_tt_generated() {")
+                .replace("\n_tt \"$@\"", "");
+        }
+        CompletionShell::Fish => {}
+    }
+    let mut script = generated.trim_end().to_string();
+    script.push('\n');
+    script.push_str(&dynamic_completion(shell));
+    if let CompletionShell::Zsh = shell {
+        script.push_str("\n_tt_recent \"$@\"\n");
+    }
+    script
+}
+
+/// prints the completion script for `shell`
+pub fn completions(app: App, shell: CompletionShell) {
+    print!("{}", script(app, shell));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    fn start(h: u32, description: &str, project: Option<&str>) -> TrackingEvent {
+        TrackingEvent::Start(TrackingData {
+            project: project.map(String::from),
+            ..TrackingData::new(
+                Some(description.to_string()),
+                Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+            )
+        })
+    }
+
+    #[test]
+    fn test_recent() {
+        let data = vec![
+            start(8, "TICKET-1 login", Some("website")),
+            start(9, "TICKET-2 logout", None),
+            start(10, "TICKET-1 login", Some("website")),
+            start(11, "meeting", Some("internal")),
+        ];
+        assert_eq!(vec!["meeting", "TICKET-1 login"], recent(&data, 2, false));
+        assert_eq!(
+            vec!["meeting", "TICKET-1 login", "TICKET-2 logout"],
+            recent(&data, 10, false)
+        );
+        assert_eq!(vec!["internal", "website"], recent(&data, 10, true));
+    }
+}
@@ -0,0 +1,316 @@
+//! A weekly job which creates a report and writes it to a file or mails it, e.g. to have the
+//! timesheet ready every friday. The job is a systemd timer on linux, a launchd agent on macOS
+//! and a scheduled task on windows, which all run `tt cron run`.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use structopt::StructOpt;
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::settings::Settings;
+
+/// the name of the systemd units and the scheduled task
+const JOB_NAME: &str = "tt-report";
+
+/// the label of the launchd agent
+const LAUNCHD_LABEL: &str = "com.github.hardliner66.timetracking.report";
+
+#[derive(Debug, StructOpt)]
+pub enum CronCommand {
+    /// register the weekly job with the scheduler of the system
+    Install,
+
+    /// remove the weekly job
+    Uninstall,
+
+    /// create the report now and write or mail it like the weekly job does
+    Run,
+}
+
+pub fn run_command(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("could not run \"{}\"", program))?;
+    if !status.success() {
+        return Err(anyhow!("\"{}\" failed: {}", program, status));
+    }
+    Ok(())
+}
+
+fn parse_time(settings: &Settings) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(&settings.cron.time, "%H:%M").with_context(|| {
+        format!(
+            "invalid cron.time \"{}\", expected a time like \"16:00\"",
+            settings.cron.time
+        )
+    })
+}
+
+/// the command line of the job: this executable with the global options of the current call
+fn job_command(global_args: &[String]) -> Result<Vec<String>> {
+    let exe = std::env::current_exe()?;
+    let mut command = vec![exe.to_string_lossy().to_string()];
+    command.extend(global_args.iter().cloned());
+    command.extend(vec!["cron".to_string(), "run".to_string()]);
+    Ok(command)
+}
+
+pub fn home_dir() -> Result<PathBuf> {
+    Ok(PathBuf::from(shellexpand::full("~")?.to_string()))
+}
+
+/// quotes an argument for ExecStart, where % starts a specifier
+pub fn systemd_quote(arg: &str) -> String {
+    format!(
+        "\"{}\"",
+        arg.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+    )
+}
+
+fn systemd_dir() -> Result<PathBuf> {
+    Ok(home_dir()?.join(".config/systemd/user"))
+}
+
+fn systemd_calendar(day: Weekday, time: NaiveTime) -> String {
+    format!("{} *-*-* {}", day, time.format("%H:%M:00"))
+}
+
+fn install_systemd(settings: &Settings, command: &[String]) -> Result<()> {
+    let dir = systemd_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let exec_start: Vec<_> = command.iter().map(|arg| systemd_quote(arg)).collect();
+    std::fs::write(
+        dir.join(format!("{}.service", JOB_NAME)),
+        format!(
+            "[Unit]\nDescription=Weekly time tracking report\n\n[Service]\nType=oneshot\nExecStart={}\n",
+            exec_start.join(" ")
+        ),
+    )?;
+    std::fs::write(
+        dir.join(format!("{}.timer", JOB_NAME)),
+        format!(
+            "[Unit]\nDescription=Weekly time tracking report\n\n[Timer]\nOnCalendar={}\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+            systemd_calendar(settings.cron.day, parse_time(settings)?)
+        ),
+    )?;
+    run_command("systemctl", &["--user", "daemon-reload"])?;
+    run_command(
+        "systemctl",
+        &["--user", "enable", "--now", &format!("{}.timer", JOB_NAME)],
+    )
+}
+
+fn uninstall_systemd() -> Result<()> {
+    let dir = systemd_dir()?;
+    let timer = format!("{}.timer", JOB_NAME);
+    if !dir.join(&timer).exists() {
+        return Err(anyhow!("The weekly job is not installed!"));
+    }
+    run_command("systemctl", &["--user", "disable", "--now", &timer])?;
+    std::fs::remove_file(dir.join(&timer))?;
+    std::fs::remove_file(dir.join(format!("{}.service", JOB_NAME)))?;
+    run_command("systemctl", &["--user", "daemon-reload"])
+}
+
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn launchd_path() -> Result<PathBuf> {
+    Ok(home_dir()?.join(format!("Library/LaunchAgents/{}.plist", LAUNCHD_LABEL)))
+}
+
+fn install_launchd(settings: &Settings, command: &[String]) -> Result<()> {
+    let path = launchd_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let time = parse_time(settings)?;
+    let arguments: String = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape_xml(arg)))
+        .collect();
+    std::fs::write(
+        &path,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>StartCalendarInterval</key>
+    <dict>
+        <key>Weekday</key>
+        <integer>{}</integer>
+        <key>Hour</key>
+        <integer>{}</integer>
+        <key>Minute</key>
+        <integer>{}</integer>
+    </dict>
+</dict>
+</plist>
+"#,
+            LAUNCHD_LABEL,
+            arguments,
+            settings.cron.day.num_days_from_sunday(),
+            time.hour(),
+            time.minute()
+        ),
+    )?;
+    run_command("launchctl", &["load", "-w", &path.to_string_lossy()])
+}
+
+fn uninstall_launchd() -> Result<()> {
+    let path = launchd_path()?;
+    if !path.exists() {
+        return Err(anyhow!("The weekly job is not installed!"));
+    }
+    run_command("launchctl", &["unload", "-w", &path.to_string_lossy()])?;
+    Ok(std::fs::remove_file(path)?)
+}
+
+fn install_task(settings: &Settings, command: &[String]) -> Result<()> {
+    let task: Vec<_> = command.iter().map(|arg| format!("\"{}\"", arg)).collect();
+    let day = settings.cron.day.to_string().to_uppercase();
+    let time = parse_time(settings)?.format("%H:%M").to_string();
+    run_command(
+        "schtasks",
+        &[
+            "/Create",
+            "/F",
+            "/SC",
+            "WEEKLY",
+            "/D",
+            &day,
+            "/ST",
+            &time,
+            "/TN",
+            JOB_NAME,
+            "/TR",
+            &task.join(" "),
+        ],
+    )
+}
+
+fn uninstall_task() -> Result<()> {
+    run_command("schtasks", &["/Delete", "/F", "/TN", JOB_NAME])
+}
+
+/// runs tt with the report arguments from the config and returns its output
+fn create_report(settings: &Settings, global_args: &[String]) -> Result<String> {
+    if settings.cron.args.is_empty() {
+        return Err(anyhow!("Please set cron.args in the config first!"));
+    }
+    let output = Command::new(std::env::current_exe()?)
+        .args(global_args)
+        .args(&settings.cron.args)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "creating the report failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn mail(to: &str, subject: &str, report: &str) -> Result<()> {
+    let mut child = Command::new("mail")
+        .args(["-s", subject, to])
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("could not run \"mail\"")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(report.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("\"mail\" failed: {}", status));
+    }
+    Ok(())
+}
+
+fn run(settings: &Settings, global_args: &[String]) -> Result<()> {
+    let report = create_report(settings, global_args)?;
+    let today = Local::today();
+    let output = &settings.cron.output;
+    let mail_to = &settings.cron.mail_to;
+
+    if !output.is_empty() {
+        let path = shellexpand::full(&today.format(output).to_string())?.to_string();
+        if let Some(dir) = Path::new(&path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, &report)?;
+        println!("Wrote the report to \"{}\".", path);
+    }
+    if !mail_to.is_empty() {
+        let subject = format!("Time tracking report {}", today.format("%Y-%m-%d"));
+        mail(mail_to, &subject, &report)?;
+        println!("Mailed the report to {}.", mail_to);
+    }
+    if output.is_empty() && mail_to.is_empty() {
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+/// runs the cron command. `global_args` are the global options of the current call, which are
+/// passed on to the report and the job, so they use the same config and data file.
+pub fn cron(settings: &Settings, command: CronCommand, global_args: &[String]) -> Result<()> {
+    match command {
+        CronCommand::Install => {
+            let command = job_command(global_args)?;
+            if cfg!(windows) {
+                install_task(settings, &command)?;
+            } else if cfg!(target_os = "macos") {
+                install_launchd(settings, &command)?;
+            } else {
+                install_systemd(settings, &command)?;
+            }
+            println!(
+                "Installed the weekly report, which runs every {} at {}.",
+                settings.cron.day, settings.cron.time
+            );
+            Ok(())
+        }
+        CronCommand::Uninstall => {
+            if cfg!(windows) {
+                uninstall_task()?;
+            } else if cfg!(target_os = "macos") {
+                uninstall_launchd()?;
+            } else {
+                uninstall_systemd()?;
+            }
+            println!("Removed the weekly report.");
+            Ok(())
+        }
+        CronCommand::Run => run(settings, global_args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd() {
+        assert_eq!(
+            "Fri *-*-* 16:30:00",
+            systemd_calendar(Weekday::Fri, NaiveTime::from_hms(16, 30, 0))
+        );
+        assert_eq!(r#""/my \"tt\"/%%Y""#, systemd_quote(r#"/my "tt"/%Y"#));
+    }
+}
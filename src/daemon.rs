@@ -0,0 +1,470 @@
+//! `tt daemon` keeps the data in memory and listens on a unix socket next to the data file
+//! (`<data_file>.sock`). While it runs, `tt start`, `tt stop` and `tt prompt` send their request to
+//! it instead of reading and writing the file themselves, so the changes are made one after the
+//! other and the prompt is answered without reading the file. Every other command still uses the
+//! file, which stays locked while it is changed, and the daemon reads it again after such a change.
+//!
+//! The protocol is one line of json per request and per response, e.g.
+//! `{"command":"start","description":"review","project":null,"tags":[],"at":null,"billable":null,"force":false}`
+//! is answered with `{"output":""}`, `{"warning":"..."}` or `{"error":"..."}`. The commands of tt
+//! add the `client` they were run in, so the daemon uses the same settings as the command would.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+use crate::absence;
+use crate::settings::Settings;
+use crate::Command;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Start {
+        description: Option<String>,
+        project: Option<String>,
+        tags: Vec<String>,
+        at: Option<String>,
+        billable: Option<bool>,
+        force: bool,
+    },
+    Stop {
+        description: Option<String>,
+        at: Option<String>,
+        force: bool,
+    },
+    Prompt {
+        format: String,
+    },
+}
+
+/// how the command which sent a request was run
+#[derive(Debug, Serialize, Deserialize)]
+struct Client {
+    /// the config file given with `--config-file`, as absolute path
+    config_file: Option<String>,
+    /// the working directory, in which the project settings are looked for
+    dir: PathBuf,
+    /// the `TT_` environment variables, which override the config
+    env: Vec<(String, String)>,
+    /// the arguments, which are recorded in the audit log of strict mode
+    args: Vec<String>,
+}
+
+impl Client {
+    fn current(config_file: &Option<String>) -> Result<Self> {
+        let dir = std::env::current_dir()?;
+        Ok(Self {
+            config_file: config_file
+                .as_ref()
+                .map(|file| dir.join(file).to_string_lossy().to_string()),
+            env: std::env::vars()
+                .filter(|(key, _)| key.to_lowercase().starts_with("tt_"))
+                .collect(),
+            args: std::env::args().skip(1).collect(),
+            dir,
+        })
+    }
+
+    /// the settings the command would use for the data file at `path`
+    fn settings(&self, path: &str) -> Result<Settings> {
+        let mut settings = Settings::load(&self.config_file, &self.dir, self.env.clone())?;
+        settings.absences = absence::read(path)?;
+        settings.args = self.args.clone();
+        Ok(settings)
+    }
+}
+
+/// a request as it is sent to the daemon
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    #[serde(flatten)]
+    request: Request,
+    /// missing in the requests of other programs, which get the settings of the daemon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client: Option<Client>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Response {
+    Output(String),
+    /// the request didn't change anything
+    Warning(String),
+    Error(String),
+    /// the settings of the command differ from the daemon's in a way the daemon can't take over,
+    /// like the timezone, so the command handles the request itself
+    Declined,
+}
+
+impl Request {
+    /// the request for the command, if the daemon can handle it. starts which ask something or
+    /// run a timer, like `--pick`, `--interactive`, `--strict` and `--for`, are run by the
+    /// command itself
+    pub fn from_command(settings: &Settings, command: &Command) -> Option<Self> {
+        // the daemon doesn't sync
+        if settings.sync.auto {
+            return None;
+        }
+        match command {
+            Command::Start {
+                description,
+                pick: false,
+                interactive: false,
+                project,
+                tags,
+                at,
+                strict: false,
+                force,
+                duration: None,
+                billable,
+            } => Some(Self::Start {
+                description: description.clone(),
+                project: project.clone(),
+                tags: tags.clone(),
+                at: at.clone(),
+                billable: billable.value(),
+                force: *force,
+            }),
+            Command::Stop {
+                description,
+                at,
+                all_profiles: false,
+                force,
+            } => Some(Self::Stop {
+                description: description.clone(),
+                at: at.clone(),
+                force: *force,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn socket_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.sock", path))
+}
+
+/// whether the settings which apply to the whole process are the same, so the daemon can handle a
+/// request with the settings of the command
+fn same_process_settings(daemon: &Settings, client: &Settings) -> bool {
+    daemon.timezone == client.timezone
+        && daemon.locale == client.locale
+        && daemon.date_format == client.date_format
+        && daemon.time_format == client.time_format
+        && daemon.include_seconds == client.include_seconds
+        && daemon.millisecond_timestamps == client.millisecond_timestamps
+        && daemon.host == client.host
+        && daemon.record_host == client.record_host
+        && daemon.record_user == client.record_user
+        && daemon.encryption.enabled == client.encryption.enabled
+        && daemon.encryption.key_file == client.encryption.key_file
+}
+
+/// prints the response of the daemon. an error of the daemon is returned as error
+pub fn print(response: Response) -> Result<()> {
+    match response {
+        Response::Output(output) if output.is_empty() => {}
+        Response::Output(output) => println!("{}", output),
+        Response::Warning(warning) => eprintln!("{}", warning),
+        Response::Error(error) => return Err(anyhow!(error)),
+        Response::Declined => unreachable!("send returns no response then"),
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+
+    use anyhow::Context;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use crate::break_reminder;
+    use crate::storage::{lock, read_data_if_exists};
+    use crate::{
+        correct_data, end_planned_stop, start_tracking, stop_tracking, write_changes,
+        CalculationOptions, CurrentSession, TrackingEvent,
+    };
+
+    /// how long a command waits for the answer of the daemon
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// the response of the daemon for the data file, or `None` if no daemon is running or it
+    /// declined the request. `config_file` is the one given to the command
+    pub fn send(
+        path: &str,
+        config_file: &Option<String>,
+        request: Request,
+    ) -> Option<Result<Response>> {
+        let socket = socket_path(path);
+        // a socket left behind by a daemon which was killed refuses the connection
+        let stream = UnixStream::connect(&socket).ok()?;
+        let exchange = || -> Result<Response> {
+            let message = Message {
+                request,
+                client: Some(Client::current(config_file)?),
+            };
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            let mut writer = &stream;
+            writeln!(writer, "{}", serde_json::to_string(&message)?)?;
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line)?;
+            Ok(serde_json::from_str(&line)?)
+        };
+        match exchange().context("the daemon didn't answer") {
+            Ok(Response::Declined) => None,
+            response => Some(response),
+        }
+    }
+
+    /// the data of the file, read again whenever another command changed it
+    struct Cache {
+        data: Vec<TrackingEvent>,
+        modified: Option<(SystemTime, u64)>,
+    }
+
+    fn modified(path: &str) -> Option<(SystemTime, u64)> {
+        let metadata = Path::new(path).metadata().ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    impl Cache {
+        fn refresh(&mut self, path: &str) -> Result<()> {
+            let modified = modified(path);
+            if modified.is_none() || modified != self.modified {
+                self.data = read_data_if_exists(path)?;
+                self.modified = modified;
+            }
+            Ok(())
+        }
+
+        /// changes the data like the command would and saves it right away. the corrections of
+        /// the command, like the automatic stop, are made first, without asking anything
+        fn change(
+            &mut self,
+            settings: &Settings,
+            path: &str,
+            request: Request,
+        ) -> Result<Response> {
+            let _lock = lock(path)?;
+            self.refresh(path)?;
+            let mut original_data = self.data.clone();
+            let mut data = original_data.clone();
+            // only a start warns about a long session, like the command. a correction changes the
+            // file, so the cache is read again, even if the request itself changes nothing
+            let session_check = Some(false).filter(|_| matches!(request, Request::Start { .. }));
+            correct_data(
+                settings,
+                path,
+                &mut original_data,
+                &mut data,
+                session_check,
+                false,
+                false,
+            )?;
+            let mut output = Vec::new();
+            match request {
+                Request::Start {
+                    description,
+                    project,
+                    tags,
+                    at,
+                    billable,
+                    force,
+                } => {
+                    if at.is_none() {
+                        output.extend(end_planned_stop(&mut data, true));
+                    }
+                    start_tracking(
+                        settings,
+                        &mut data,
+                        description,
+                        project,
+                        tags,
+                        at,
+                        billable,
+                        force,
+                    )?;
+                    if data == original_data {
+                        return Ok(Response::Warning(
+                            "Time tracking is already running!".to_string(),
+                        ));
+                    }
+                }
+                Request::Stop {
+                    description,
+                    at,
+                    force,
+                } => {
+                    if at.is_none() {
+                        output.extend(end_planned_stop(&mut data, false));
+                    }
+                    stop_tracking(&mut data, description, at, force)?;
+                    if data == original_data {
+                        return Ok(Response::Warning(
+                            "Time tracking is not running!".to_string(),
+                        ));
+                    }
+                }
+                Request::Prompt { .. } => unreachable!("doesn't change the data"),
+            }
+            write_changes(settings, path, &original_data, &mut data, true)?;
+            self.data = data;
+            self.modified = modified(path);
+            Ok(Response::Output(output.join("\n")))
+        }
+
+        /// handles the request with the settings of the command which sent it
+        fn handle(
+            &mut self,
+            settings: &Settings,
+            path: &str,
+            message: Message,
+        ) -> Result<Response> {
+            let client_settings = match message.client {
+                Some(client) => client.settings(path)?,
+                None => settings.clone(),
+            };
+            if !same_process_settings(settings, &client_settings) {
+                return Ok(Response::Declined);
+            }
+            let settings = &client_settings;
+            match message.request {
+                Request::Prompt { format } => {
+                    self.refresh(path)?;
+                    let session = CurrentSession::from_events(
+                        settings,
+                        &self.data,
+                        CalculationOptions::default(),
+                    );
+                    Ok(Response::Output(session.format(&format)))
+                }
+                request => self.change(settings, path, request),
+            }
+        }
+    }
+
+    fn serve(cache: &mut Cache, settings: &Settings, path: &str, stream: UnixStream) -> Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        // `tt daemon` only connects to check whether a daemon is running
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(message) => cache
+                .handle(settings, path, message)
+                .unwrap_or_else(|e| Response::Error(e.to_string())),
+            Err(e) => Response::Error(format!("invalid request: {}", e)),
+        };
+        let mut writer = &stream;
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+        Ok(())
+    }
+
+    pub fn run(settings: &Settings, path: &str) -> Result<()> {
+        let socket = socket_path(path);
+        if UnixStream::connect(&socket).is_ok() {
+            return Err(anyhow!("A daemon for {} is already running!", path));
+        }
+        // left behind by a daemon which was killed
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket)?;
+        let handler_socket = socket.clone();
+        ctrlc::set_handler(move || {
+            let _ = std::fs::remove_file(&handler_socket);
+            std::process::exit(0);
+        })?;
+        println!("Listening on {}", socket.display());
+
+        if settings.watch.break_reminder_after.is_some() {
+            let (settings, path) = (settings.clone(), path.to_string());
+            std::thread::spawn(move || loop {
+                std::thread::sleep(break_reminder::CHECK_INTERVAL);
+                if let Err(e) = break_reminder::check(&settings, &path) {
+                    eprintln!("Warning: {}", e);
+                }
+            });
+        }
+
+        let mut cache = Cache {
+            data: Vec::new(),
+            modified: None,
+        };
+        cache.refresh(path)?;
+        // one request after the other, so no two changes can overlap
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(Into::into)
+                .and_then(|stream| serve(&mut cache, settings, path, stream));
+            if let Err(e) = result {
+                eprintln!("Warning: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{run, send};
+
+#[cfg(not(unix))]
+pub fn send(
+    _path: &str,
+    _config_file: &Option<String>,
+    _request: Request,
+) -> Option<Result<Response>> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn run(_settings: &Settings, _path: &str) -> Result<()> {
+    Err(anyhow!(
+        "tt daemon needs unix domain sockets, which are not available on this system!"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_format() {
+        let request = Request::Stop {
+            description: None,
+            at: Some("17:00".to_string()),
+            force: false,
+        };
+        assert_eq!(
+            r#"{"command":"stop","description":null,"at":"17:00","force":false}"#,
+            serde_json::to_string(&request).unwrap()
+        );
+        assert!(matches!(
+            serde_json::from_str(r#"{"command":"prompt","format":"{state}"}"#),
+            Ok(Message {
+                request: Request::Prompt { .. },
+                client: None,
+            })
+        ));
+        let message: Message = serde_json::from_str(
+            r#"{"command":"prompt","format":"{state}","client":{"config_file":null,"dir":"/home","env":[["TT_LOCALE","de_DE"]],"args":["prompt"]}}"#,
+        )
+        .unwrap();
+        assert!(matches!(message.request, Request::Prompt { .. }));
+        assert_eq!(
+            vec![("TT_LOCALE".to_string(), "de_DE".to_string())],
+            message.client.unwrap().env
+        );
+        assert_eq!(
+            r#"{"warning":"nothing"}"#,
+            serde_json::to_string(&Response::Warning("nothing".to_string())).unwrap()
+        );
+    }
+}
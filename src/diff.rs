@@ -0,0 +1,128 @@
+//! `tt diff`, which compares the data with a backup or another data file, e.g. before restoring a
+//! backup or after merging the data of another machine. Events are matched by their id, so an
+//! edited event is shown as changed instead of as removed and added.
+
+use anyhow::Result;
+use uuid::Uuid;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::color::{paint, Style};
+use crate::merge::read_other;
+use crate::{to_human_readable, TrackingEvent};
+
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+    /// only in the data
+    Added(&'a TrackingEvent),
+    /// only in the other file
+    Removed(&'a TrackingEvent),
+    /// the event of the other file and how it is in the data
+    Changed(&'a TrackingEvent, &'a TrackingEvent),
+}
+
+impl Change<'_> {
+    fn event(&self) -> &TrackingEvent {
+        match self {
+            Self::Added(event) | Self::Removed(event) | Self::Changed(_, event) => event,
+        }
+    }
+}
+
+/// how `data` differs from `other`, sorted by time
+fn changes<'a>(data: &'a [TrackingEvent], other: &'a [TrackingEvent]) -> Vec<Change<'a>> {
+    let by_id = |events: &'a [TrackingEvent]| -> HashMap<Uuid, &'a TrackingEvent> {
+        events.iter().map(|e| (e.data().id, e)).collect()
+    };
+    let (in_data, in_other) = (by_id(data), by_id(other));
+    let mut changes: Vec<_> = data
+        .iter()
+        .filter_map(|event| match in_other.get(&event.data().id) {
+            None => Some(Change::Added(event)),
+            Some(old) if *old != event => Some(Change::Changed(old, event)),
+            Some(_) => None,
+        })
+        .chain(
+            other
+                .iter()
+                .filter(|event| !in_data.contains_key(&event.data().id))
+                .map(Change::Removed),
+        )
+        .collect();
+    changes.sort_by_key(|change| change.event().time(true));
+    changes
+}
+
+fn line(event: &TrackingEvent) -> String {
+    to_human_readable(event.name(), event.data())
+}
+
+pub fn diff(data: &[TrackingEvent], path: &Path) -> Result<()> {
+    let other = read_other(path)?;
+    let changes = changes(data, &other);
+    if changes.is_empty() {
+        println!("No differences to \"{}\".", path.display());
+        return Ok(());
+    }
+
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for change in &changes {
+        match change {
+            Change::Added(event) => {
+                added += 1;
+                println!("{}", paint(format!("+ {}", line(event)), Style::Active));
+            }
+            Change::Removed(event) => {
+                removed += 1;
+                println!("{}", paint(format!("- {}", line(event)), Style::Error));
+            }
+            Change::Changed(old, new) => {
+                changed += 1;
+                println!("{}", paint(format!("~ {}", line(old)), Style::Warning));
+                println!("{}", paint(format!("  {}", line(new)), Style::Warning));
+            }
+        }
+    }
+    println!(
+        "{} added, {} removed and {} changed compared to \"{}\".",
+        added,
+        removed,
+        changed,
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_changes() {
+        let event = |h| {
+            TrackingEvent::Start(TrackingData::new(
+                None,
+                Utc.ymd(2024, 3, 4).and_hms(h, 0, 0),
+            ))
+        };
+        let (kept, removed, added) = (event(8), event(9), event(10));
+        let mut edited = event(11);
+        let original = edited.clone();
+        edited.data_mut().description = Some("review".to_string());
+
+        let other = vec![kept.clone(), removed, original];
+        let data = vec![kept, added, edited];
+        assert_eq!(
+            vec![
+                Change::Removed(&other[1]),
+                Change::Added(&data[1]),
+                Change::Changed(&other[2], &data[2]),
+            ],
+            changes(&data, &other)
+        );
+        assert!(changes(&data, &data).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! The earnings of the billable time: the rounded billable time of every project multiplied by
+//! the hourly rate of the project, or `billing.rate` for projects without their own.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use crate::color::{paint, Style};
+use crate::settings::Settings;
+use crate::{filter_events, format_duration, CalculationOptions, FilterData, TrackingEvent};
+
+struct Earning {
+    project: Option<String>,
+    billable: Duration,
+    rate: f64,
+}
+
+impl Earning {
+    fn amount(&self) -> f64 {
+        self.billable.num_seconds() as f64 / 3600.0 * self.rate
+    }
+}
+
+/// formats the amount with two decimals and thousands separators, like "1,234.50 EUR"
+fn format_money(amount: f64, currency: &str) -> String {
+    let cents = (amount * 100.0).round() as i64;
+    let units = (cents.abs() / 100).to_string();
+    let mut grouped = String::new();
+    for (i, digit) in units.chars().enumerate() {
+        if i > 0 && (units.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02} {}", sign, grouped, cents.abs() % 100, currency)
+        .trim_end()
+        .to_string()
+}
+
+/// the billable time of every project with its rate, rounded with the rounding of the project
+fn collect(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<Earning> {
+    let include_seconds = options.include_seconds;
+    let mut earnings: Vec<Earning> = Vec::new();
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let stop = data_iterator
+            .find(|e| e.is_stop())
+            .map_or_else(Utc::now, |stop| stop.time(include_seconds));
+        if !start.data().is_billable(settings) {
+            continue;
+        }
+        let project = start.data().project.clone();
+        let project_settings = settings.for_project(&project);
+        let mut duration =
+            match options.limit_interval(settings, stop - start.time(include_seconds)) {
+                Some(duration) => duration,
+                None => continue,
+            };
+        if !options.no_round {
+            duration = project_settings.rounding.apply(duration);
+        }
+        match earnings.iter_mut().find(|e| e.project == project) {
+            Some(earning) => earning.billable += duration,
+            None => {
+                let rate = project
+                    .as_ref()
+                    .and_then(|project| settings.projects.get(project))
+                    .and_then(|project| project.rate)
+                    .unwrap_or(settings.billing.rate);
+                earnings.push(Earning {
+                    project,
+                    billable: duration,
+                    rate,
+                })
+            }
+        }
+    }
+    earnings.sort_by(|a, b| b.amount().total_cmp(&a.amount()));
+    earnings
+}
+
+pub fn earnings(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    options: CalculationOptions,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let earnings = collect(settings, &filtered_data, options);
+    if earnings.is_empty() {
+        println!("No billable time.");
+        return Ok(());
+    }
+    let currency = &settings.billing.currency;
+    let rows: Vec<_> = earnings
+        .iter()
+        .map(|earning| {
+            (
+                format_duration(earning.billable),
+                format_money(earning.rate, currency),
+                format_money(earning.amount(), currency),
+                earning.project.clone().unwrap_or_else(|| "-".to_string()),
+            )
+        })
+        .collect();
+    let total = format_money(earnings.iter().map(Earning::amount).sum(), currency);
+    let rate_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0).max(4);
+    let amount_width = rows
+        .iter()
+        .map(|row| row.2.len())
+        .chain(std::iter::once(total.len()))
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{}",
+        paint(
+            format!(
+                "{:>8}  {:>rate_width$}  {:>amount_width$}  Project",
+                "Billable",
+                "Rate",
+                "Amount",
+                rate_width = rate_width,
+                amount_width = amount_width
+            ),
+            Style::Header
+        )
+    );
+    for (billable, rate, amount, project) in rows {
+        println!(
+            "{:>8}  {:>rate_width$}  {:>amount_width$}  {}",
+            billable,
+            rate,
+            amount,
+            project,
+            rate_width = rate_width,
+            amount_width = amount_width
+        );
+    }
+    let billable = earnings
+        .iter()
+        .fold(Duration::zero(), |total, earning| total + earning.billable);
+    println!(
+        "{}",
+        paint(
+            format!(
+                "{:>8}  {:>rate_width$}  {:>amount_width$}  Total",
+                format_duration(billable),
+                "",
+                total,
+                rate_width = rate_width,
+                amount_width = amount_width
+            ),
+            Style::Header
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_money() {
+        assert_eq!("1,234.50 EUR", format_money(1234.5, "EUR"));
+        assert_eq!("0.00 EUR", format_money(0.0, "EUR"));
+        assert_eq!("1,000,000.01", format_money(1_000_000.01, ""));
+        assert_eq!("-12.30 $", format_money(-12.3, "$"));
+    }
+}
@@ -0,0 +1,154 @@
+//! Encryption of the data file at rest.
+//!
+//! An encrypted file starts with its own header, followed by the salt for deriving the key from
+//! the passphrase with Argon2, the nonce and the data file encrypted with ChaCha20-Poly1305. The
+//! whole file is encrypted at once, so with encryption enabled, events can't be appended to the
+//! file and every change rewrites it.
+
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::settings::Settings;
+
+const HEADER: &[u8] = b"TTENC\n";
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// the environment variable the passphrase is read from, if no key file is set
+const PASSPHRASE_VARIABLE: &str = "TT_PASSPHRASE";
+
+struct Config {
+    enabled: bool,
+    passphrase: Option<String>,
+}
+
+/// set once by `init`, so reading and writing the data file doesn't need the settings
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(HEADER)
+}
+
+/// whether the data file gets encrypted when it's written
+pub fn is_enabled() -> bool {
+    matches!(CONFIG.get(), Some(config) if config.enabled)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("could not derive the key from the passphrase: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0; SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| anyhow!("could not encrypt the data file"))?;
+
+    let mut contents = HEADER.to_vec();
+    contents.extend_from_slice(&salt);
+    contents.extend_from_slice(&nonce);
+    contents.extend(ciphertext);
+    Ok(contents)
+}
+
+fn decrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let data = &data[HEADER.len()..];
+    if data.len() < SALT_SIZE + NONCE_SIZE {
+        return Err(anyhow!("The encrypted data file is incomplete!"));
+    }
+    let (salt, data) = data.split_at(SALT_SIZE);
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Could not decrypt the data file, is the passphrase correct?"))
+}
+
+/// encrypts the contents of a data file, if encryption is enabled
+pub fn encode(data: Vec<u8>) -> Result<Vec<u8>> {
+    match CONFIG.get() {
+        Some(Config {
+            enabled: true,
+            passphrase: Some(passphrase),
+        }) => encrypt(passphrase, &data),
+        _ => Ok(data),
+    }
+}
+
+/// decrypts the contents of a data file, if it is encrypted
+pub fn decode(data: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    match CONFIG.get().and_then(|config| config.passphrase.as_ref()) {
+        Some(passphrase) => decrypt(passphrase, &data),
+        None => Err(anyhow!(
+            "The data file is encrypted, please set encryption.key_file or {}!",
+            PASSPHRASE_VARIABLE
+        )),
+    }
+}
+
+fn read_passphrase(settings: &Settings) -> Result<Option<String>> {
+    if settings.encryption.key_file.is_empty() {
+        return Ok(std::env::var(PASSPHRASE_VARIABLE).ok());
+    }
+    let key_file = shellexpand::full(&settings.encryption.key_file)?.to_string();
+    let passphrase = std::fs::read_to_string(&key_file)
+        .with_context(|| format!("could not read key file \"{}\"", key_file))?;
+    Ok(Some(
+        passphrase.trim_end_matches(&['\r', '\n'][..]).to_string(),
+    ))
+}
+
+/// reads the passphrase for the data file at `path`. if the file is encrypted, it gets decrypted
+/// once, so a wrong passphrase is noticed before anything reads or writes the file.
+pub fn init<P: AsRef<Path>>(settings: &Settings, path: P) -> Result<()> {
+    let passphrase = read_passphrase(settings)?.filter(|passphrase| !passphrase.is_empty());
+    if settings.encryption.enabled && passphrase.is_none() {
+        return Err(anyhow!(
+            "Encryption is enabled, please set encryption.key_file or {}!",
+            PASSPHRASE_VARIABLE
+        ));
+    }
+    CONFIG.get_or_init(|| Config {
+        enabled: settings.encryption.enabled,
+        passphrase,
+    });
+
+    match std::fs::read(path) {
+        Ok(data) if is_encrypted(&data) => decode(data).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt() {
+        let data = b"TTLOG\nsome events".to_vec();
+        let encrypted = encrypt("secret", &data).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted
+            .windows(data.len())
+            .any(|window| window == &data[..]));
+        assert_eq!(data, decrypt("secret", &encrypted).unwrap());
+        assert!(decrypt("wrong", &encrypted).is_err());
+        assert!(decrypt("secret", HEADER).is_err());
+    }
+}
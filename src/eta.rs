@@ -0,0 +1,115 @@
+//! `tt eta` and the `{eta}` placeholder of the prompt: the time at which the goal of today is
+//! reached, when working from now on without another break. the rest of `min_daily_break` which
+//! wasn't taken yet is added, so the work time isn't cut short by it afterwards.
+
+use anyhow::Result;
+use chrono::prelude::*;
+use chrono::Duration;
+
+use crate::settings::Settings;
+use crate::{
+    format_hours_minutes, summarize_day, CalculationOptions, CurrentSession, TrackingEvent,
+};
+
+/// when `goal` is reached, with `work_time` done since `first_start` and working from `now` on
+fn leave_time(
+    goal: Duration,
+    min_daily_break: Duration,
+    first_start: Option<DateTime<Utc>>,
+    work_time: Duration,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let taken_break = first_start.map_or_else(Duration::zero, |first| now - first - work_time);
+    let missing_break = (min_daily_break - taken_break).max(Duration::zero());
+    now + (goal - work_time) + missing_break
+}
+
+/// the time at which the goal of today is reached, or `None` if there is no goal today.
+/// `todays_events` are the events of today
+pub fn from_events(
+    settings: &Settings,
+    todays_events: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Option<DateTime<Utc>> {
+    let goal = settings.goal_for_date(Local::today().naive_local());
+    if goal <= Duration::zero() {
+        return None;
+    }
+    // the break is added by `leave_time`
+    let without_break = Settings {
+        min_daily_break: Duration::zero(),
+        ..settings.clone()
+    };
+    let summary = summarize_day(&without_break, todays_events, options);
+    Some(leave_time(
+        goal,
+        settings.min_daily_break,
+        summary.first_start,
+        summary.work_time,
+        Utc::now(),
+    ))
+}
+
+/// the local time without seconds, like "17:12"
+pub fn format(eta: DateTime<Utc>) -> String {
+    eta.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+pub fn eta(settings: &Settings, path: &str) -> Result<()> {
+    let session = CurrentSession::read(settings, path);
+    let now = Utc::now();
+    match session.eta {
+        None => println!("There is no goal for today."),
+        Some(eta) if eta <= now => println!(
+            "You reached the goal for today, {} ago.",
+            format_hours_minutes(now - eta)
+        ),
+        Some(eta) => println!(
+            "You can leave at {} (in {}).",
+            format(eta),
+            format_hours_minutes(eta - now)
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leave_time() {
+        let at = |h, m| Utc.ymd(2024, 3, 4).and_hms(h, m, 0);
+        let goal = Duration::hours(8);
+        let min_break = Duration::minutes(30);
+        let now = at(13, 0);
+
+        // nothing tracked yet
+        assert_eq!(
+            at(21, 30),
+            leave_time(goal, min_break, None, Duration::zero(), now)
+        );
+        // 4 hours of work without a break
+        let morning = (Some(at(9, 0)), Duration::hours(4));
+        assert_eq!(
+            at(17, 30),
+            leave_time(goal, min_break, morning.0, morning.1, now)
+        );
+        assert_eq!(
+            at(17, 0),
+            leave_time(goal, Duration::zero(), morning.0, morning.1, now)
+        );
+        // 3 hours of work and a break of 20 minutes so far
+        let lunch = (Some(at(9, 40)), Duration::hours(3));
+        assert_eq!(
+            at(18, 10),
+            leave_time(goal, min_break, lunch.0, lunch.1, now)
+        );
+        // a longer break than needed doesn't make the day shorter
+        let long_lunch = (Some(at(8, 0)), Duration::hours(4));
+        assert_eq!(
+            at(17, 0),
+            leave_time(goal, min_break, long_lunch.0, long_lunch.1, now)
+        );
+    }
+}
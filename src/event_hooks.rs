@@ -0,0 +1,191 @@
+//! The commands from the `[hooks]` config, which are run when the time tracking is started or
+//! stopped and when the work time of the day reaches the daily goal. The details of the event are
+//! passed in environment variables starting with `TT_`.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::settings::Settings;
+use crate::{format_hours_minutes, CurrentSession, TrackingEvent};
+
+fn event_name(event: &TrackingEvent) -> &'static str {
+    match event {
+        TrackingEvent::Start(_) => "start",
+        TrackingEvent::Resume(_) => "resume",
+        TrackingEvent::Stop(_) => "stop",
+        TrackingEvent::Pause(_) => "pause",
+    }
+}
+
+/// the added events which changed whether the time is tracked. switching to another entry stops
+/// the running one and starts the new one. edited events and entries added in the past don't
+/// change it
+fn state_changes<'a>(
+    original_data: &[TrackingEvent],
+    data: &'a [TrackingEvent],
+) -> Vec<&'a TrackingEvent> {
+    let ids: HashSet<_> = original_data.iter().map(|e| e.data().id).collect();
+    let is_new = |event: &TrackingEvent| !ids.contains(&event.data().id);
+    let was_running = original_data.last().is_some_and(|e| e.is_start());
+    let last = match data.last() {
+        Some(last) if is_new(last) => last,
+        _ => return Vec::new(),
+    };
+    let previous = data.len().checked_sub(2).map(|i| &data[i]);
+    match previous {
+        _ if last.is_start() && !was_running => vec![last],
+        Some(previous) if last.is_start() && previous.is_stop() && is_new(previous) => {
+            vec![previous, last]
+        }
+        _ if last.is_stop() && was_running => vec![last],
+        _ => Vec::new(),
+    }
+}
+
+/// the details of the event. a stop gets the description, project and tags of the entry it ends
+fn environment(data: &[TrackingEvent], event: &TrackingEvent) -> Vec<(&'static str, String)> {
+    let mut environment = vec![
+        ("TT_EVENT", event_name(event).to_string()),
+        ("TT_ID", event.data().id.to_string()),
+        (
+            "TT_TIME",
+            event.time(true).with_timezone(&Local).to_rfc3339(),
+        ),
+    ];
+    let start = if event.is_start() {
+        Some(event)
+    } else {
+        let position = data
+            .iter()
+            .rposition(|e| e.data().id == event.data().id)
+            .unwrap_or(0);
+        data[..position].iter().rev().find(|e| e.is_start())
+    };
+    if let Some(start) = start {
+        let entry = start.data();
+        environment.extend(vec![
+            (
+                "TT_DESCRIPTION",
+                entry.description.clone().unwrap_or_default(),
+            ),
+            ("TT_PROJECT", entry.project.clone().unwrap_or_default()),
+            ("TT_TAGS", entry.tags.join(",")),
+        ]);
+        if event.is_stop() {
+            environment.push((
+                "TT_DURATION",
+                format_hours_minutes(event.time(true) - start.time(true)),
+            ));
+        }
+    }
+    environment
+}
+
+fn run_hook(name: &str, command: &str, environment: &[(&str, String)]) {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    // the data is already written, so a failing hook is only reported
+    match process.envs(environment.iter().cloned()).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("Warning: The {} hook failed: {}", name, status),
+        Err(e) => eprintln!("Warning: Could not run the {} hook: {}", name, e),
+    }
+}
+
+/// runs the on_start and on_stop hooks for the changes made to `original_data`
+pub fn run(settings: &Settings, original_data: &[TrackingEvent], data: &[TrackingEvent]) {
+    for event in state_changes(original_data, data) {
+        let (name, command) = if event.is_start() {
+            ("on_start", &settings.hooks.on_start)
+        } else {
+            ("on_stop", &settings.hooks.on_stop)
+        };
+        if !command.trim().is_empty() {
+            run_hook(name, command, &environment(data, event));
+        }
+    }
+}
+
+/// the date on which the on_goal_reached hook was run last is stored next to the data file
+fn goal_reached_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.goal-reached", path))
+}
+
+/// runs the on_goal_reached hook, if the daily goal is reached and the hook didn't run today yet
+pub fn check_goal(settings: &Settings, path: &str) -> Result<()> {
+    if settings.hooks.on_goal_reached.trim().is_empty() {
+        return Ok(());
+    }
+    let today = Local::today().naive_local();
+    let goal = settings.goal_for_date(today);
+    let marker = goal_reached_path(path);
+    let already_run = std::fs::read_to_string(&marker)
+        .is_ok_and(|date| date.trim() == today.format("%Y-%m-%d").to_string());
+    if goal <= Duration::zero() || already_run {
+        return Ok(());
+    }
+    let session = CurrentSession::read(settings, path);
+    if session.today < goal {
+        return Ok(());
+    }
+    std::fs::write(&marker, today.format("%Y-%m-%d").to_string())?;
+    run_hook(
+        "on_goal_reached",
+        &settings.hooks.on_goal_reached,
+        &[
+            ("TT_EVENT", "goal_reached".to_string()),
+            ("TT_TODAY", format_hours_minutes(session.today)),
+            ("TT_GOAL", format_hours_minutes(goal)),
+        ],
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_state_changes() {
+        let at = |h| TrackingData::new(None, Utc.ymd(2024, 3, 4).and_hms(h, 0, 0));
+        let (start, stop) = (TrackingEvent::Start(at(8)), TrackingEvent::Stop(at(9)));
+        let stopped = vec![start.clone(), stop.clone()];
+
+        let started = vec![start.clone(), stop.clone(), TrackingEvent::Start(at(10))];
+        assert_eq!(vec![&started[2]], state_changes(&stopped, &started));
+        let stopped_again = [&started[..], &[TrackingEvent::Stop(at(11))]].concat();
+        assert_eq!(
+            vec![&stopped_again[3]],
+            state_changes(&started, &stopped_again)
+        );
+        // switching to another entry
+        let switched = [
+            &started[..],
+            &[TrackingEvent::Stop(at(11)), TrackingEvent::Start(at(11))],
+        ]
+        .concat();
+        assert_eq!(
+            vec![&switched[3], &switched[4]],
+            state_changes(&started, &switched)
+        );
+        // an entry added at the end while nothing is running doesn't change anything
+        let added = [&stopped[..], &[TrackingEvent::Stop(at(10))]].concat();
+        assert!(state_changes(&stopped, &added).is_empty());
+        // neither does a new entry added in the past
+        let mut past = started.clone();
+        past.insert(0, TrackingEvent::Start(at(6)));
+        assert!(state_changes(&started, &past).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! The formats of `tt export --format`. Besides the built-in ones, every executable named
+//! `tt-export-<name>` in $PATH adds the format `<name>`, so a company specific timesheet doesn't
+//! need a change to tt. Such an exporter gets the filtered events as a json array on stdin, and
+//! what it prints to stdout is written to the export path.
+
+use anyhow::{anyhow, Result};
+use iif::iif;
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::settings::Settings;
+use crate::storage::write_output;
+use crate::timesheet::{self, ExportFormat};
+use crate::TrackingEvent;
+
+const PREFIX: &str = "tt-export-";
+
+pub trait Exporter {
+    /// exports the events of the week containing the date `week`, or the default range of the
+    /// format
+    fn export(
+        &self,
+        settings: &Settings,
+        data: &[TrackingEvent],
+        week: Option<String>,
+        path: &str,
+    ) -> Result<()>;
+}
+
+impl Exporter for ExportFormat {
+    fn export(
+        &self,
+        settings: &Settings,
+        data: &[TrackingEvent],
+        week: Option<String>,
+        path: &str,
+    ) -> Result<()> {
+        timesheet::export(settings, data, *self, week, path)
+    }
+}
+
+/// an exporter plugin, which is an executable named `tt-export-<name>`
+pub struct External {
+    name: String,
+    program: PathBuf,
+}
+
+impl Exporter for External {
+    fn export(
+        &self,
+        settings: &Settings,
+        data: &[TrackingEvent],
+        week: Option<String>,
+        path: &str,
+    ) -> Result<()> {
+        let mut command = Command::new(&self.program);
+        let data = match week {
+            Some(week) => {
+                let (start, end) = timesheet::week_of(settings, timesheet::parse_week(&week)?);
+                command
+                    .env("TT_WEEK_START", start.format("%Y-%m-%d").to_string())
+                    .env("TT_WEEK_END", end.format("%Y-%m-%d").to_string());
+                timesheet::filter_week(settings, data, (start, end))?
+            }
+            None => data.to_vec(),
+        };
+        let input = serde_json::to_vec(&data).expect("could not serialize data");
+
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("could not run the exporter \"{}\": {}", self.name, e))?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        // written from another thread, so an exporter printing while it reads can't block tt
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        let written = writer.join().expect("could not write to the exporter");
+        if !output.status.success() {
+            return Err(anyhow!(
+                "the exporter \"{}\" failed: {}",
+                self.name,
+                output.status
+            ));
+        }
+        // an exporter may stop reading once it has what it needs
+        if let Err(e) = written {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(e.into());
+            }
+        }
+        write_output(path, output.stdout)
+    }
+}
+
+fn file_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}{}.exe", PREFIX, name)
+    } else {
+        format!("{}{}", PREFIX, name)
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// the first `tt-export-<name>` in `dirs`
+fn find_external(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+    dirs.iter()
+        .map(|dir| dir.join(file_name(name)))
+        .find(|path| is_executable(path))
+}
+
+/// the names of the exporter plugins in `dirs`
+fn external_names(dirs: &[PathBuf]) -> BTreeSet<String> {
+    dirs.iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_executable(&entry.path()))
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = file_name.strip_prefix(PREFIX)?;
+            let name = iif!(cfg!(windows), name.strip_suffix(".exe")?, name);
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// the built-in format or the exporter plugin called `name`
+pub fn find(name: &str) -> Result<Box<dyn Exporter>> {
+    if let Ok(format) = name.parse::<ExportFormat>() {
+        return Ok(Box::new(format));
+    }
+    let dirs = path_dirs();
+    match find_external(name, &dirs) {
+        Some(program) => Ok(Box::new(External {
+            name: name.to_string(),
+            program,
+        })),
+        None => {
+            let plugins: String = external_names(&dirs)
+                .iter()
+                .map(|name| format!(", {}", name))
+                .collect();
+            Err(anyhow!(
+                "invalid value \"{}\", expected one of: md, html, timeclock{} (or an executable \"{}\" in $PATH)",
+                name,
+                plugins,
+                file_name(name)
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_external() {
+        let dir = std::env::temp_dir().join(format!("tt-test-exporter-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plugin = dir.join(file_name("mycompany"));
+        std::fs::write(&plugin, "#!/bin/sh\ncat\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let dirs = vec![dir.join("missing"), dir.clone()];
+        let found = (
+            find_external("mycompany", &dirs),
+            find_external("other", &dirs),
+            find_external("../tt-export-mycompany", &dirs),
+            external_names(&dirs),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(Some(plugin), found.0);
+        assert_eq!(None, found.1);
+        assert_eq!(None, found.2);
+        assert_eq!(
+            vec!["mycompany".to_string()],
+            found.3.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! A wizard which walks through the starts without a stop, the stops without a start and the stops
+//! before their start, and proposes how to make the history consistent again. The proposed times
+//! are when the work days around the problem usually started or ended.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Input, Select};
+
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+
+use crate::natural_time::parse_time;
+use crate::{locale, parse_date_or_date_time, to_human_readable, DateOrDateTime};
+use crate::{TrackingData, TrackingEvent};
+
+/// how many of the closest days with data are used for the usual start and end of a day
+const NEIGHBOURS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Problem {
+    /// the start is followed by another start, or is the last event and from a previous day
+    MissingStop(usize),
+    /// the stop follows another stop, or is the first event
+    MissingStart(usize),
+    /// the stop is before the start before it
+    StopBeforeStart(usize),
+}
+
+/// the first problem at or after the event `from`
+fn find_problem(data: &[TrackingEvent], from: usize, today: NaiveDate) -> Option<Problem> {
+    (from..data.len()).find_map(|i| {
+        let event = &data[i];
+        let previous = i.checked_sub(1).map(|previous| &data[previous]);
+        if event.is_start() {
+            let missing = match data.get(i + 1) {
+                Some(next) => next.is_start(),
+                None => event.local_date() < today,
+            };
+            missing.then_some(Problem::MissingStop(i))
+        } else {
+            match previous {
+                Some(start) if start.is_start() => {
+                    (event.time(true) < start.time(true)).then_some(Problem::StopBeforeStart(i))
+                }
+                _ => Some(Problem::MissingStart(i)),
+            }
+        }
+    })
+}
+
+/// the first start and the last stop of every day which starts with a start and ends with a stop,
+/// in the timezone the events were recorded in
+fn day_bounds(data: &[TrackingEvent]) -> BTreeMap<NaiveDate, (NaiveTime, NaiveTime)> {
+    let mut days: BTreeMap<NaiveDate, Vec<&TrackingEvent>> = BTreeMap::new();
+    for event in data {
+        days.entry(event.local_date()).or_default().push(event);
+    }
+    days.into_iter()
+        .filter_map(|(date, events)| {
+            let (first, last) = (events.first()?, events.last()?);
+            let (start, end) = (first.local_time().time(), last.local_time().time());
+            (first.is_start() && last.is_stop() && start < end).then_some((date, (start, end)))
+        })
+        .collect()
+}
+
+/// the usual first start and last stop of the days around `date`, which are the medians of the
+/// closest days before and after it
+fn usual_day(
+    days: &BTreeMap<NaiveDate, (NaiveTime, NaiveTime)>,
+    date: NaiveDate,
+) -> Option<(NaiveTime, NaiveTime)> {
+    let before = days.range(..date).rev().take(NEIGHBOURS / 2);
+    let after = days.range(date.succ()..).take(NEIGHBOURS / 2);
+    let (mut starts, mut ends): (Vec<_>, Vec<_>) = before.chain(after).map(|(_, b)| *b).unzip();
+    if starts.is_empty() {
+        return None;
+    }
+    starts.sort();
+    ends.sort();
+    Some((starts[starts.len() / 2], ends[ends.len() / 2]))
+}
+
+/// `time` on the day of `event`, in the timezone the event was recorded in
+fn on_day_of(event: &TrackingEvent, time: NaiveTime) -> Option<DateTime<Utc>> {
+    let local = event.local_time();
+    local
+        .offset()
+        .from_local_datetime(&local.date().naive_local().and_time(time))
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// the time at which `start` probably ended: when the days around it usually end, if that is
+/// before `limit`
+fn suggest_stop(
+    days: &BTreeMap<NaiveDate, (NaiveTime, NaiveTime)>,
+    start: &TrackingEvent,
+    limit: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let (_, end) = usual_day(days, start.local_date())?;
+    let stop = on_day_of(start, end)?;
+    (start.time(true) < stop && stop <= limit).then_some(stop)
+}
+
+/// the time at which the entry of `stop` probably started: when the days around it usually start,
+/// if that is after `limit`
+fn suggest_start(
+    days: &BTreeMap<NaiveDate, (NaiveTime, NaiveTime)>,
+    stop: &TrackingEvent,
+    limit: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let (start, _) = usual_day(days, stop.local_date())?;
+    let start = on_day_of(stop, start)?;
+    (limit.is_none_or(|limit| limit <= start) && start < stop.time(true)).then_some(start)
+}
+
+/// a new event at `time`, in the timezone of `event`
+fn at_time_of(event: &TrackingEvent, time: DateTime<Utc>) -> TrackingData {
+    TrackingData {
+        offset: event.data().offset,
+        ..TrackingData::new(None, time)
+    }
+}
+
+fn format(event: &TrackingEvent, time: DateTime<Utc>) -> String {
+    locale::format_date_time(&time.with_timezone(event.local_time().offset()))
+}
+
+enum Action {
+    /// adds the missing event or moves the stop to the time
+    At(DateTime<Utc>),
+    /// the same, but asks for the time
+    AskTime,
+    Remove(usize),
+    Skip,
+}
+
+impl Problem {
+    fn index(self) -> usize {
+        match self {
+            Self::MissingStop(i) | Self::MissingStart(i) | Self::StopBeforeStart(i) => i,
+        }
+    }
+
+    /// the event the problem is about and the events around it, which are shown before asking
+    fn context(self, data: &[TrackingEvent]) -> (&'static str, Vec<&TrackingEvent>) {
+        match self {
+            Self::MissingStop(i) => ("This start has no stop.", vec![&data[i]]),
+            Self::MissingStart(i) => (
+                "This stop has no start.",
+                data[i.saturating_sub(1)..=i].iter().collect(),
+            ),
+            Self::StopBeforeStart(i) => (
+                "The stop is before its start.",
+                vec![&data[i - 1], &data[i]],
+            ),
+        }
+    }
+
+    /// the event whose timezone and day are used for the new time
+    fn reference(self, data: &[TrackingEvent]) -> &TrackingEvent {
+        match self {
+            Self::MissingStop(i) | Self::MissingStart(i) => &data[i],
+            Self::StopBeforeStart(i) => &data[i - 1],
+        }
+    }
+
+    /// whether the added or moved event at `time` keeps the events in order
+    fn fits(self, data: &[TrackingEvent], now: DateTime<Utc>, time: DateTime<Utc>) -> bool {
+        let next = |i: usize| data.get(i + 1).map_or(now, |next| next.time(true));
+        match self {
+            Self::MissingStop(i) => data[i].time(true) < time && time <= next(i),
+            Self::MissingStart(i) => {
+                let previous = i.checked_sub(1).map(|previous| data[previous].time(true));
+                previous.is_none_or(|previous| previous <= time) && time < data[i].time(true)
+            }
+            Self::StopBeforeStart(i) => data[i - 1].time(true) < time && time <= next(i),
+        }
+    }
+
+    /// the choices with the proposed time first, if there is one
+    fn options(
+        self,
+        data: &[TrackingEvent],
+        days: &BTreeMap<NaiveDate, (NaiveTime, NaiveTime)>,
+        now: DateTime<Utc>,
+    ) -> Vec<(String, Action)> {
+        let reference = self.reference(data);
+        let (suggestion, proposal, other) = match self {
+            Self::MissingStop(i) => (
+                suggest_stop(
+                    days,
+                    reference,
+                    data.get(i + 1).map_or(now, |e| e.time(true)),
+                ),
+                "Add a stop at {} (when the days around it usually end)",
+                "Add a stop at another time",
+            ),
+            Self::MissingStart(i) => (
+                suggest_start(
+                    days,
+                    reference,
+                    i.checked_sub(1).map(|previous| data[previous].time(true)),
+                ),
+                "Add a start at {} (when the days around it usually start)",
+                "Add a start at another time",
+            ),
+            Self::StopBeforeStart(i) => (
+                suggest_stop(
+                    days,
+                    reference,
+                    data.get(i + 1).map_or(now, |e| e.time(true)),
+                ),
+                "Move the stop to {} (when the days around it usually end)",
+                "Move the stop to another time",
+            ),
+        };
+        let mut options: Vec<(String, Action)> = suggestion
+            .map(|time| {
+                (
+                    proposal.replace("{}", &format(reference, time)),
+                    Action::At(time),
+                )
+            })
+            .into_iter()
+            .collect();
+        options.push((other.to_string(), Action::AskTime));
+        match self {
+            Self::MissingStop(i) => {
+                options.push(("Remove the start".to_string(), Action::Remove(i)))
+            }
+            Self::MissingStart(i) => {
+                options.push(("Remove the stop".to_string(), Action::Remove(i)))
+            }
+            Self::StopBeforeStart(i) => options.extend(vec![
+                ("Remove the stop".to_string(), Action::Remove(i)),
+                ("Remove the start".to_string(), Action::Remove(i - 1)),
+            ]),
+        }
+        options.push(("Skip".to_string(), Action::Skip));
+        options
+    }
+
+    /// adds the missing event or moves the stop to `time`. returns the index after the fixed events
+    fn apply(self, data: &mut Vec<TrackingEvent>, time: DateTime<Utc>) -> usize {
+        match self {
+            Self::MissingStop(i) => {
+                let stop = TrackingEvent::Stop(at_time_of(&data[i], time));
+                data.insert(i + 1, stop);
+                i + 2
+            }
+            Self::MissingStart(i) => {
+                let start = TrackingEvent::Start(at_time_of(&data[i], time));
+                data.insert(i, start);
+                i + 2
+            }
+            Self::StopBeforeStart(i) => {
+                data[i].data_mut().time = time;
+                i + 1
+            }
+        }
+    }
+}
+
+/// asks for a time, either on the day of `event` or with a date, until it is `valid`
+fn ask_time(event: &TrackingEvent, valid: impl Fn(DateTime<Utc>) -> bool) -> Result<DateTime<Utc>> {
+    loop {
+        let text: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Time (e.g. \"17:30\" or \"2024-03-04 17:30\")")
+            .interact_text()?;
+        let text = text.trim();
+        let time = match parse_time(text) {
+            Some(time) => on_day_of(event, time),
+            None => match parse_date_or_date_time(text) {
+                Ok(DateOrDateTime::DateTime(date_time)) => event
+                    .local_time()
+                    .offset()
+                    .from_local_datetime(&date_time)
+                    .single()
+                    .map(|time| time.with_timezone(&Utc)),
+                _ => None,
+            },
+        };
+        match time {
+            Some(time) if valid(time) => return Ok(time),
+            Some(_) => println!("The time has to be between the events before and after it!"),
+            None => println!("Could not parse the time!"),
+        }
+    }
+}
+
+/// walks through the problems and asks how to fix each of them. returns the fixed data, or `None`
+/// if nothing was changed
+pub fn fix(data: &[TrackingEvent]) -> Result<Option<Vec<TrackingEvent>>> {
+    let today = Local::today().naive_local();
+    let now = Utc::now();
+    if find_problem(data, 0, today).is_none() {
+        println!("No unmatched starts or stops found.");
+        return Ok(None);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("tt fix needs an interactive terminal!"));
+    }
+
+    let days = day_bounds(data);
+    let mut fixed = data.to_vec();
+    let mut changes = 0;
+    let mut from = 0;
+    while let Some(problem) = find_problem(&fixed, from, today) {
+        let (message, context) = problem.context(&fixed);
+        println!();
+        for event in context {
+            println!("  {}", to_human_readable(event.name(), event.data()));
+        }
+        println!("{}", message);
+
+        let mut options = problem.options(&fixed, &days, now);
+        let labels: Vec<_> = options.iter().map(|(label, _)| label.clone()).collect();
+        let choice = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("How should this be fixed?")
+            .items(&labels)
+            .default(0)
+            .interact_opt()?;
+        let action = match choice {
+            Some(choice) => options.swap_remove(choice).1,
+            None => {
+                println!("Nothing changed.");
+                return Ok(None);
+            }
+        };
+        from = match action {
+            Action::At(time) => problem.apply(&mut fixed, time),
+            Action::AskTime => {
+                let reference = problem.reference(&fixed);
+                let time = ask_time(reference, |time| problem.fits(&fixed, now, time))?;
+                problem.apply(&mut fixed, time)
+            }
+            Action::Remove(i) => {
+                fixed.remove(i);
+                // removing an event can leave the event before it without its counterpart
+                i.saturating_sub(1)
+            }
+            Action::Skip => {
+                from = problem.index() + 1;
+                continue;
+            }
+        };
+        changes += 1;
+    }
+
+    if changes == 0 {
+        println!("Nothing changed.");
+        return Ok(None);
+    }
+    println!("Made {} changes.", changes);
+    Ok(Some(fixed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(start: bool, day: u32, h: u32, m: u32) -> TrackingEvent {
+        let data = TrackingData {
+            offset: Some(0),
+            ..TrackingData::new(None, Utc.ymd(2021, 4, day).and_hms(h, m, 0))
+        };
+        if start {
+            TrackingEvent::Start(data)
+        } else {
+            TrackingEvent::Stop(data)
+        }
+    }
+
+    #[test]
+    fn test_find_problem() {
+        let today = NaiveDate::from_ymd(2021, 4, 10);
+        let data = vec![
+            event(false, 1, 7, 0),
+            event(true, 1, 8, 0),
+            event(false, 1, 12, 0),
+            event(true, 1, 13, 0),
+            event(true, 2, 9, 0),
+            event(false, 2, 8, 0),
+            event(true, 3, 9, 0),
+        ];
+        assert_eq!(
+            Some(Problem::MissingStart(0)),
+            find_problem(&data, 0, today)
+        );
+        assert_eq!(Some(Problem::MissingStop(3)), find_problem(&data, 1, today));
+        assert_eq!(
+            Some(Problem::StopBeforeStart(5)),
+            find_problem(&data, 4, today)
+        );
+        assert_eq!(Some(Problem::MissingStop(6)), find_problem(&data, 6, today));
+        // the running entry of today is fine
+        let today = NaiveDate::from_ymd(2021, 4, 3);
+        assert_eq!(None, find_problem(&data, 6, today));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let data = vec![
+            event(true, 1, 8, 0),
+            event(false, 1, 16, 0),
+            event(true, 2, 9, 0),
+            event(false, 2, 17, 0),
+            event(true, 3, 8, 30),
+            event(true, 4, 8, 0),
+            event(false, 4, 18, 0),
+        ];
+        let days = day_bounds(&data);
+        let at = |day, h, m| Utc.ymd(2021, 4, day).and_hms(h, m, 0);
+        assert_eq!(
+            Some((NaiveTime::from_hms(8, 0, 0), NaiveTime::from_hms(17, 0, 0))),
+            usual_day(&days, NaiveDate::from_ymd(2021, 4, 3))
+        );
+        assert_eq!(
+            Some(at(3, 17, 0)),
+            suggest_stop(&days, &data[4], data[5].time(true))
+        );
+        // the usual end is after the next event
+        assert_eq!(None, suggest_stop(&days, &data[4], at(3, 12, 0)));
+        assert_eq!(
+            Some(at(3, 8, 0)),
+            suggest_start(&days, &event(false, 3, 12, 0), Some(at(2, 17, 0)))
+        );
+        assert_eq!(
+            None,
+            suggest_start(&days, &event(false, 3, 7, 0), Some(at(2, 17, 0)))
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Showing the time goals and changing them in the config file, without having to edit it.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Duration;
+use structopt::StructOpt;
+use toml_edit::{value, DocumentMut, Item, Value};
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::format_hours_minutes;
+use crate::natural_time::parse_duration_setting;
+use crate::settings::{config_dir, Settings};
+
+#[derive(Debug, Clone, Copy)]
+pub enum GoalKind {
+    Daily,
+    Weekly,
+}
+
+impl FromStr for GoalKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: daily, weekly",
+                s
+            )),
+        }
+    }
+}
+
+impl GoalKind {
+    fn key(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    fn max_hours(self) -> i64 {
+        match self {
+            Self::Daily => 24,
+            Self::Weekly => 168,
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum GoalCommand {
+    /// show the daily and weekly goal
+    Show {
+        /// show the goals of this project
+        #[structopt(long)]
+        project: Option<String>,
+    },
+
+    /// set the daily or weekly goal in the config file
+    Set {
+        /// which goal to set. possible values: "daily" or "weekly"
+        kind: GoalKind,
+
+        /// the new goal, like "7h30m" or "38h"
+        goal: String,
+
+        /// set the goal of this project, which overrides the global goal when filtering by it
+        #[structopt(long)]
+        project: Option<String>,
+    },
+}
+
+fn parse_goal(kind: GoalKind, goal: &str) -> Result<Duration> {
+    let duration = parse_duration_setting(goal).ok_or_else(|| {
+        anyhow!(
+            "invalid goal \"{}\", expected a duration like \"7h30m\" or \"7:30\"",
+            goal.trim()
+        )
+    })?;
+    if duration > Duration::hours(kind.max_hours()) {
+        return Err(anyhow!(
+            "The {} goal can't be more than {} hours!",
+            kind.key(),
+            kind.max_hours()
+        ));
+    }
+    Ok(duration)
+}
+
+/// the config file which gets changed: the one given with --config-file or the global one
+fn config_path(config_file: &Option<String>) -> String {
+    match config_file {
+        Some(config_file) => config_file.clone(),
+        None => format!("{}/config.toml", config_dir()),
+    }
+}
+
+/// sets the goal in the config, keeping its formatting and comments
+fn set_goal(
+    config: &str,
+    kind: GoalKind,
+    goal: Duration,
+    project: &Option<String>,
+) -> Result<String> {
+    let mut document: DocumentMut = config.parse().context("could not parse the config file")?;
+    let root = match project {
+        Some(project) => &mut document["projects"][project.as_str()],
+        None => document.as_item_mut(),
+    };
+    let time_goal = &mut root["time_goal"][kind.key()];
+    // a goal in the older form with hours and minutes keeps it
+    if time_goal.is_table_like() {
+        set_value(&mut time_goal["hours"], goal.num_hours());
+        set_value(&mut time_goal["minutes"], goal.num_minutes() % 60);
+    } else {
+        set_value(time_goal, format_goal(goal));
+    }
+    // nested tables are created inline, but the config uses sections
+    for key in &["projects", "time_goal"] {
+        if let Some(item) = document.get_mut(key) {
+            set_implicit(item);
+        }
+    }
+    Ok(document.to_string())
+}
+
+/// the goal like it is written in the config, e.g. "7h30m"
+fn format_goal(goal: Duration) -> String {
+    match (goal.num_hours(), goal.num_minutes() % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// replaces the value of `item`, keeping the comment after it
+fn set_value<V: Into<Value>>(item: &mut Item, new_value: V) {
+    let decor = item.as_value().map(|value| value.decor().clone());
+    *item = value(new_value);
+    if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+        *value.decor_mut() = decor;
+    }
+}
+
+/// turns the inline tables in `item` into sections
+fn set_implicit(item: &mut Item) {
+    if let Some(table) = item.as_inline_table_mut() {
+        let mut table = std::mem::take(table).into_table();
+        table.set_implicit(true);
+        *item = Item::Table(table);
+    }
+    if let Some(table) = item.as_table_mut() {
+        for (_, item) in table.iter_mut() {
+            set_implicit(item);
+        }
+    }
+}
+
+fn show(settings: &Settings, project: &Option<String>) {
+    let settings = settings.for_project(project);
+    println!(
+        "Daily Goal: {}",
+        format_hours_minutes(settings.time_goal.daily)
+    );
+    let mut day = settings.week_starts_on;
+    for _ in 0..7 {
+        if let Some(goal) = settings.time_goal.per_day.get(&day) {
+            println!("Goal on {}: {}", day, format_hours_minutes(*goal));
+        }
+        day = day.succ();
+    }
+    println!(
+        "Weekly Goal: {}",
+        format_hours_minutes(settings.time_goal.weekly)
+    );
+}
+
+pub fn goal(settings: &Settings, command: GoalCommand, config_file: &Option<String>) -> Result<()> {
+    match command {
+        GoalCommand::Show { project } => {
+            show(settings, &project);
+            Ok(())
+        }
+        GoalCommand::Set {
+            kind,
+            goal,
+            project,
+        } => {
+            let goal = parse_goal(kind, &goal)?;
+            let path = config_path(config_file);
+            let config = match std::fs::read_to_string(&path) {
+                Ok(config) => config,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e).with_context(|| format!("could not read \"{}\"", path)),
+            };
+            if let Some(dir) = Path::new(&path).parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, set_goal(&config, kind, goal, &project)?)?;
+            println!(
+                "Set the {} goal{} to {} in \"{}\".",
+                kind.key(),
+                project
+                    .as_ref()
+                    .map(|project| format!(" of {}", project))
+                    .unwrap_or_default(),
+                format_hours_minutes(goal),
+                path
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_goal() {
+        let config = "# my goals\n[time_goal.daily]\nhours = 8 # full time\nminutes = 0\n";
+        let goal = parse_goal(GoalKind::Daily, "7h30m").unwrap();
+        assert_eq!(
+            "# my goals\n[time_goal.daily]\nhours = 7 # full time\nminutes = 30\n",
+            set_goal(config, GoalKind::Daily, goal, &None).unwrap()
+        );
+
+        let goal = parse_goal(GoalKind::Weekly, "20h").unwrap();
+        assert_eq!(
+            "[projects.website.time_goal]\nweekly = \"20h\"\n",
+            set_goal("", GoalKind::Weekly, goal, &Some("website".to_string())).unwrap()
+        );
+        let config = "[time_goal]\ndaily = \"8h\" # full time\n";
+        let goal = parse_goal(GoalKind::Daily, "7:30").unwrap();
+        assert_eq!(
+            "[time_goal]\ndaily = \"7h30m\" # full time\n",
+            set_goal(config, GoalKind::Daily, goal, &None).unwrap()
+        );
+        assert!(parse_goal(GoalKind::Daily, "25h").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Git hooks which start time tracking with the current branch (or the ticket in its name)
+//! whenever a branch is checked out or a commit is made, and the sleep hook (see `sleep`).
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use structopt::StructOpt;
+
+use std::path::{Path, PathBuf};
+
+use crate::settings::Settings;
+use crate::sleep;
+use crate::{TrackingData, TrackingEvent};
+
+/// marks hooks written by tt, so they can be updated and removed safely
+const MARKER: &str = "# installed by `tt hook install`";
+
+const HOOKS: &[&str] = &["post-checkout", "post-commit"];
+
+#[derive(Debug, StructOpt)]
+pub enum HookCommand {
+    /// install the hooks into the given repository [default: git.repositories from the config]
+    Install { repository: Option<PathBuf> },
+
+    /// remove the hooks from the given repository [default: git.repositories from the config]
+    Uninstall { repository: Option<PathBuf> },
+
+    /// start tracking for the current branch. this is called by the hooks
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Run,
+
+    /// install a hook which stops time tracking when the computer goes to sleep. the next call of
+    /// tt after waking up asks whether to continue. needs sudo on linux and sleepwatcher on macOS
+    InstallSleep,
+
+    /// remove the sleep hook
+    UninstallSleep,
+
+    /// stop time tracking before the computer sleeps. this is called by the sleep hook
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Sleep,
+}
+
+pub fn git(repository: &Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()
+        .context("could not run git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git failed in \"{}\": {}",
+            repository.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn repositories(settings: &Settings, repository: Option<PathBuf>) -> Result<Vec<PathBuf>> {
+    match repository {
+        Some(repository) => Ok(vec![repository]),
+        None if settings.git.repositories.is_empty() => Err(anyhow!(
+            "No repository given and git.repositories is empty in the config!"
+        )),
+        None => settings
+            .git
+            .repositories
+            .iter()
+            .map(|repository| Ok(PathBuf::from(shellexpand::full(repository)?.to_string())))
+            .collect(),
+    }
+}
+
+fn hooks_dir(repository: &Path) -> Result<PathBuf> {
+    Ok(repository.join(git(repository, &["rev-parse", "--git-path", "hooks"])?))
+}
+
+fn is_own_hook(path: &Path) -> bool {
+    matches!(std::fs::read_to_string(path), Ok(content) if content.contains(MARKER))
+}
+
+fn install(repository: &Path) -> Result<()> {
+    let hooks_dir = hooks_dir(repository)?;
+    std::fs::create_dir_all(&hooks_dir)?;
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        if path.exists() && !is_own_hook(&path) {
+            eprintln!(
+                "Skipping \"{}\", because it already exists. Add `tt hook run` to it manually.",
+                path.display()
+            );
+            continue;
+        }
+        std::fs::write(&path, format!("#!/bin/sh\n{}\ntt hook run\n", MARKER))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        println!("Installed \"{}\".", path.display());
+    }
+    Ok(())
+}
+
+fn uninstall(repository: &Path) -> Result<()> {
+    let hooks_dir = hooks_dir(repository)?;
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        if is_own_hook(&path) {
+            std::fs::remove_file(&path)?;
+            println!("Removed \"{}\".", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// finds a ticket id like "ABC-123" in the branch name
+fn ticket_from_branch(branch: &str) -> Option<&str> {
+    let bytes = branch.as_bytes();
+    let mut start = 0;
+    while start < bytes.len() {
+        let letters = bytes[start..]
+            .iter()
+            .take_while(|b| b.is_ascii_uppercase())
+            .count();
+        let dash = start + letters;
+        let digits = bytes.get(dash + 1..).map_or(0, |rest| {
+            rest.iter().take_while(|b| b.is_ascii_digit()).count()
+        });
+        if letters > 0 && bytes.get(dash) == Some(&b'-') && digits > 0 {
+            return Some(&branch[start..dash + 1 + digits]);
+        }
+        start += letters.max(1);
+    }
+    None
+}
+
+/// starts tracking for the current branch, unless it is already tracked
+fn run(settings: &Settings, data: &mut Vec<TrackingEvent>) -> Result<bool> {
+    let branch = git(Path::new("."), &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if branch == "HEAD" {
+        // detached head, e.g. during a rebase
+        return Ok(false);
+    }
+    let description = if settings.git.use_ticket {
+        ticket_from_branch(&branch).unwrap_or(&branch)
+    } else {
+        &branch
+    }
+    .to_string();
+
+    let now = Local::now().into();
+    match data.last() {
+        Some(event) if event.is_start() && event.description().as_ref() == Some(&description) => {
+            return Ok(false)
+        }
+        Some(event) if event.is_start() => {
+            data.push(TrackingEvent::Stop(TrackingData::new(None, now)))
+        }
+        _ => {}
+    }
+    data.push(TrackingEvent::Start(TrackingData::new(
+        Some(description),
+        now,
+    )));
+    Ok(true)
+}
+
+/// runs the hook command and returns whether the data was changed
+pub fn hook(
+    settings: &Settings,
+    data: &mut Vec<TrackingEvent>,
+    command: HookCommand,
+    data_path: &str,
+) -> Result<bool> {
+    match command {
+        HookCommand::Install { repository } => {
+            for repository in repositories(settings, repository)? {
+                install(&repository)?;
+            }
+            Ok(false)
+        }
+        HookCommand::Uninstall { repository } => {
+            for repository in repositories(settings, repository)? {
+                uninstall(&repository)?;
+            }
+            Ok(false)
+        }
+        HookCommand::Run => run(settings, data),
+        HookCommand::InstallSleep | HookCommand::UninstallSleep => {
+            unreachable!("the sleep hook is installed before the data is read")
+        }
+        HookCommand::Sleep => sleep::sleep(data, data_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticket_from_branch() {
+        assert_eq!(Some("ABC-123"), ticket_from_branch("feature/ABC-123-login"));
+        assert_eq!(Some("AB-1"), ticket_from_branch("AB-1"));
+        assert_eq!(Some("XY-42"), ticket_from_branch("fix/Foo-XY-42"));
+        assert_eq!(None, ticket_from_branch("feature/login-page"));
+        assert_eq!(None, ticket_from_branch("ABC-"));
+        assert_eq!(None, ticket_from_branch("main"));
+    }
+}
@@ -0,0 +1,67 @@
+//! The computer and the user new events are recorded on, so the entries can be told apart after
+//! syncing several machines.
+
+use iif::iif;
+
+use std::sync::OnceLock;
+
+use crate::settings::Settings;
+
+struct Origin {
+    host: Option<String>,
+    user: Option<String>,
+}
+
+/// set once by `init`, so new events can be created without the settings. without it, like in
+/// the tests, nothing is recorded
+static ORIGIN: OnceLock<Origin> = OnceLock::new();
+
+fn non_empty(name: String) -> Option<String> {
+    let name = name.trim();
+    iif!(name.is_empty(), None, Some(name.to_string()))
+}
+
+/// the hostname of this computer
+fn system_host() -> Option<String> {
+    if cfg!(windows) {
+        return std::env::var("COMPUTERNAME").ok().and_then(non_empty);
+    }
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| {
+            let output = std::process::Command::new("hostname").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).to_string())
+        })
+        .and_then(non_empty)
+        // macOS adds the domain of the local network
+        .map(|host| host.trim_end_matches(".local").to_string())
+}
+
+fn system_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .and_then(non_empty)
+}
+
+/// reads the host and the user to record from the config
+pub fn init(settings: &Settings) {
+    ORIGIN.get_or_init(|| Origin {
+        host: if settings.record_host {
+            non_empty(settings.host.clone()).or_else(system_host)
+        } else {
+            None
+        },
+        user: iif!(settings.record_user, system_user(), None),
+    });
+}
+
+/// the host which is recorded with new events
+pub fn host() -> Option<String> {
+    ORIGIN.get().and_then(|origin| origin.host.clone())
+}
+
+/// the user which is recorded with new events
+pub fn user() -> Option<String> {
+    ORIGIN.get().and_then(|origin| origin.user.clone())
+}
@@ -0,0 +1,364 @@
+//! Import of data from other time trackers.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use serde::Deserialize;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::storage::{parse_json_data, read_input};
+use crate::{TrackingData, TrackingEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Json,
+    Toggl,
+    Timewarrior,
+    Watson,
+}
+
+impl FromStr for ImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "toggl" => Ok(Self::Toggl),
+            "timewarrior" => Ok(Self::Timewarrior),
+            "watson" => Ok(Self::Watson),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: json, toggl, timewarrior, watson",
+                s
+            )),
+        }
+    }
+}
+
+/// a row of the detailed report csv of Toggl Track. all other columns are ignored
+#[derive(Debug, Deserialize)]
+struct TogglEntry {
+    #[serde(rename = "Description", default)]
+    description: String,
+    #[serde(rename = "Project", default)]
+    project: String,
+    #[serde(rename = "Tags", default)]
+    tags: String,
+    #[serde(rename = "Billable", default)]
+    billable: String,
+    #[serde(rename = "Start date")]
+    start_date: String,
+    #[serde(rename = "Start time")]
+    start_time: String,
+    #[serde(rename = "End date")]
+    end_date: String,
+    #[serde(rename = "End time")]
+    end_time: String,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    Some(s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn parse_local(date: &str, time: &str) -> Result<DateTime<Utc>> {
+    Ok(Local
+        .datetime_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S")
+        .with_context(|| format!("invalid date or time \"{} {}\"", date, time))?
+        .with_timezone(&Utc))
+}
+
+impl TogglEntry {
+    fn into_events(self) -> Result<[TrackingEvent; 2]> {
+        let start = parse_local(&self.start_date, &self.start_time)?;
+        let end = parse_local(&self.end_date, &self.end_time)?;
+        Ok([
+            TrackingEvent::Start(TrackingData {
+                description: non_empty(&self.description),
+                project: non_empty(&self.project),
+                tags: self.tags.split(',').filter_map(non_empty).collect(),
+                // without a paid plan, toggl reports all time as not billable, so only "Yes" is
+                // taken over and everything else uses the default of the project
+                billable: Some(true).filter(|_| self.billable.trim() == "Yes"),
+                ..TrackingData::new(None, start)
+            }),
+            TrackingEvent::Stop(TrackingData::new(None, end)),
+        ])
+    }
+}
+
+fn read_toggl<R: std::io::Read>(reader: R) -> Result<Vec<TrackingEvent>> {
+    let mut events = Vec::new();
+    for (i, entry) in csv::Reader::from_reader(reader)
+        .into_deserialize::<TogglEntry>()
+        .enumerate()
+    {
+        let entry = entry.with_context(|| format!("could not read entry {}", i + 1))?;
+        events.extend(entry.into_events()?.iter().cloned());
+    }
+    Ok(events)
+}
+
+/// the words of the rest of a line of a timewarrior data file, with whether they were quoted. tags
+/// with spaces and the annotation are quoted, with `\"` for a quote within them
+fn timewarrior_words(line: &str) -> Vec<(String, bool)> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' => {
+                let mut word = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => word.extend(chars.next()),
+                        '"' => break,
+                        c => word.push(c),
+                    }
+                }
+                words.push((word, true));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    word.push(c);
+                }
+                words.push((word, false));
+            }
+        }
+    }
+    words
+}
+
+fn parse_timewarrior_time(time: &str) -> Result<DateTime<Utc>> {
+    Utc.datetime_from_str(time, "%Y%m%dT%H%M%SZ")
+        .with_context(|| format!("invalid time \"{}\"", time))
+}
+
+/// an interval of a timewarrior data file, like
+/// `inc 20210401T090000Z - 20210401T103000Z # website bug # "fix login"`.
+/// the tags become the tags and the annotation the description. an interval without an end is
+/// still running
+fn parse_timewarrior_line(line: &str) -> Result<Vec<TrackingEvent>> {
+    let rest = line
+        .strip_prefix("inc ")
+        .ok_or_else(|| anyhow!("not an interval: \"{}\"", line))?;
+    let mut words = timewarrior_words(rest).into_iter();
+    let start = match words.next() {
+        Some((start, _)) => parse_timewarrior_time(&start)?,
+        None => return Err(anyhow!("the interval has no start: \"{}\"", line)),
+    };
+    let mut words = words.peekable();
+    let end = match words.next_if(|(word, quoted)| word == "-" && !quoted) {
+        Some(_) => match words.next() {
+            Some((end, _)) => Some(parse_timewarrior_time(&end)?),
+            None => return Err(anyhow!("the interval has no end: \"{}\"", line)),
+        },
+        None => None,
+    };
+    let is_separator = |(word, quoted): &(String, bool)| word == "#" && !quoted;
+    let mut tags = Vec::new();
+    let mut annotation = None;
+    if words.next_if(is_separator).is_some() {
+        tags.extend(
+            words
+                .by_ref()
+                .take_while(|word| !is_separator(word))
+                .map(|(tag, _)| tag),
+        );
+        let rest: Vec<_> = words.map(|(word, _)| word).collect();
+        annotation = non_empty(&rest.join(" "));
+    }
+    let mut events = vec![TrackingEvent::Start(TrackingData {
+        tags,
+        ..TrackingData::new(annotation, start)
+    })];
+    events.extend(end.map(|end| TrackingEvent::Stop(TrackingData::new(None, end))));
+    Ok(events)
+}
+
+/// reads the intervals of timewarrior data files (`~/.timewarrior/data/*.data`)
+fn read_timewarrior(data: &str) -> Result<Vec<TrackingEvent>> {
+    let mut events = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        events.extend(
+            parse_timewarrior_line(line)
+                .with_context(|| format!("could not read line {}", i + 1))?,
+        );
+    }
+    events.sort_by_key(|e| e.time(true));
+    Ok(events)
+}
+
+fn watson_time(value: &serde_json::Value) -> Result<DateTime<Utc>> {
+    value
+        .as_i64()
+        .or_else(|| value.as_f64().map(|time| time as i64))
+        .map(|time| Utc.timestamp(time, 0))
+        .ok_or_else(|| anyhow!("invalid time {}", value))
+}
+
+/// reads the frames file of Watson (`~/.config/watson/frames`). a frame is a list of the start,
+/// the stop, the project, the id, the tags, the time of the last change and, in newer versions,
+/// a note, which becomes the description
+fn read_watson(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    let frames: Vec<Vec<serde_json::Value>> =
+        serde_json::from_slice(data).context("invalid Watson frames")?;
+    let mut events = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let context = || format!("could not read frame {}", i + 1);
+        if frame.len() < 3 {
+            return Err(anyhow!("the frame is incomplete")).with_context(context);
+        }
+        let start = watson_time(&frame[0]).with_context(context)?;
+        let stop = watson_time(&frame[1]).with_context(context)?;
+        let tags = frame
+            .get(4)
+            .and_then(|tags| tags.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|tag| tag.as_str())
+                    .filter_map(non_empty)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let note = frame
+            .get(6)
+            .and_then(|note| note.as_str())
+            .and_then(non_empty);
+        events.push(TrackingEvent::Start(TrackingData {
+            project: frame[2].as_str().and_then(non_empty),
+            tags,
+            ..TrackingData::new(note, start)
+        }));
+        events.push(TrackingEvent::Stop(TrackingData::new(None, stop)));
+    }
+    events.sort_by_key(|e| e.time(true));
+    Ok(events)
+}
+
+/// json starts with the array or object of the events and Watson frames with an array of arrays.
+/// timewarrior data starts with an interval, everything else is taken as csv
+fn detect_format(data: &[u8]) -> ImportFormat {
+    let mut content = data.iter().filter(|b| !b.is_ascii_whitespace());
+    match (content.next(), content.next()) {
+        (Some(b'['), Some(b'[')) => ImportFormat::Watson,
+        (Some(b'['), _) | (Some(b'{'), _) => ImportFormat::Json,
+        _ if data.trim_ascii_start().starts_with(b"inc ") => ImportFormat::Timewarrior,
+        _ => ImportFormat::Toggl,
+    }
+}
+
+/// reads the events to import from the file at `path` or from stdin, if the path is "-". without
+/// a format, it's detected from the content. returns the events with the format they were read in.
+pub fn import<P: AsRef<Path>>(
+    path: P,
+    format: Option<ImportFormat>,
+) -> Result<(ImportFormat, Vec<TrackingEvent>)> {
+    let data = read_input(path)?;
+    let format = format.unwrap_or_else(|| detect_format(&data));
+    let events = match format {
+        ImportFormat::Json => parse_json_data(&data)?,
+        // the detailed report csv exported from Toggl Track
+        ImportFormat::Toggl => read_toggl(&data[..])?,
+        ImportFormat::Timewarrior => read_timewarrior(&String::from_utf8_lossy(&data))?,
+        ImportFormat::Watson => read_watson(&data)?,
+    };
+    Ok((format, events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_toggl() {
+        let csv = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount ()
+me,me@example.com,,website,,\"fix login, again\",Yes,2021-04-01,09:00:00,2021-04-01,10:30:00,01:30:00,\"bug, urgent\",
+me,me@example.com,,,,,No,2021-04-01,11:00:00,2021-04-01,11:15:00,00:15:00,,
+";
+        let events = read_toggl(csv.as_bytes()).unwrap();
+        let at = |h, m| Local.ymd(2021, 4, 1).and_hms(h, m, 0).with_timezone(&Utc);
+        assert_eq!(
+            vec![
+                TrackingEvent::Start(TrackingData {
+                    description: Some("fix login, again".to_string()),
+                    project: Some("website".to_string()),
+                    tags: vec!["bug".to_string(), "urgent".to_string()],
+                    billable: Some(true),
+                    ..TrackingData::new(None, at(9, 0))
+                }),
+                TrackingEvent::Stop(TrackingData::new(None, at(10, 30))),
+                TrackingEvent::Start(TrackingData::new(None, at(11, 0))),
+                TrackingEvent::Stop(TrackingData::new(None, at(11, 15))),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(ImportFormat::Json, detect_format(b"\n  [{\"Start\": {}}]"));
+        assert_eq!(ImportFormat::Toggl, detect_format(b"User,Email,Client"));
+        assert_eq!(
+            ImportFormat::Watson,
+            detect_format(b"[\n [1617267600, 1617273000")
+        );
+        assert_eq!(
+            ImportFormat::Timewarrior,
+            detect_format(b"inc 20210401T090000Z")
+        );
+    }
+
+    #[test]
+    fn test_read_timewarrior() {
+        let data = r#"
+inc 20210401T090000Z - 20210401T103000Z # website "bug \"42\"" # "fix login"
+inc 20210401T110000Z - 20210401T111500Z
+inc 20210401T120000Z # review
+"#;
+        let at = |h, m| Utc.ymd(2021, 4, 1).and_hms(h, m, 0);
+        assert_eq!(
+            vec![
+                TrackingEvent::Start(TrackingData {
+                    tags: vec!["website".to_string(), "bug \"42\"".to_string()],
+                    ..TrackingData::new(Some("fix login".to_string()), at(9, 0))
+                }),
+                TrackingEvent::Stop(TrackingData::new(None, at(10, 30))),
+                TrackingEvent::Start(TrackingData::new(None, at(11, 0))),
+                TrackingEvent::Stop(TrackingData::new(None, at(11, 15))),
+                TrackingEvent::Start(TrackingData {
+                    tags: vec!["review".to_string()],
+                    ..TrackingData::new(None, at(12, 0))
+                }),
+            ],
+            read_timewarrior(data).unwrap()
+        );
+        assert!(read_timewarrior("inc yesterday").is_err());
+    }
+
+    #[test]
+    fn test_read_watson() {
+        let frames = br#"[
+            [1617267600, 1617273000, "website", "a1b2", ["bug", "urgent"], 1617273000],
+            [1617274800, 1617275700, "", "c3d4", [], 1617275700, "review"]
+        ]"#;
+        let at = |h, m| Utc.ymd(2021, 4, 1).and_hms(h, m, 0);
+        assert_eq!(
+            vec![
+                TrackingEvent::Start(TrackingData {
+                    project: Some("website".to_string()),
+                    tags: vec!["bug".to_string(), "urgent".to_string()],
+                    ..TrackingData::new(None, at(9, 0))
+                }),
+                TrackingEvent::Stop(TrackingData::new(None, at(10, 30))),
+                TrackingEvent::Start(TrackingData::new(Some("review".to_string()), at(11, 0))),
+                TrackingEvent::Stop(TrackingData::new(None, at(11, 15))),
+            ],
+            read_watson(frames).unwrap()
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! `tt start --interactive`, which asks for the description, the project, the tags and the start
+//! time one after the other, for those who don't want to remember the flags. The values used
+//! before are suggested: tab completes the text with the most recent value starting with it and
+//! the arrow keys go through them, the most recent first.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Completion, History, Input};
+
+use std::io::{self, IsTerminal};
+
+use crate::{parse_date_time, TrackingEvent};
+
+/// the values used before, the most recent first
+struct Suggestions {
+    values: Vec<String>,
+    /// whether the input is a comma separated list, of which only the last value is completed
+    list: bool,
+}
+
+/// the values without repetitions, in the order they appear first
+fn distinct(values: impl Iterator<Item = String>) -> Vec<String> {
+    let mut distinct = Vec::new();
+    for value in values {
+        if !distinct.contains(&value) {
+            distinct.push(value);
+        }
+    }
+    distinct
+}
+
+impl Completion for Suggestions {
+    fn get(&self, input: &str) -> Option<String> {
+        let (done, last) = match input.rfind(',').filter(|_| self.list) {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input),
+        };
+        let typed = last.trim_start();
+        if typed.is_empty() {
+            return None;
+        }
+        self.values
+            .iter()
+            .find(|value| value.starts_with(typed) && *value != typed)
+            .map(|value| format!("{}{}{}", done, &last[..last.len() - typed.len()], value))
+    }
+}
+
+impl History<String> for Suggestions {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.values.get(pos).cloned()
+    }
+
+    // only the values of the data are suggested
+    fn write(&mut self, _value: &String) {}
+}
+
+/// asks for a text, which can be left empty, with the values used before as suggestions
+fn ask(
+    prompt: &str,
+    initial: Option<String>,
+    values: Vec<String>,
+    list: bool,
+) -> Result<Option<String>> {
+    let completion = Suggestions {
+        values: values.clone(),
+        list,
+    };
+    let mut history = Suggestions { values, list };
+    let text: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .with_initial_text(initial.unwrap_or_default())
+        .allow_empty(true)
+        .completion_with(&completion)
+        .history_with(&mut history)
+        .interact_text()?;
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
+
+/// what `tt start` was asked to start
+pub struct Entry {
+    pub description: Option<String>,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub at: Option<String>,
+}
+
+/// asks for every value of the entry, with the values given on the command line as initial text
+pub fn ask_entry(data: &[TrackingEvent], entry: Entry) -> Result<Entry> {
+    let Entry {
+        description,
+        project,
+        tags,
+        at,
+    } = entry;
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("--interactive needs an interactive terminal!"));
+    }
+    let starts: Vec<_> = data
+        .iter()
+        .rev()
+        .filter(|e| e.is_start())
+        .map(|e| e.data())
+        .collect();
+
+    let descriptions = distinct(starts.iter().filter_map(|data| data.description.clone()));
+    let description = ask("Description", description, descriptions, false)?;
+
+    // a description used before gets the project it had the last time
+    let previous = starts
+        .iter()
+        .find(|data| description.is_some() && data.description == description);
+    let project = project.or_else(|| previous.and_then(|data| data.project.clone()));
+    let projects = distinct(starts.iter().filter_map(|data| data.project.clone()));
+    let project = ask("Project", project, projects, false)?;
+
+    let all_tags = distinct(starts.iter().flat_map(|data| data.tags.clone()));
+    let tags = ask(
+        "Tags (comma separated)",
+        Some(tags.join(", ")),
+        all_tags,
+        true,
+    )?;
+    let tags = tags
+        .iter()
+        .flat_map(|tags| tags.split(','))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect();
+
+    let at: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Start (e.g. \"08:30\" or \"15m ago\", empty for now)")
+        .with_initial_text(at.unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|text: &String| -> Result<(), String> {
+            if text.trim().is_empty() {
+                return Ok(());
+            }
+            match parse_date_time(text.trim()) {
+                Ok(time) if time > Utc::now() => Err("The start can't be in the future!".into()),
+                Ok(_) => Ok(()),
+                Err(_) => Err("Could not parse the time!".into()),
+            }
+        })
+        .interact_text()?;
+    let at = Some(at.trim().to_string()).filter(|at| !at.is_empty());
+    Ok(Entry {
+        description,
+        project,
+        tags,
+        at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion() {
+        let values = vec!["review", "refactor parser", "review"];
+        let suggestions = Suggestions {
+            values: distinct(values.into_iter().map(String::from)),
+            list: false,
+        };
+        assert_eq!(vec!["review", "refactor parser"], suggestions.values);
+        assert_eq!(Some("review".to_string()), suggestions.get("rev"));
+        assert_eq!(Some("refactor parser".to_string()), suggestions.get("ref"));
+        assert_eq!(None, suggestions.get("review"));
+        assert_eq!(None, suggestions.get(""));
+
+        let tags = Suggestions {
+            values: vec!["urgent".to_string(), "call".to_string()],
+            list: true,
+        };
+        assert_eq!(Some("call, urgent".to_string()), tags.get("call, ur"));
+        assert_eq!(Some("call".to_string()), tags.get("c"));
+    }
+}
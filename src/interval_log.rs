@@ -0,0 +1,134 @@
+//! `tt log`, which pairs the starts and stops and shows one line per interval with the total of
+//! every day. This is easier to review than the single events of `tt list`.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use crate::color::Style;
+use crate::settings::Settings;
+use crate::table::{Cell, Column, Table};
+use crate::{filter_events, format_hours_minutes, locale, pager, FilterData, TrackingEvent};
+
+/// an interval, with the stop `None` while it is running
+type Interval<'a> = (&'a TrackingEvent, Option<&'a TrackingEvent>);
+
+/// the intervals grouped by the day they started on
+fn intervals_per_day(data: &[TrackingEvent]) -> Vec<(NaiveDate, Vec<Interval<'_>>)> {
+    let mut days: Vec<(NaiveDate, Vec<Interval>)> = Vec::new();
+    let mut events = data.iter();
+    while let Some(start) = events.find(|e| e.is_start()) {
+        let stop = events.find(|e| e.is_stop());
+        let date = start.local_date();
+        match days.last_mut() {
+            Some((day, intervals)) if *day == date => intervals.push((start, stop)),
+            _ => days.push((date, vec![(start, stop)])),
+        }
+    }
+    days
+}
+
+fn duration((start, stop): &Interval) -> Duration {
+    stop.map_or_else(Utc::now, |stop| stop.time(true)) - start.time(true)
+}
+
+pub fn log(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    wide: bool,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let days = intervals_per_day(&filtered_data);
+    if days.is_empty() {
+        println!("No entries found!");
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::left("Date"),
+        Column::left("Time"),
+        Column::right("Duration"),
+        Column::left("Description").truncated(),
+        Column::left("Project"),
+        Column::left("Tags").truncated(),
+    ]);
+    let mut total = Duration::zero();
+    for (_, intervals) in &days {
+        let mut day_total = Duration::zero();
+        for (i, interval) in intervals.iter().enumerate() {
+            let (start, stop) = interval;
+            let data = start.data();
+            let until = match stop {
+                Some(stop) => locale::format_time(&stop.local_time()),
+                None => "now".to_string(),
+            };
+            let date = if i == 0 {
+                locale::format_date(&start.local_time())
+            } else {
+                String::new()
+            };
+            let tags: Vec<_> = data.tags.iter().map(|t| format!("#{}", t)).collect();
+            table.add_styled_row(
+                vec![
+                    date.into(),
+                    format!("{} - {}", locale::format_time(&start.local_time()), until).into(),
+                    format_hours_minutes(duration(interval)).into(),
+                    data.description.clone().unwrap_or_default().into(),
+                    data.project.clone().unwrap_or_default().into(),
+                    tags.join(" ").into(),
+                ],
+                stop.is_none().then_some(Style::Active),
+            );
+            day_total += duration(interval);
+        }
+        table.add_styled_row(
+            vec![
+                Cell::from(""),
+                "Total".into(),
+                format_hours_minutes(day_total).into(),
+            ],
+            Some(Style::Header),
+        );
+        total += day_total;
+    }
+    if days.len() > 1 {
+        table.add_styled_row(
+            vec![
+                Cell::from("Total"),
+                format!("{} days", days.len()).into(),
+                format_hours_minutes(total).into(),
+            ],
+            Some(Style::Header),
+        );
+    }
+    pager::print_lines(&table.render(wide))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_intervals_per_day() {
+        let event = |day, h| TrackingData {
+            offset: Some(0),
+            ..TrackingData::new(None, Utc.ymd(2024, 3, day).and_hms(h, 0, 0))
+        };
+        let data = vec![
+            TrackingEvent::Start(event(4, 9)),
+            TrackingEvent::Pause(event(4, 12)),
+            TrackingEvent::Resume(event(4, 13)),
+            TrackingEvent::Stop(event(4, 17)),
+            TrackingEvent::Start(event(5, 9)),
+        ];
+        let days = intervals_per_day(&data);
+        assert_eq!(2, days.len());
+        assert_eq!(NaiveDate::from_ymd(2024, 3, 4), days[0].0);
+        assert_eq!(
+            vec![Duration::hours(3), Duration::hours(4)],
+            days[0].1.iter().map(duration).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(&data[4], None)], days[1].1);
+    }
+}
@@ -0,0 +1,183 @@
+//! Every change to the data is recorded in a journal file next to the data file, so it can be
+//! undone and redone. A change only stores the events which were removed and added, which keeps
+//! the journal small for the common case of appending a single event.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::encryption;
+use crate::{get_human_readable, TrackingEvent};
+
+/// the maximum number of changes which can be undone
+const MAX_CHANGES: usize = 100;
+
+#[derive(Default, Serialize, Deserialize)]
+struct Journal {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Change {
+    removed: Vec<TrackingEvent>,
+    added: Vec<TrackingEvent>,
+}
+
+fn count_events(events: &[TrackingEvent]) -> HashMap<&TrackingEvent, usize> {
+    let mut counts = HashMap::new();
+    for event in events {
+        *counts.entry(event).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// returns all events from `events` which are not in `other`, respecting duplicates
+pub fn difference(events: &[TrackingEvent], other: &[TrackingEvent]) -> Vec<TrackingEvent> {
+    let mut other = count_events(other);
+    events
+        .iter()
+        .filter(|event| match other.get_mut(event) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+impl Change {
+    fn between(old: &[TrackingEvent], new: &[TrackingEvent]) -> Self {
+        Self {
+            removed: difference(old, new),
+            added: difference(new, old),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    fn reversed(self) -> Self {
+        Self {
+            removed: self.added,
+            added: self.removed,
+        }
+    }
+
+    fn apply(&self, data: &mut Vec<TrackingEvent>) {
+        let remaining = difference(data, &self.removed);
+        *data = remaining;
+        data.extend(self.added.iter().cloned());
+        data.sort_by_key(|e| e.time(true));
+    }
+
+    fn print(&self) {
+        for line in get_human_readable(&self.removed) {
+            println!("- {}", line);
+        }
+        for line in get_human_readable(&self.added) {
+            println!("+ {}", line);
+        }
+    }
+}
+
+pub fn journal_path(data_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.journal", data_path))
+}
+
+/// the journal contains events as well, so it gets encrypted like the data file
+fn read_journal<P: AsRef<Path>>(path: P) -> Journal {
+    std::fs::read(path)
+        .ok()
+        .and_then(|journal| encryption::decode(journal).ok())
+        .and_then(|journal| serde_json::from_slice(&journal).ok())
+        .unwrap_or_default()
+}
+
+fn write_journal<P: AsRef<Path>>(path: P, journal: &Journal) -> Result<()> {
+    let journal = serde_json::to_vec(journal).expect("could not serialize journal");
+    Ok(std::fs::write(path, encryption::encode(journal)?)?)
+}
+
+/// records the change from `old` to `new` and clears everything that could be redone
+pub fn record<P: AsRef<Path>>(path: P, old: &[TrackingEvent], new: &[TrackingEvent]) -> Result<()> {
+    let change = Change::between(old, new);
+    if change.is_empty() {
+        return Ok(());
+    }
+
+    let mut journal = read_journal(&path);
+    journal.undo.push(change);
+    if journal.undo.len() > MAX_CHANGES {
+        journal.undo.remove(0);
+    }
+    journal.redo.clear();
+    write_journal(path, &journal)
+}
+
+pub fn undo<P: AsRef<Path>>(path: P, data: &mut Vec<TrackingEvent>) -> Result<()> {
+    let mut journal = read_journal(&path);
+    match journal.undo.pop() {
+        Some(change) => {
+            let change = change.reversed();
+            change.apply(data);
+            println!("Undone:");
+            change.print();
+            journal.redo.push(change.reversed());
+            write_journal(path, &journal)
+        }
+        None => {
+            eprintln!("Nothing to undo!");
+            Ok(())
+        }
+    }
+}
+
+pub fn redo<P: AsRef<Path>>(path: P, data: &mut Vec<TrackingEvent>) -> Result<()> {
+    let mut journal = read_journal(&path);
+    match journal.redo.pop() {
+        Some(change) => {
+            change.apply(data);
+            println!("Redone:");
+            change.print();
+            journal.undo.push(change);
+            write_journal(path, &journal)
+        }
+        None => {
+            eprintln!("Nothing to redo!");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_change_can_be_reversed() {
+        let event = |h| {
+            TrackingEvent::Start(TrackingData::new(
+                None,
+                Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+            ))
+        };
+        let old = vec![event(8), event(9), event(9)];
+        let new = vec![event(8), event(9), event(10)];
+
+        let change = Change::between(&old, &new);
+        assert_eq!(vec![event(9)], change.removed);
+        assert_eq!(vec![event(10)], change.added);
+
+        let mut data = new.clone();
+        change.reversed().apply(&mut data);
+        assert_eq!(old, data);
+    }
+}
@@ -0,0 +1,259 @@
+//! `tt kiosk`, a fullscreen time clock for a dedicated screen, e.g. a Raspberry Pi at a workbench.
+//! A single key starts or stops time tracking and every start and stop is written to the data file
+//! right away, so nothing is lost when the screen is turned off.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use dialoguer::console::{Key, Term};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+
+use crate::color::{paint, Style};
+use crate::settings::Settings;
+use crate::storage::{lock, read_data_if_exists};
+use crate::{
+    format_duration, start_tracking, stop_tracking, write_changes, CalculationOptions,
+    CurrentSession, SessionState, TrackingEvent,
+};
+
+/// how often the clock is redrawn
+const REDRAW_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// the digits of the clock, 3 columns wide and 5 rows high
+fn glyph(c: char) -> [&'static str; 5] {
+    match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        _ => [" "; 5],
+    }
+}
+
+/// `text` in large digits, with every block `scale` times as wide and high
+fn big(text: &str, scale: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for row in 0..5 {
+        let line: Vec<String> = text
+            .chars()
+            .map(|c| {
+                glyph(c)[row]
+                    .chars()
+                    .map(|block| block.to_string().repeat(scale * 2))
+                    .collect()
+            })
+            .collect();
+        for _ in 0..scale {
+            lines.push(line.join(&" ".repeat(scale * 2)));
+        }
+    }
+    lines
+}
+
+/// starts or stops time tracking and saves it right away. returns what was done
+fn toggle(
+    settings: &Settings,
+    path: &str,
+    entry: &(Option<String>, Option<String>, Vec<String>),
+) -> Result<String> {
+    let _lock = lock(path)?;
+    let mut data = read_data_if_exists(path)?;
+    let original_data = data.clone();
+    let running = data.last().is_some_and(|e| e.is_start());
+    if running {
+        stop_tracking(&mut data, None, None, false)?;
+    } else {
+        let (description, project, tags) = entry.clone();
+        start_tracking(
+            settings,
+            &mut data,
+            description,
+            project,
+            tags,
+            None,
+            None,
+            false,
+        )?;
+    }
+    write_changes(settings, path, &original_data, &mut data, true)?;
+    let time = data
+        .last()
+        .map(TrackingEvent::local_time)
+        .map_or_else(String::new, |time| time.format("%H:%M").to_string());
+    Ok(format!(
+        "{} at {}",
+        if running { "Stopped" } else { "Started" },
+        time
+    ))
+}
+
+/// the lines of the screen: the elapsed time of the running session or the time of day, the state,
+/// the work time of today and the keys
+fn screen(session: &CurrentSession, message: &str, (height, width): (usize, usize)) -> Vec<String> {
+    let running = session.state == SessionState::Running;
+    let clock = if running {
+        let elapsed = session.elapsed;
+        format!(
+            "{:02}:{:02}:{:02}",
+            elapsed.num_hours(),
+            elapsed.num_minutes() % 60,
+            elapsed.num_seconds() % 60
+        )
+    } else {
+        Local::now().format("%H:%M:%S").to_string()
+    };
+    // as large as the clock and the 6 lines below it fit
+    let clock_width = big(&clock, 1)[0].chars().count();
+    let scale = (width / clock_width)
+        .min(height.saturating_sub(6) / 5)
+        .max(1);
+    let mut lines = big(&clock, scale);
+
+    let mut state = format!("{} {}", session.state.symbol(), session.state.name());
+    if let Some(data) = session.data.as_ref().filter(|_| running) {
+        if let Some(description) = &data.description {
+            state.push_str(&format!("  {}", description));
+        }
+        if let Some(project) = &data.project {
+            state.push_str(&format!("  [{}]", project));
+        }
+    }
+    lines.extend(vec![
+        String::new(),
+        state,
+        format!("today {}", format_duration(session.today)),
+        String::new(),
+        format!(
+            "[space] {}   [q] quit",
+            if running { "stop" } else { "start" }
+        ),
+        message.to_string(),
+    ]);
+    lines
+}
+
+/// overwrites the screen with the centered lines, without clearing it first, so it doesn't flicker
+fn draw(term: &Term, lines: &[String], running: bool) -> Result<()> {
+    let (height, width) = term.size();
+    let (height, width) = (usize::from(height), usize::from(width));
+    let top = height.saturating_sub(lines.len()) / 2;
+    let mut output = Vec::new();
+    output.resize(top, " ".repeat(width));
+    for line in lines {
+        let length = line.chars().count();
+        let left = width.saturating_sub(length) / 2;
+        let right = width.saturating_sub(length + left);
+        let line = format!("{}{}{}", " ".repeat(left), line, " ".repeat(right));
+        // only the clock is colored
+        output.push(if running && line.contains('█') {
+            paint(line, Style::Active)
+        } else {
+            line
+        });
+    }
+    term.move_cursor_to(0, 0)?;
+    term.write_str(&output.join("\n"))?;
+    term.clear_to_end_of_screen()?;
+    Ok(())
+}
+
+pub fn kiosk(
+    settings: &Settings,
+    path: &str,
+    description: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+) -> Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err(anyhow!("tt kiosk needs an interactive terminal!"));
+    }
+    let quit = Arc::new(AtomicBool::new(false));
+    let handler_quit = quit.clone();
+    ctrlc::set_handler(move || handler_quit.store(true, Ordering::SeqCst))?;
+
+    // the keys are read on their own thread, so the clock keeps running while waiting for them
+    let (sender, keys) = mpsc::channel();
+    let reader = term.clone();
+    std::thread::spawn(move || {
+        while let Ok(key) = reader.read_key() {
+            let last = matches!(key, Key::Char('q') | Key::Escape | Key::CtrlC);
+            if sender.send(key).is_err() || last {
+                break;
+            }
+        }
+    });
+
+    let options = CalculationOptions {
+        include_seconds: true,
+        ..CalculationOptions::default()
+    };
+    let entry = (description, project, tags);
+    let mut message = String::new();
+    term.clear_screen()?;
+    term.hide_cursor()?;
+    let result = loop {
+        if quit.load(Ordering::SeqCst) {
+            break Ok(());
+        }
+        let session = CurrentSession::read_with_options(settings, path, options);
+        let (height, width) = term.size();
+        let lines = screen(
+            &session,
+            &message,
+            (usize::from(height), usize::from(width)),
+        );
+        if let Err(e) = draw(&term, &lines, session.state == SessionState::Running) {
+            break Err(e);
+        }
+
+        match keys.recv_timeout(REDRAW_INTERVAL) {
+            Ok(Key::Char(' ')) | Ok(Key::Enter) => {
+                message = match toggle(settings, path, &entry) {
+                    Ok(message) => message,
+                    Err(e) => format!("Error: {}", e),
+                };
+            }
+            Ok(Key::Char('q')) | Ok(Key::Escape) | Ok(Key::CtrlC) => break Ok(()),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break Ok(()),
+        }
+    };
+    term.clear_screen()?;
+    term.show_cursor()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big() {
+        assert_eq!(
+            vec![
+                "██████    ",
+                "██  ██  ██",
+                "██  ██    ",
+                "██  ██  ██",
+                "██████    "
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>(),
+            big("0:", 1)
+        );
+        let scaled = big("1", 2);
+        assert_eq!(10, scaled.len());
+        assert_eq!("        ████", scaled[0]);
+    }
+}
@@ -0,0 +1,78 @@
+//! `tt status --watch`, which keeps a single line with the elapsed time of the current session,
+//! the work time of today and the time remaining to reach the goal up to date. Like the
+//! statusbar, it only reads the end of the data file, once every second.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::event_hooks;
+use crate::settings::Settings;
+use crate::{format_duration, CalculationOptions, CurrentSession, SessionState};
+
+/// how often the line is redrawn
+const REDRAW_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// how often Ctrl-C is checked while waiting for the next redraw
+const QUIT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+fn line(settings: &Settings, session: &CurrentSession) -> String {
+    let mut parts = vec![session.state.symbol().to_string()];
+    match &session.data {
+        Some(data) if session.state != SessionState::Stopped => {
+            if let Some(description) = &data.description {
+                parts.push(description.clone());
+            }
+            if let Some(project) = &data.project {
+                parts.push(format!("[{}]", project));
+            }
+        }
+        _ => parts.push(session.state.name().to_string()),
+    }
+    if session.state == SessionState::Running {
+        parts.push(format!("elapsed {}", format_duration(session.elapsed)));
+    }
+    parts.push(format!("today {}", format_duration(session.today)));
+    let goal = settings.goal_for_date(Local::today().naive_local());
+    if goal > Duration::zero() {
+        let remaining = goal - session.today;
+        if remaining > Duration::zero() {
+            parts.push(format!("remaining {}", format_duration(remaining)));
+        } else {
+            parts.push("goal reached".to_string());
+        }
+    }
+    parts.join("  ")
+}
+
+pub fn watch(settings: &Settings, path: &str) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_running = running.clone();
+    ctrlc::set_handler(move || handler_running.store(false, Ordering::SeqCst))?;
+
+    let options = CalculationOptions {
+        include_seconds: true,
+        ..CalculationOptions::default()
+    };
+    // the previous line is overwritten, so a shorter line has to clear the rest of it
+    let mut width = 0;
+    while running.load(Ordering::SeqCst) {
+        let session = CurrentSession::read_with_options(settings, path, options);
+        let line = line(settings, &session);
+        print!("\r{:width$}", line, width = width);
+        io::stdout().flush()?;
+        width = line.chars().count();
+        event_hooks::check_goal(settings, path)?;
+
+        let mut waited = std::time::Duration::from_secs(0);
+        while waited < REDRAW_INTERVAL && running.load(Ordering::SeqCst) {
+            std::thread::sleep(QUIT_CHECK_INTERVAL);
+            waited += QUIT_CHECK_INTERVAL;
+        }
+    }
+    println!();
+    Ok(())
+}
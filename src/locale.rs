@@ -0,0 +1,148 @@
+//! Formatting of the dates and times tt shows, with the formats and the language of month and
+//! day names from the config.
+
+use anyhow::{anyhow, Result};
+use chrono::format::{Fixed, Item, StrftimeItems};
+use chrono::prelude::*;
+use pure_rust_locales::{locale_match, Locale};
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use crate::settings::Settings;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+
+struct Format {
+    locale: Locale,
+    date_format: String,
+    time_format: String,
+}
+
+/// set once by `init`, so the formatting functions don't need the settings
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+fn check_format(name: &str, format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(anyhow!("invalid {} \"{}\"", name, format));
+    }
+    Ok(())
+}
+
+/// reads the locale and the formats from the config
+pub fn init(settings: &Settings) -> Result<()> {
+    let locale: Locale = settings
+        .locale
+        .parse()
+        .map_err(|_| anyhow!("unknown locale \"{}\"", settings.locale))?;
+    check_format("date_format", &settings.date_format)?;
+    check_format("time_format", &settings.time_format)?;
+    FORMAT.get_or_init(|| Format {
+        locale,
+        date_format: settings.date_format.clone(),
+        time_format: settings.time_format.clone(),
+    });
+    Ok(())
+}
+
+/// replaces the names of months, days and AM/PM with the ones of the locale
+fn localize<'a, Tz: TimeZone>(item: Item<'a>, time: &DateTime<Tz>, locale: Locale) -> Item<'a> {
+    let am_pm = || {
+        let am_pm = locale_match!(locale => LC_TIME::AM_PM);
+        let i = if time.hour() < 12 { 0 } else { 1 };
+        // many locales don't use a 12-hour clock and have no names for it
+        match am_pm.get(i) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => ["AM", "PM"][i].to_string(),
+        }
+    };
+    let month = time.month0() as usize;
+    let day = time.weekday().num_days_from_sunday() as usize;
+    let name = match item {
+        Item::Fixed(Fixed::LongMonthName) => {
+            locale_match!(locale => LC_TIME::MON)[month].to_string()
+        }
+        Item::Fixed(Fixed::ShortMonthName) => {
+            locale_match!(locale => LC_TIME::ABMON)[month].to_string()
+        }
+        Item::Fixed(Fixed::LongWeekdayName) => {
+            locale_match!(locale => LC_TIME::DAY)[day].to_string()
+        }
+        Item::Fixed(Fixed::ShortWeekdayName) => {
+            locale_match!(locale => LC_TIME::ABDAY)[day].to_string()
+        }
+        Item::Fixed(Fixed::UpperAmPm) => am_pm(),
+        Item::Fixed(Fixed::LowerAmPm) => am_pm().to_lowercase(),
+        item => return item,
+    };
+    Item::OwnedLiteral(name.into_boxed_str())
+}
+
+fn format<Tz: TimeZone>(time: &DateTime<Tz>, format: &str, locale: Locale) -> String
+where
+    Tz::Offset: Display,
+{
+    let items: Vec<_> = StrftimeItems::new(format)
+        .map(|item| localize(item, time, locale))
+        .collect();
+    time.format_with_items(items.iter()).to_string()
+}
+
+/// formats the date and the time with the formats from the config
+pub fn format_date_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    match FORMAT.get() {
+        Some(f) => format(
+            time,
+            &format!("{} {}", f.date_format, f.time_format),
+            f.locale,
+        ),
+        None => time
+            .format(&format!("{} {}", DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT))
+            .to_string(),
+    }
+}
+
+/// formats the date with the date format from the config
+pub fn format_date<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    match FORMAT.get() {
+        Some(f) => format(time, &f.date_format, f.locale),
+        None => time.format(DEFAULT_DATE_FORMAT).to_string(),
+    }
+}
+
+/// formats the time with the time format from the config
+pub fn format_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    match FORMAT.get() {
+        Some(f) => format(time, &f.time_format, f.locale),
+        None => time.format(DEFAULT_TIME_FORMAT).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = Utc.ymd(2021, 4, 5).and_hms(15, 4, 5);
+        assert_eq!(
+            "Mo, 05. April 2021 03:04 PM",
+            format(&time, "%a, %d. %B %Y %I:%M %p", Locale::de_AT)
+        );
+        assert_eq!(
+            "Monday 3:04 pm",
+            format(&time, "%A %-I:%M %P", Locale::en_US)
+        );
+        assert!(check_format("time_format", "%H:%Q").is_err());
+    }
+}
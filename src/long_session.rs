@@ -0,0 +1,111 @@
+//! Warnings about sessions which are running suspiciously long, because stopping them was
+//! probably forgotten.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+
+use std::io::{self, IsTerminal};
+
+use crate::natural_time::parse_time;
+use crate::settings::Settings;
+use crate::{parse_date_time, TrackingData, TrackingEvent};
+
+/// parses the time of the stop. a time without date is on the day the session started.
+fn parse_stop_time(input: &str, start: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let stop = match parse_time(input) {
+        Some(time) => start
+            .with_timezone(&Local)
+            .date()
+            .and_time(time)
+            .ok_or_else(|| anyhow!("invalid time"))?
+            .with_timezone(&Utc),
+        None => parse_date_time(input)?,
+    };
+    if stop <= start {
+        Err(anyhow!(
+            "The stop has to be after the start of the session!"
+        ))
+    } else if stop > Utc::now() {
+        Err(anyhow!("The stop can't be in the future!"))
+    } else {
+        Ok(stop)
+    }
+}
+
+/// asks for the time at which the session ended. returns `None` if the session should keep running
+fn ask_stop_time(start: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    loop {
+        println!(
+            "Insert a stop at (e.g. \"17:30\" or \"yesterday 18:00\", leave empty to keep it running): "
+        );
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if input.trim().is_empty() {
+            return Ok(None);
+        }
+        match parse_stop_time(input.trim(), start) {
+            Ok(stop) => return Ok(Some(stop)),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// warns if the running session is longer than `max_session_hours` and offers to stop it, if it
+/// can `ask`. returns whether a stop was inserted. with `strict`, a long session which keeps
+/// running is an error.
+pub fn check(
+    settings: &Settings,
+    data: &mut Vec<TrackingEvent>,
+    strict: bool,
+    ask: bool,
+) -> Result<bool> {
+    if settings.max_session_hours == 0 {
+        return Ok(false);
+    }
+    let start = match data.last() {
+        Some(event) if event.is_start() => event.time(true),
+        _ => return Ok(false),
+    };
+    let elapsed = Utc::now() - start;
+    if elapsed < Duration::hours(i64::from(settings.max_session_hours)) {
+        return Ok(false);
+    }
+
+    eprintln!(
+        "Warning: The current session is running since {} ({} hours). Did you forget to stop it?",
+        start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+        elapsed.num_hours()
+    );
+    if ask && io::stdin().is_terminal() {
+        if let Some(stop) = ask_stop_time(start)? {
+            data.push(TrackingEvent::Stop(TrackingData::new(None, stop)));
+            println!(
+                "Inserted a stop at {}.",
+                stop.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+            return Ok(true);
+        }
+    }
+    if strict {
+        return Err(anyhow!(
+            "The current session is running for more than {} hours!",
+            settings.max_session_hours
+        ));
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stop_time() {
+        let start = Utc::now() - Duration::hours(30);
+        let stop = parse_stop_time("2h ago", start).unwrap();
+        assert!(stop > start && stop < Utc::now());
+        assert!(parse_stop_time("3 days ago", start).is_err());
+        let tomorrow = (Local::now() + Duration::days(1)).format("%Y-%m-%d %H:%M:%S");
+        assert!(parse_stop_time(&tomorrow.to_string(), start).is_err());
+    }
+}
@@ -1,31 +1,128 @@
-use anyhow::{Context, Result};
-use chrono::{prelude::*, serde::ts_seconds, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use anyhow::{anyhow, Context, Result};
+use chrono::{prelude::*, serde::ts_seconds, Duration, NaiveDate, NaiveDateTime};
 use iif::iif;
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::{self, Write}};
+use std::io::{self, IsTerminal};
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
+use table::Table;
+use uuid::Uuid;
 
+mod absence;
+mod anonymize;
+mod archive;
+mod auto_stop;
+mod backup;
+mod break_reminder;
+mod calendar;
+#[cfg(feature = "chart")]
+mod chart;
+mod check;
+mod cleanup;
+mod clock_jump;
+mod color;
+mod completions;
+mod cron;
+mod daemon;
+mod diff;
+mod earnings;
+mod encryption;
+mod eta;
+mod event_hooks;
+mod exporter;
+mod fix;
+mod goal;
+mod hook;
+mod host;
+mod import;
+mod interactive;
+mod interval_log;
+mod journal;
+mod kiosk;
+mod live_status;
+mod locale;
+mod long_session;
+mod merge;
+mod migration;
+mod nag;
+mod natural_time;
+mod notification;
+mod overtime;
+mod pager;
+mod pomodoro;
+mod precision;
+mod profile;
+mod progress;
+#[cfg(feature = "push")]
+mod push;
+mod report;
+#[cfg(feature = "rest-sync")]
+mod rest_sync;
+mod search;
+mod serve;
 mod settings;
+mod sleep;
+mod stats;
+mod statusbar;
+mod storage;
+mod strict;
+mod sync;
+mod table;
+mod template;
+mod timeclock;
+mod timesheet;
+mod vacation;
+mod watch;
+mod window;
 
+use absence::AbsenceCommand;
+use backup::BackupCommand;
+use calendar::CalendarPeriod;
+use cleanup::{cleanup, CleanupStrategy};
+use color::{paint, Style};
+use completions::{CompletionShell, CompletionValues};
+use cron::CronCommand;
+use goal::GoalCommand;
+use hook::HookCommand;
+use import::ImportFormat;
+use pomodoro::PomodoroOptions;
+use profile::ProfileCommand;
+#[cfg(feature = "push")]
+use push::PushService;
+use report::{GroupBy, SummaryKey};
 use settings::Settings;
+use statusbar::StatusbarStyle;
+#[cfg(feature = "binary")]
+use storage::write_json_data;
+use storage::{lock, read_range, read_tail, save_data, write_output};
+use vacation::VacationCommand;
+use window::DailyWindow;
 
 #[derive(Debug, StructOpt)]
 struct Options {
     #[cfg(feature = "binary")]
-    /// which data file to use. [default: ~/timetracking.bin]
-    #[structopt(short, long)]
+    /// which data file to use. [default: timetracking.bin in the data directory of the platform]
+    #[structopt(short, long, env = "TT_DATA_FILE")]
     data_file: Option<PathBuf>,
 
     #[cfg(not(feature = "binary"))]
-    /// which data file to use. [default: ~/timetracking.json]
-    #[structopt(short, long)]
+    /// which data file to use. [default: timetracking.json in the data directory of the platform]
+    #[structopt(short, long, env = "TT_DATA_FILE")]
     data_file: Option<PathBuf>,
 
     /// which config file to use.
-    #[structopt(short, long)]
+    #[structopt(short, long, env = "TT_CONFIG_FILE")]
     config_file: Option<String>,
 
+    /// which profile to use. [default: the profile selected with `tt profile switch`]
+    #[structopt(short, long)]
+    profile: Option<String>,
+
+    /// add new entries with their times, even if they are before the last entry because the
+    /// system clock jumped back, without asking
+    #[structopt(long)]
+    allow_out_of_order: bool,
+
     #[structopt(subcommand)]
     command: Option<Command>,
 }
@@ -33,17 +130,116 @@ struct Options {
 #[derive(Default, Debug, StructOpt)]
 struct FilterData {
     /// show all entries after this point in time [defaults to current day 00:00:00]
-    /// allowed formats are: "%Y-%m-%d %H:%M:%S", "%Y-%m-%d", "%H:%M:%S"
+    /// allowed formats are: "%Y-%m-%d %H:%M:%S", "%Y-%m-%d", "%H:%M:%S" or relative like
+    /// "yesterday", "last monday 9:00" or "2h ago"
     #[structopt(short, long)]
     from: Option<String>,
 
     /// show all entries before this point in time [defaults to start day 23:59:59]
-    /// allowed formats are: "%Y-%m-%d %H:%M:%S", "%Y-%m-%d", "%H:%M:%S"
+    /// allowed formats are: "%Y-%m-%d %H:%M:%S", "%Y-%m-%d", "%H:%M:%S" or relative like
+    /// "yesterday", "last monday 9:00" or "2h ago"
     #[structopt(short, long)]
     to: Option<String>,
 
-    /// filter entries. possible filter values: "week", "all" or part of the description
+    /// filter entries. possible filter values: "today", "yesterday", "week", "last-week", "month",
+    /// "last-month", "year", "all" or part of the description
     filter: Option<String>,
+
+    /// only show entries belonging to this project
+    #[structopt(long)]
+    project: Option<String>,
+
+    /// only show entries with this tag. can be given multiple times, in which case all tags
+    /// have to match
+    #[structopt(long = "tag", number_of_values = 1)]
+    tags: Vec<String>,
+
+    /// only show entries whose description contains this text. can be given multiple times, in
+    /// which case one of them has to match, e.g. to sum up the time of related tickets
+    #[structopt(long = "filter", number_of_values = 1)]
+    descriptions: Vec<String>,
+
+    /// with multiple --filter, all of them have to match instead of any
+    #[structopt(long, requires = "descriptions")]
+    match_all: bool,
+
+    /// only show entries recorded on this computer, see `host` in the config
+    #[structopt(long)]
+    host: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Copy, StructOpt)]
+struct CalculationOptions {
+    /// include seconds in time calculation
+    #[structopt(short)]
+    include_seconds: bool,
+
+    /// show raw times, without applying the rounding rules from the config
+    #[structopt(long)]
+    no_round: bool,
+
+    /// count intervals shorter than ignore_intervals_shorter_than from the config
+    #[structopt(long)]
+    keep_short_intervals: bool,
+
+    /// count intervals longer than split_intervals_longer_than from the config completely
+    #[structopt(long)]
+    keep_long_intervals: bool,
+}
+
+impl CalculationOptions {
+    /// the part of an interval of the given length which counts. intervals shorter than
+    /// `ignore_intervals_shorter_than` don't count at all, intervals longer than
+    /// `split_intervals_longer_than` only count up to it, because their stop was probably
+    /// forgotten
+    fn limit_interval(self, settings: &Settings, duration: Duration) -> Option<Duration> {
+        match (
+            settings.ignore_intervals_shorter_than,
+            settings.split_intervals_longer_than,
+        ) {
+            (Some(min), _) if !self.keep_short_intervals && duration < min => None,
+            (_, Some(max)) if !self.keep_long_intervals => Some(duration.min(max)),
+            _ => Some(duration),
+        }
+    }
+}
+
+/// the options of the shortcuts for `tt show`, like `tt week`
+#[derive(Debug, StructOpt)]
+struct PeriodOptions {
+    /// only show entries belonging to this project
+    #[structopt(long)]
+    project: Option<String>,
+
+    /// only show entries with this tag. can be given multiple times, in which case all tags
+    /// have to match
+    #[structopt(long = "tag", number_of_values = 1)]
+    tags: Vec<String>,
+
+    #[structopt(flatten)]
+    options: CalculationOptions,
+}
+
+#[derive(Default, Debug, Clone, Copy, StructOpt)]
+struct BillableOptions {
+    /// mark the time as billable, regardless of the default of the project
+    #[structopt(long, conflicts_with = "non-billable")]
+    billable: bool,
+
+    /// mark the time as not billable, regardless of the default of the project
+    #[structopt(long)]
+    non_billable: bool,
+}
+
+impl BillableOptions {
+    /// the flag to store with the event. without one, the default of the project is used
+    fn value(self) -> Option<bool> {
+        match (self.billable, self.non_billable) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -54,26 +250,174 @@ enum Command {
     #[cfg(not(feature = "binary"))]
     /// export data to file
     Export {
-        /// where to write the output file
+        /// export a weekly timesheet or timeclock entries for hledger instead. possible values:
+        /// "md", "html", "timeclock" or the name of an exporter plugin, which is an executable
+        /// "tt-export-<name>" in $PATH
+        #[structopt(long)]
+        format: Option<String>,
+        /// a date in the week the timesheet is exported for. format: "%Y-%m-%d"
+        /// [defaults to current week, or everything for timeclock]
+        #[structopt(long, requires = "format")]
+        week: Option<String>,
+        /// replace the descriptions, projects, tags, notes, hosts and users with numbered
+        /// placeholders like "project 1" and keep the times, e.g. to share the data in a bug
+        /// report
+        #[structopt(long)]
+        anonymize: bool,
+        /// group the entries by day, with the work time of every day and every week, e.g. to
+        /// attach the export to a timesheet
+        #[structopt(long, conflicts_with = "format")]
+        summarized: bool,
+        /// where to write the output file. "-" writes to stdout
         path: PathBuf,
+        #[structopt(flatten)]
+        filter: FilterData,
     },
 
     /// show info from the latest entry. Returns the exit code 0, if the time tracking is currently
     /// active and -1 if not.
-    Status,
+    Status {
+        /// fail if the running session is longer than max_session_hours
+        #[structopt(long)]
+        strict: bool,
+
+        /// show which profiles are currently tracking time
+        #[structopt(short, long)]
+        all: bool,
+
+        /// keep running and show the elapsed time of the current session, the work time of today
+        /// and the time remaining to reach the goal, updated every second. Ctrl-C quits
+        #[structopt(long, visible_alias = "follow", conflicts_with_all = &["all", "strict"])]
+        watch: bool,
+
+        /// also show the progress of the daily and the weekly goal [default: status_goals from the
+        /// config]
+        #[structopt(long, conflicts_with_all = &["all", "watch"])]
+        goals: bool,
+    },
+
+    /// check the data for problems, like starts without a stop or very long intervals. Returns a
+    /// nonzero exit code if problems were found
+    Check {
+        /// report intervals longer than this many hours [default: max_session_hours from the config]
+        #[structopt(long)]
+        max_hours: Option<u32>,
+    },
+
+    /// resolve repeated start or stop events, by asking which one to keep or with a strategy
+    Cleanup {
+        /// resolve all repeated events without asking. possible values: "keep-first", "keep-last",
+        /// "merge" (keeps the first start or the last stop with the descriptions and tags of all)
+        /// or "drop"
+        #[structopt(long)]
+        strategy: Option<CleanupStrategy>,
+
+        /// only show how the repeated events would be resolved
+        #[structopt(long)]
+        dry_run: bool,
+    },
+
+    /// walk through the starts without a stop, the stops without a start and the stops before
+    /// their start, and fix them by adding or removing events. proposes the times at which the
+    /// days around them usually started or ended
+    Fix,
+
+    /// delete entries. shows the entries and asks for confirmation before deleting them
+    Delete {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// only delete the entries with these numbers, as shown by `tt list`. the filter is
+        /// ignored when numbers or ids are given. can be given multiple times
+        #[structopt(short, long = "index", number_of_values = 1)]
+        indices: Vec<usize>,
+
+        /// only delete the entries with these ids, as shown by `tt list --ids`. unlike the numbers,
+        /// they don't change when other entries are added or deleted. can be given multiple times
+        #[structopt(long = "id", number_of_values = 1)]
+        ids: Vec<Uuid>,
+
+        /// only delete the entries at exactly this point in time
+        #[structopt(short, long)]
+        at: Option<String>,
+
+        /// only show the entries which would be deleted
+        #[structopt(long)]
+        dry_run: bool,
 
-    /// starts an interactive cleanup session
-    Cleanup,
+        /// delete without asking for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+
+    /// add a complete interval, e.g. when you forgot to track it
+    Add {
+        /// when the interval started. format: "HH:MM:SS", "YY-mm-dd HH:MM:SS" or relative like
+        /// "yesterday 13:00"
+        start: String,
+
+        /// when the interval ended or how long it took. a time without a date is on the day
+        /// of the start. format: like start or a duration like "2h30m"
+        end: String,
+
+        /// a description for the interval
+        description: Option<String>,
+
+        /// the project the tracked time belongs to
+        #[structopt(short, long)]
+        project: Option<String>,
+
+        /// a tag for the interval. can be given multiple times
+        #[structopt(short, long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+
+        #[structopt(flatten)]
+        billable: BillableOptions,
+    },
 
     /// start time tracking
     Start {
         /// a description for the event
         description: Option<String>,
 
+        /// pick the description and project of a previous entry with a fuzzy finder
+        #[structopt(long, conflicts_with = "description")]
+        pick: bool,
+
+        /// ask for the description, the project, the tags and the start time, with the values
+        /// used before as suggestions. values given as arguments are asked with them prefilled
+        #[structopt(short, long, conflicts_with = "pick")]
+        interactive: bool,
+
+        /// the project the tracked time belongs to
+        #[structopt(short, long)]
+        project: Option<String>,
+
+        /// a tag for the event. can be given multiple times
+        #[structopt(short, long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+
         /// the time at which the event happend.
-        /// format: "HH:MM:SS" or "YY-mm-dd HH:MM:SS" [defaults to current time]
+        /// format: "HH:MM:SS", "YY-mm-dd HH:MM:SS" or relative like "5m ago" or "yesterday 14:00"
+        /// [defaults to current time]
         #[structopt(short, long)]
         at: Option<String>,
+
+        /// fail instead of starting, if the running session is longer than max_session_hours
+        #[structopt(long)]
+        strict: bool,
+
+        /// split the entry which was running at the time given with --at, instead of failing
+        #[structopt(long, requires = "at")]
+        force: bool,
+
+        /// stop after this duration, e.g. "1h" or "45m" for a meeting. the stop event is written
+        /// right away and moved to the current time, if tracking is started or stopped earlier
+        #[structopt(long = "for")]
+        duration: Option<String>,
+
+        #[structopt(flatten)]
+        billable: BillableOptions,
     },
 
     /// stop time tracking
@@ -82,273 +426,1531 @@ enum Command {
         description: Option<String>,
 
         /// the time at which the event happend.
-        /// format: "HH:MM:SS" or "YY-mm-dd HH:MM:SS" [defaults to current time]
+        /// format: "HH:MM:SS", "YY-mm-dd HH:MM:SS" or relative like "5m ago" or "yesterday 14:00"
+        /// [defaults to current time]
         #[structopt(short, long)]
         at: Option<String>,
-    },
 
-    /// continue time tracking with last description
-    Continue,
+        /// stop time tracking in the data files of all profiles
+        #[structopt(long)]
+        all_profiles: bool,
 
-    /// list all entries
-    List {
-        #[structopt(flatten)]
-        filter: FilterData,
+        /// move the end of the entry which was running or ended last at the time given with --at,
+        /// instead of failing
+        #[structopt(long, requires = "at", conflicts_with = "all-profiles")]
+        force: bool,
     },
 
-    /// show path to data file
-    Path,
-
-    /// show work time for given timespan
-    Show {
-        #[structopt(flatten)]
-        filter: FilterData,
+    /// pause time tracking, e.g. for a lunch break
+    Pause {
+        /// a description for the event
+        description: Option<String>,
 
-        /// show only the time with no additional text
+        /// the time at which the event happend.
+        /// format: "HH:MM:SS", "YY-mm-dd HH:MM:SS" or relative like "5m ago" or "yesterday 14:00"
+        /// [defaults to current time]
         #[structopt(short, long)]
-        plain: bool,
+        at: Option<String>,
+    },
 
-        /// show time until the defined time goals are met.
+    /// resume paused time tracking with the description, project and tags it had before
+    Resume {
+        /// the time at which the event happend.
+        /// format: "HH:MM:SS", "YY-mm-dd HH:MM:SS" or relative like "5m ago" or "yesterday 14:00"
+        /// [defaults to current time]
         #[structopt(short, long)]
-        remaining: bool,
+        at: Option<String>,
+    },
 
-        /// include seconds in time calculation
-        #[structopt(short)]
-        include_seconds: bool,
+    /// continue time tracking with last description, or with an older task
+    Continue {
+        /// which of the recent tasks to continue, as listed by `tt recent`. 1 is the most recent
+        /// one, 2 the one before that, etc.
+        number: Option<usize>,
 
-        /// show only the time with no additional text. [default: "{hh}:{mm}:{ss}"]
-        #[structopt(long)]
-        format: Option<String>,
+        /// continue the most recent task whose description contains this text. asks which one to
+        /// continue, if several tasks match
+        #[structopt(long, conflicts_with = "number")]
+        description_contains: Option<String>,
     },
-    #[cfg(feature = "binary")]
-    /// export data to file
-    Export {
-        /// export in a human readable format. This format is for human reading only and cannot be
-        /// imported
+
+    /// change the description of the running entry
+    Annotate {
+        /// the new description
+        description: String,
+
+        /// change a past entry instead. 1 is the entry before the latest one, 2 the one before that, etc.
         #[structopt(short, long)]
-        readable: bool,
-        /// pretty print json
+        index: Option<usize>,
+    },
+
+    /// add a note about what you did to the running entry, e.g. to remember it for the
+    /// timesheet
+    Note {
+        /// the text of the note. without it, the note is read from stdin, so it can span
+        /// multiple lines
+        text: Option<String>,
+
+        /// add the note to a past entry instead. 1 is the entry before the latest one, 2 the one
+        /// before that, etc.
         #[structopt(short, long)]
-        pretty: bool,
-        /// where to write the output file
-        path: PathBuf,
+        index: Option<usize>,
     },
-    #[cfg(feature = "binary")]
-    /// import data from json file
-    Import {
-        /// which file to import
-        path: PathBuf,
+
+    /// keep running and stop time tracking when the computer is idle
+    Watch {
+        /// minutes of inactivity after which time tracking gets stopped
+        /// [default: watch.idle_minutes from the config]
+        #[structopt(long)]
+        idle_minutes: Option<u32>,
     },
-}
 
-impl Default for Command {
-    fn default() -> Self {
-        Self::Show {
-            filter: FilterData::default(),
-            format: None,
-            include_seconds: false,
-            plain: false,
-            remaining: false,
-        }
-    }
-}
+    /// remind with a desktop notification to start tracking, if nothing was tracked for longer
+    /// than watch.remind_after during the work hours. checks once, so it can be run from cron
+    Nag,
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-struct TrackingData {
-    description: Option<String>,
+    /// postpone the reminder to take a break (see watch.break_reminder_after)
+    Snooze {
+        /// for how long, e.g. "30m" [default: watch.break_reminder_snooze from the config]
+        duration: Option<String>,
+    },
 
-    #[serde(with = "ts_seconds")]
-    time: DateTime<Utc>,
-}
+    /// show a fullscreen clock which starts and stops time tracking with a single key, e.g. on a
+    /// dedicated screen at a workbench. every start and stop is saved right away
+    Kiosk {
+        /// the description of the started entries
+        description: Option<String>,
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-enum TrackingEvent {
-    Start(TrackingData),
-    Stop(TrackingData),
-}
+        /// the project of the started entries
+        #[structopt(short, long)]
+        project: Option<String>,
 
-impl TrackingEvent {
-    fn time(&self, include_seconds: bool) -> DateTime<Utc> {
-        match self {
-            Self::Start(TrackingData { time, .. }) | Self::Stop(TrackingData { time, .. }) => {
-                let time = *time;
-                if include_seconds {
-                    time
-                } else {
-                    time.with_second(0).expect("could not set seconds to zero")
-                }
-            }
-        }
-    }
+        /// a tag for the started entries. can be given multiple times
+        #[structopt(short, long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+    },
 
-    fn description(&self) -> Option<String> {
-        match self {
-            Self::Start(TrackingData { description, .. })
-            | Self::Stop(TrackingData { description, .. }) => description.clone(),
-        }
-    }
+    /// alternate between tracked work cycles and breaks, 25 and 5 minutes by default
+    Pomodoro {
+        #[structopt(flatten)]
+        options: PomodoroOptions,
+    },
 
-    fn is_start(&self) -> bool {
-        match self {
-            Self::Start(_) => true,
-            Self::Stop(_) => false,
-        }
-    }
+    /// keep the data in memory and handle `tt start`, `tt stop` and `tt prompt` over a unix socket
+    /// next to the data file, so they don't read and write the file themselves while it runs
+    Daemon,
 
-    fn is_stop(&self) -> bool {
-        match self {
-            Self::Start(_) => false,
-            Self::Stop(_) => true,
-        }
-    }
-}
+    /// serve a small http api on localhost to start and stop time tracking and to query the data
+    Serve {
+        /// the port to listen on [default: the port from the config]
+        #[structopt(long)]
+        port: Option<u16>,
+    },
 
-#[cfg_attr(test, derive(PartialEq, Eq))]
-#[derive(Debug, Clone, Copy)]
-enum DateOrDateTime {
-    Date(NaiveDate),
-    DateTime(NaiveDateTime),
-}
+    /// merge the changes from the git remote in the config and push the data file to it, or
+    /// exchange the changes with a sync server
+    Sync {
+        /// the url of a sync server to sync with [default: sync.url from the config]
+        #[structopt(long)]
+        remote: Option<String>,
+    },
 
-impl From<NaiveDate> for DateOrDateTime {
-    fn from(date: NaiveDate) -> Self {
-        Self::Date(date)
-    }
-}
+    /// add, remove and list vacation days, which have no goal
+    Vacation {
+        #[structopt(subcommand)]
+        command: VacationCommand,
+    },
 
-impl From<NaiveDateTime> for DateOrDateTime {
-    fn from(date_time: NaiveDateTime) -> Self {
-        Self::DateTime(date_time)
-    }
-}
+    /// add, remove and list sick days, vacation and public holidays, which have no goal and are
+    /// shown in report and overtime
+    Absence {
+        #[structopt(subcommand)]
+        command: AbsenceCommand,
+    },
 
-#[cfg(feature = "binary")]
-fn read_data<P: AsRef<Path>>(path: P) -> Result<Vec<TrackingEvent>> {
-    let data = std::fs::read(&path)?;
-    Ok(bincode::deserialize(&data)?)
-}
+    /// list and create backups of the data file
+    Backup {
+        #[structopt(subcommand)]
+        command: BackupCommand,
+    },
 
-#[cfg(not(feature = "binary"))]
-fn read_data<P: AsRef<Path>>(path: P) -> Result<Vec<TrackingEvent>> {
-    read_json_data(path)
-}
+    /// show the events which were added, removed or changed compared to a backup or another data
+    /// file, e.g. before restoring a backup
+    Diff {
+        /// the other data file, in any format tt uses or exported with `tt export`
+        /// [default: the newest backup]
+        path: Option<PathBuf>,
 
-fn read_json_data<P: AsRef<Path>>(path: P) -> Result<Vec<TrackingEvent>> {
-    let data = std::fs::read_to_string(&path)?;
-    Ok(serde_json::from_str(&data)?)
-}
+        /// compare with the backup with this number, as shown by `tt backup list`
+        #[structopt(short, long, conflicts_with = "path")]
+        backup: Option<usize>,
+    },
 
-fn write_with_flush<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
-    let mut f = File::create(path)?;
-    f.write_all(contents.as_ref())?;
-    f.flush()?;
-    Ok(())
-}
+    /// replace the data with a backup
+    Restore {
+        /// the number of the backup, as shown by `tt backup list`
+        index: usize,
+    },
 
-#[cfg(feature = "binary")]
-fn write_data<P: AsRef<Path>>(path: P, data: &[TrackingEvent]) -> Result<()> {
+    /// install git hooks which start time tracking with the current branch name or a hook which
+    /// stops it when the computer goes to sleep
+    #[structopt(alias = "hooks")]
+    Hook {
+        #[structopt(subcommand)]
+        command: HookCommand,
+    },
 
-    let data = bincode::serialize(data).expect("could not serialize data");
+    /// show the daily and weekly goal or set them in the config file
+    Goal {
+        #[structopt(subcommand)]
+        command: GoalCommand,
+    },
 
-    let temp_path = path.as_ref().with_extension("bin.bak");
+    /// install a weekly job which writes or mails a report
+    Cron {
+        #[structopt(subcommand)]
+        command: CronCommand,
+    },
 
-    match write_with_flush(&temp_path, &data) {
-        Ok(_) => {
-            Ok(std::fs::rename(temp_path, path.as_ref())?)
-        }
-        Err(e) => Err(e.into()),
-    }
-}
+    /// list and switch profiles
+    Profile {
+        #[structopt(subcommand)]
+        command: ProfileCommand,
+    },
 
-fn write_json_data<P: AsRef<Path>>(path: P, data: &[TrackingEvent], pretty: bool) -> Result<()> {
-    let data = iif!(
-        pretty,
-        serde_json::to_string_pretty(data),
-        serde_json::to_string(data)
-    )
-    .expect("could not serialize data");
-    Ok(write_with_flush(&path, &data)?)
-}
+    /// list the most recently used descriptions or projects, one per line
+    Recent {
+        /// how many to list
+        #[structopt(short, long, default_value = "10")]
+        number: usize,
 
-#[cfg(not(feature = "binary"))]
-fn write_data<P: AsRef<Path>>(path: P, data: &[TrackingEvent]) -> Result<()> {
-    write_json_data(path, data, false)
-}
+        /// list the projects instead of the descriptions
+        #[structopt(long)]
+        projects: bool,
+    },
 
-fn start_tracking(
-    settings: &Settings,
-    data: &mut Vec<TrackingEvent>,
-    description: Option<String>,
-    at: Option<String>,
-) -> Result<()> {
-    let (should_add, last_description) = match data.last() {
-        None => (true, None),
-        Some(event) => (event.is_stop(), event.description()),
-    };
-    if should_add || at.is_some() {
-        data.push(TrackingEvent::Start(TrackingData {
-            description,
-            time: at.map_or_else(|| Ok(Local::now().into()), |at| parse_date_time(&at))?,
-        }));
-    } else if settings.auto_insert_stop && at.is_none() {
-        match (description, last_description) {
-            (Some(description), Some(last_description)) if description == last_description => {
-                eprintln!(
-                    "Timetracking with the description \"{}\" is already running!",
-                    description
-                )
-            }
-            (description, _) => {
-                data.push(TrackingEvent::Stop(TrackingData {
-                    description: None,
-                    time: Local::now().into(),
-                }));
-                data.push(TrackingEvent::Start(TrackingData {
-                    description,
-                    time: Local::now().into(),
-                }));
-            }
-        }
-    } else {
-        eprintln!("Time tracking is already running!");
-    }
+    /// print the completion script for a shell, which also suggests the recent descriptions for
+    /// `tt start`, the projects, profiles and filters. possible values: "bash", "zsh", "fish"
+    Completions { shell: CompletionShell },
+
+    /// list the values the completion suggests. possible values: "projects", "profiles",
+    /// "filters"
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    Complete { values: CompletionValues },
 
+    /// print the time at which the goal of today is reached, including the rest of
+    /// min_daily_break
+    Eta,
+
+    /// print a single compact line for use in a shell prompt
+    Prompt {
+        /// the format of the line. possible placeholders: {state}, {elapsed}, {today}, {eta},
+        /// {description}, {project}, {tags} [default: prompt_format from the config]
+        #[structopt(long)]
+        format: Option<String>,
+    },
+
+    /// print the current session for a custom module of waybar, i3blocks or polybar, which
+    /// polls it
+    Statusbar {
+        /// the output format of the bar. possible values: "waybar", "i3blocks", "polybar"
+        #[structopt(long, default_value = "waybar")]
+        style: StatusbarStyle,
+
+        /// the text in the bar, with the placeholders of `tt prompt`
+        /// [default: statusbar.format from the config]
+        #[structopt(long)]
+        format: Option<String>,
+    },
+
+    /// undo the last change to the data
+    Undo,
+
+    /// redo the last undone change
+    Redo,
+
+    /// list all entries with their numbers, which can be used to delete them. uses $PAGER if
+    /// the list doesn't fit on the screen
+    List {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// only show the last n entries
+        #[structopt(short, long)]
+        last: Option<usize>,
+
+        /// show the newest entries first
+        #[structopt(short, long)]
+        reverse: bool,
+
+        /// also show the notes of the entries and the computer they were recorded on
+        #[structopt(short, long)]
+        verbose: bool,
+
+        /// show the id of every entry, which stays the same when other entries are changed
+        #[structopt(long)]
+        ids: bool,
+
+        /// show the full descriptions, even if the table is wider than the terminal
+        #[structopt(long)]
+        wide: bool,
+
+        /// print a line of json for every entry, with its number, id, type, time in UTC,
+        /// description, project and tags, for scripts
+        #[structopt(long, conflicts_with_all = &["verbose", "wide"])]
+        json: bool,
+    },
+
+    /// show one line per interval with its start, stop and duration, and the total of every day
+    Log {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// show the full descriptions, even if the table is wider than the terminal
+        #[structopt(long)]
+        wide: bool,
+    },
+
+    /// find the intervals whose description or notes contain a text, in the whole history
+    Search {
+        /// the text to search for
+        pattern: String,
+
+        /// ignore the case of the text
+        #[structopt(short, long)]
+        ignore_case: bool,
+
+        /// search for a regular expression instead of a text
+        #[structopt(short = "e", long)]
+        regex: bool,
+    },
+
+    /// show path to data file
+    Path,
+
+    /// move old entries into a file per year next to the data file (e.g. timetracking-2022.bin),
+    /// which show, report, overtime, summary, earnings, stats and calendar read again when they need them
+    Archive {
+        /// archive the entries before this date
+        #[structopt(long)]
+        before: String,
+    },
+
+    /// show work time for given timespan
+    Show {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// show only the time with no additional text
+        #[structopt(short, long)]
+        plain: bool,
+
+        /// show time until the defined time goals are met.
+        #[structopt(short, long)]
+        remaining: bool,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+
+        /// show only the time with no additional text. [default: "{hh}:{mm}:{ss}"]
+        #[structopt(long)]
+        format: Option<String>,
+
+        /// fail if the running session is longer than max_session_hours
+        #[structopt(long)]
+        strict: bool,
+
+        /// show the start, stop, work time and the difference to the goal of every day above
+        /// the total
+        #[structopt(long, conflicts_with = "plain")]
+        breakdown: bool,
+
+        /// only count the time within this time of day, e.g. "09:00-17:00". a window which ends
+        /// before it starts wraps around midnight, so "17:00-09:00" is the time outside of it
+        #[structopt(long)]
+        daily_window: Option<DailyWindow>,
+
+        /// show the work time of every group above the total. possible values: "description",
+        /// "project", "tag". an entry with several tags counts for each of them
+        #[structopt(long, conflicts_with = "remaining")]
+        group_by: Option<SummaryKey>,
+
+        /// show the work time of every project above the total, like `--group-by project`
+        #[structopt(long, conflicts_with_all = &["group-by", "remaining"])]
+        per_project: bool,
+    },
+    /// show the work time of today with the start, stop and goal, like `tt show today --breakdown`
+    Today {
+        #[structopt(flatten)]
+        period: PeriodOptions,
+    },
+
+    /// show the work time of yesterday with the start, stop and goal, like `tt show yesterday
+    /// --breakdown`
+    Yesterday {
+        #[structopt(flatten)]
+        period: PeriodOptions,
+    },
+
+    /// show the work time of this week with the start, stop and goal of every day, like `tt show
+    /// week --breakdown`
+    Week {
+        #[structopt(flatten)]
+        period: PeriodOptions,
+    },
+
+    /// show the work time of this month with the start, stop and goal of every day, like `tt show
+    /// month --breakdown`
+    Month {
+        #[structopt(flatten)]
+        period: PeriodOptions,
+    },
+
+    /// show a table of work time, breaks and goals per day, week or month
+    Report {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// how to group the entries. possible values: "day", "week", "month"
+        #[structopt(short, long, default_value = "day")]
+        group_by: GroupBy,
+
+        /// render the entries with this handlebars template instead of showing a table
+        #[structopt(long)]
+        template: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+
+        /// only count the time within this time of day, e.g. "09:00-17:00". a window which ends
+        /// before it starts wraps around midnight, so "17:00-09:00" is the time outside of it
+        #[structopt(long)]
+        daily_window: Option<DailyWindow>,
+
+        /// show the full descriptions, even if the table is wider than the terminal
+        #[structopt(long)]
+        wide: bool,
+    },
+
+    /// show the difference between work time and daily goals per day and the running balance
+    Overtime {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+    },
+
+    /// show the total time per task, sorted by duration
+    Summary {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// what to sum up the time by. possible values: "description", "project", "tag". an
+        /// entry with several tags counts for each of them
+        #[structopt(short, long, default_value = "description")]
+        by: SummaryKey,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+
+        /// show the full descriptions, even if the table is wider than the terminal
+        #[structopt(long)]
+        wide: bool,
+    },
+
+    #[cfg(feature = "chart")]
+    /// draw a bar chart of the hours per day and a pie chart of the time per project into an svg
+    /// or png file, e.g. for reports and retrospectives
+    Chart {
+        /// the file to write, ending in ".svg" or ".png"
+        #[structopt(short, long)]
+        output: PathBuf,
+
+        /// which entries to draw. possible values: "today", "yesterday", "week", "last-week",
+        /// "month", "last-month", "year", "all"
+        #[structopt(long, default_value = "month")]
+        range: String,
+
+        /// only draw entries belonging to this project
+        #[structopt(long)]
+        project: Option<String>,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+    },
+
+    #[cfg(feature = "push")]
+    /// upload the intervals which were finished since the last push to clockify or harvest, using
+    /// the push settings from the config
+    Push {
+        /// possible values: "clockify", "harvest"
+        service: PushService,
+
+        /// only show which entries would be uploaded
+        #[structopt(long)]
+        dry_run: bool,
+    },
+
+    /// show the earnings of the billable time by project: the rounded billable time multiplied by
+    /// the hourly rate of the project or billing.rate from the config
+    Earnings {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+    },
+
+    /// show statistics like the average work day, the longest session and the trend of the last
+    /// weeks. uses all entries, unless a filter is given
+    Stats {
+        #[structopt(flatten)]
+        filter: FilterData,
+
+        /// how many weeks the trend shows
+        #[structopt(short, long, default_value = "12")]
+        weeks: usize,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+    },
+
+    /// show a month or week grid with the tracked time of every day. days on which the goal was met
+    /// are green, partially met ones yellow and missed ones red
+    Calendar {
+        /// possible values: "month", "week"
+        #[structopt(default_value = "month")]
+        period: CalendarPeriod,
+
+        /// a date in the month or week to show [default: today]
+        #[structopt(long)]
+        date: Option<String>,
+
+        #[structopt(flatten)]
+        options: CalculationOptions,
+    },
+
+    #[cfg(feature = "binary")]
+    /// export data to file
+    Export {
+        /// export in a human readable format. This format is for human reading only and cannot be
+        /// imported
+        #[structopt(short, long)]
+        readable: bool,
+        /// pretty print json
+        #[structopt(short, long)]
+        pretty: bool,
+        /// export a weekly timesheet or timeclock entries for hledger instead. possible values:
+        /// "md", "html", "timeclock" or the name of an exporter plugin, which is an executable
+        /// "tt-export-<name>" in $PATH
+        #[structopt(long, conflicts_with = "readable")]
+        format: Option<String>,
+        /// a date in the week the timesheet is exported for. format: "%Y-%m-%d"
+        /// [defaults to current week, or everything for timeclock]
+        #[structopt(long, requires = "format")]
+        week: Option<String>,
+        /// replace the descriptions, projects, tags, notes, hosts and users with numbered
+        /// placeholders like "project 1" and keep the times, e.g. to share the data in a bug
+        /// report
+        #[structopt(long)]
+        anonymize: bool,
+        /// group the entries by day, with the work time of every day and every week, e.g. to
+        /// attach the export to a timesheet
+        #[structopt(long, requires = "readable")]
+        summarized: bool,
+        /// where to write the output file. "-" writes to stdout
+        path: PathBuf,
+        #[structopt(flatten)]
+        filter: FilterData,
+    },
+    /// import data from a json file, which replaces all data, or add the data exported from
+    /// another time tracker
+    Import {
+        /// the format of the file. possible values: "json", "toggl" (the detailed report csv),
+        /// "timewarrior" (a data file), "watson" (the frames file)
+        /// [default: detected from the content]
+        #[structopt(short, long)]
+        format: Option<ImportFormat>,
+
+        /// which file to import. "-" reads from stdin
+        path: PathBuf,
+    },
+
+    /// add the entries of another data file, e.g. from another machine, and resolve the
+    /// conflicts between them
+    Merge {
+        /// the other data file, in any format tt uses or exported with `tt export`
+        path: PathBuf,
+
+        /// resolve the conflicts without asking. possible values: "keep-first", "keep-last",
+        /// "merge" or "drop"
+        #[structopt(long)]
+        strategy: Option<CleanupStrategy>,
+
+        /// only show what would be merged
+        #[structopt(long)]
+        dry_run: bool,
+    },
+}
+
+impl Command {
+    /// turns the shortcuts like `tt week` into the `show` command they stand for
+    fn expand_shortcut(self) -> Self {
+        let (filter, period) = match self {
+            Command::Today { period } => ("today", period),
+            Command::Yesterday { period } => ("yesterday", period),
+            Command::Week { period } => ("week", period),
+            Command::Month { period } => ("month", period),
+            command => return command,
+        };
+        Command::Show {
+            filter: FilterData {
+                filter: Some(filter.to_string()),
+                project: period.project,
+                tags: period.tags,
+                ..FilterData::default()
+            },
+            format: None,
+            options: period.options,
+            plain: false,
+            remaining: false,
+            strict: false,
+            breakdown: true,
+            daily_window: None,
+            group_by: None,
+            per_project: false,
+        }
+    }
+
+    /// whether the command changes the data, so it needs the latest data from the sync remote
+    fn changes_data(&self) -> bool {
+        matches!(
+            self,
+            Command::Start { .. }
+                | Command::Stop { .. }
+                | Command::Pause { .. }
+                | Command::Resume { .. }
+                | Command::Continue { .. }
+                | Command::Annotate { .. }
+                | Command::Note { .. }
+                | Command::Add { .. }
+                | Command::Delete { .. }
+                | Command::Cleanup { .. }
+                | Command::Fix
+                | Command::Restore { .. }
+                | Command::Undo
+                | Command::Redo
+                | Command::Hook { .. }
+                | Command::Import { .. }
+                | Command::Merge { .. }
+                | Command::Archive { .. }
+        )
+    }
+
+    /// whether the command starts or stops time tracking on its own, so it doesn't ask whether to
+    /// continue after the computer slept
+    fn starts_or_stops(&self) -> bool {
+        matches!(
+            self,
+            Command::Start { .. }
+                | Command::Stop { .. }
+                | Command::Pause { .. }
+                | Command::Resume { .. }
+                | Command::Continue { .. }
+                | Command::Hook { .. }
+        )
+    }
+
+    /// the strict flag of the commands which warn about long running sessions
+    fn session_check(&self) -> Option<bool> {
+        match self {
+            Command::Start { strict, .. }
+            | Command::Show { strict, .. }
+            | Command::Status { strict, .. } => Some(*strict),
+            _ => None,
+        }
+    }
+
+    /// the times between which the command needs the archived data, if it reads the archives.
+    /// only commands which don't change the data read them.
+    fn archive_bounds(&self, settings: &Settings) -> Result<Option<Bounds>> {
+        match self {
+            Command::List { .. } | Command::Show { .. } | Command::Calendar { .. } => {
+                self.read_bounds(settings).map(Some)
+            }
+            Command::Report { filter, .. }
+            | Command::Summary { filter, .. }
+            | Command::Earnings { filter, .. }
+            | Command::Log { filter, .. } => {
+                filter_bounds(filter, settings.week_starts_on).map(Some)
+            }
+            Command::Stats { filter, .. } | Command::Export { filter, .. }
+                if filter.filter.is_some() || filter.from.is_some() || filter.to.is_some() =>
+            {
+                filter_bounds(filter, settings.week_starts_on).map(Some)
+            }
+            #[cfg(feature = "chart")]
+            Command::Chart { range, project, .. } => {
+                filter_bounds(&chart::filter(range, project), settings.week_starts_on).map(Some)
+            }
+            Command::Stats { .. } | Command::Overtime { .. } | Command::Search { .. } => {
+                Ok(Some((None, None)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// the times between which the command needs the data, so large data files don't have to be
+    /// read completely. `None` is unbounded.
+    fn read_bounds(&self, settings: &Settings) -> Result<Bounds> {
+        match self {
+            Command::List { filter, .. } => filter_bounds(filter, settings.week_starts_on),
+            Command::Show {
+                filter, remaining, ..
+            } => {
+                let week_starts_on = settings.for_project(&filter.project).week_starts_on;
+                let (since, until) = filter_bounds(filter, week_starts_on)?;
+                if !remaining {
+                    return Ok((since, until));
+                }
+                // the remaining time also depends on the current week
+                let week = FilterData {
+                    filter: Some("week".to_string()),
+                    ..FilterData::default()
+                };
+                let (week_since, week_until) = filter_bounds(&week, week_starts_on)?;
+                Ok((
+                    since.zip(week_since).map(|(a, b)| a.min(b)),
+                    until.zip(week_until).map(|(a, b)| a.max(b)),
+                ))
+            }
+            Command::Calendar { period, date, .. } => {
+                let (first_day, last_day) = calendar::range(settings, *period, date)?;
+                Ok((
+                    Some(Utc.from_utc_datetime(&first_day.pred().and_hms(0, 0, 0))),
+                    Some(Utc.from_utc_datetime(&(last_day + Duration::days(2)).and_hms(0, 0, 0))),
+                ))
+            }
+            // the overtime balance needs everything since the start of the overtime, the progress
+            // of the goals everything since the start of the week, otherwise only the last event
+            // is shown
+            Command::Status { goals, .. } => {
+                let week = Some(settings::start_of_week(
+                    Local::today().naive_local(),
+                    settings.week_starts_on,
+                ))
+                .filter(|_| *goals || settings.status_goals);
+                let start = match (settings.overtime.start, week) {
+                    (Some(start), Some(week)) => Some(start.min(week)),
+                    (start, week) => start.or(week),
+                };
+                Ok((
+                    Some(start.map_or_else(Utc::now, |start| {
+                        Utc.from_utc_datetime(&(start - Duration::days(1)).and_hms(0, 0, 0))
+                    })),
+                    None,
+                ))
+            }
+            _ => Ok((None, None)),
+        }
+    }
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self::Show {
+            filter: FilterData::default(),
+            format: None,
+            options: CalculationOptions::default(),
+            plain: false,
+            remaining: false,
+            strict: false,
+            breakdown: false,
+            daily_window: None,
+            group_by: None,
+            per_project: false,
+        }
+    }
+}
+
+/// a note about what was done during an interval, with the time it was written
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Note {
+    #[serde(with = "ts_seconds")]
+    time: DateTime<Utc>,
+
+    text: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TrackingData {
+    /// identifies the event, even after it was edited or other events were added before it.
+    /// events written before ids were added have none, storage gives them their legacy id
+    #[serde(default)]
+    id: Uuid,
+
+    description: Option<String>,
+
+    #[serde(default)]
+    project: Option<String>,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    /// whole seconds, unless millisecond timestamps are enabled (see the precision module)
+    #[serde(with = "precision")]
+    time: DateTime<Utc>,
+
+    /// the offset from UTC in seconds of the timezone the event was recorded in.
+    /// events recorded before the offset was stored use the current local timezone
+    #[serde(default)]
+    offset: Option<i32>,
+
+    /// whether the tracked time can be billed. events without it use the default of the project
+    #[serde(default)]
+    billable: Option<bool>,
+
+    /// notes added while the interval was running. only start and resume events have them
+    #[serde(default)]
+    notes: Vec<Note>,
+
+    /// the computer the event was recorded on
+    #[serde(default)]
+    host: Option<String>,
+
+    /// the user who recorded the event, if recording it is enabled
+    #[serde(default)]
+    user: Option<String>,
+}
+
+/// the id is left out, so the same event compares equal no matter whether it was read from a file
+/// with ids or without them
+impl PartialEq for TrackingData {
+    fn eq(&self, other: &Self) -> bool {
+        self.description == other.description
+            && self.project == other.project
+            && self.tags == other.tags
+            && self.time == other.time
+            && self.offset == other.offset
+            && self.billable == other.billable
+            && self.notes == other.notes
+            && self.host == other.host
+            && self.user == other.user
+    }
+}
+
+impl Eq for TrackingData {}
+
+impl std::hash::Hash for TrackingData {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.description.hash(state);
+        self.project.hash(state);
+        self.tags.hash(state);
+        self.time.hash(state);
+        self.offset.hash(state);
+        self.billable.hash(state);
+        self.notes.hash(state);
+        self.host.hash(state);
+        self.user.hash(state);
+    }
+}
+
+/// the offset from UTC in seconds of the local timezone at `time`
+fn local_offset(time: DateTime<Utc>) -> i32 {
+    Local
+        .offset_from_utc_datetime(&time.naive_utc())
+        .local_minus_utc()
+}
+
+impl TrackingData {
+    fn new(description: Option<String>, time: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            description,
+            project: None,
+            tags: Vec::new(),
+            time,
+            offset: Some(local_offset(time)),
+            billable: None,
+            notes: Vec::new(),
+            host: host::host(),
+            user: host::user(),
+        }
+    }
+
+    /// the time in the timezone the data was recorded in
+    fn local_time(&self) -> DateTime<FixedOffset> {
+        let offset = self.offset.unwrap_or_else(|| local_offset(self.time));
+        let offset = FixedOffset::east_opt(offset).unwrap_or_else(|| FixedOffset::east(0));
+        self.time.with_timezone(&offset)
+    }
+
+    /// whether the time tracked with this data can be billed. without a flag on the event, the
+    /// default of its project or the global default from the config is used
+    fn is_billable(&self, settings: &Settings) -> bool {
+        self.billable.unwrap_or_else(|| {
+            self.project
+                .as_ref()
+                .and_then(|project| settings.projects.get(project))
+                .and_then(|project| project.billable)
+                .unwrap_or(settings.billable)
+        })
+    }
+
+    /// the id of data which was written without one. it only depends on the content and the
+    /// position of the event in the file, so it is the same every time the data is read. it must
+    /// never change, as files keep the events without ids until they are written again
+    fn legacy_id(&self, position: usize) -> Uuid {
+        let notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|note| format!("{}:{}", note.time.timestamp(), note.text))
+            .collect();
+        let content = format!(
+            "{}|{:?}|{:?}|{:?}|{}|{:?}|{:?}|{:?}",
+            position,
+            self.description,
+            self.project,
+            self.tags,
+            self.time.timestamp_millis(),
+            self.offset,
+            self.billable,
+            notes
+        );
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, content.as_bytes())
+    }
+
+    /// the same data at another time, recorded in the current timezone, as a new event. the notes
+    /// belong to the old interval, so they are not taken over
+    fn with_time(self, time: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            time,
+            offset: Some(local_offset(time)),
+            notes: Vec::new(),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum TrackingEvent {
+    Start(TrackingData),
+    Stop(TrackingData),
+    // new variants have to be added at the end, so existing binary data stays readable
+    Pause(TrackingData),
+    Resume(TrackingData),
+}
+
+impl TrackingEvent {
+    fn time(&self, include_seconds: bool) -> DateTime<Utc> {
+        let time = self.data().time;
+        if include_seconds || precision::include_seconds() {
+            time
+        } else {
+            time.with_second(0)
+                .and_then(|time| time.with_nanosecond(0))
+                .expect("could not set seconds to zero")
+        }
+    }
+
+    /// the time in the timezone the event was recorded in
+    fn local_time(&self) -> DateTime<FixedOffset> {
+        self.data().local_time()
+    }
+
+    /// the day on which the event happened, in the timezone it was recorded in
+    fn local_date(&self) -> NaiveDate {
+        self.local_time().date().naive_local()
+    }
+
+    fn data(&self) -> &TrackingData {
+        match self {
+            Self::Start(data) | Self::Stop(data) | Self::Pause(data) | Self::Resume(data) => data,
+        }
+    }
+
+    fn data_mut(&mut self) -> &mut TrackingData {
+        match self {
+            Self::Start(data) | Self::Stop(data) | Self::Pause(data) | Self::Resume(data) => data,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Start(_) => "Start",
+            Self::Stop(_) => "Stop",
+            Self::Pause(_) => "Pause",
+            Self::Resume(_) => "Resume",
+        }
+    }
+
+    fn description(&self) -> Option<String> {
+        self.data().description.clone()
+    }
+
+    /// whether the description contains any of `texts`, or all of them with `match_all`
+    fn matches_descriptions(&self, texts: &[String], match_all: bool) -> bool {
+        let description = self.data().description.clone().unwrap_or_default();
+        let contains = |text: &String| description.contains(text.as_str());
+        iif!(
+            match_all,
+            texts.iter().all(contains),
+            texts.iter().any(contains)
+        )
+    }
+
+    fn matches_project_and_tags(&self, project: &Option<String>, tags: &[String]) -> bool {
+        let data = self.data();
+        let project_matches = match project {
+            Some(project) => data.project.as_ref() == Some(project),
+            None => true,
+        };
+        project_matches && tags.iter().all(|tag| data.tags.contains(tag))
+    }
+
+    /// returns true for events that begin a work interval, which includes resuming after a pause
+    fn is_start(&self) -> bool {
+        match self {
+            Self::Start(_) | Self::Resume(_) => true,
+            Self::Stop(_) | Self::Pause(_) => false,
+        }
+    }
+
+    /// returns true for events that end a work interval, which includes pausing
+    fn is_stop(&self) -> bool {
+        !self.is_start()
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[derive(Debug, Clone, Copy)]
+enum DateOrDateTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl From<NaiveDate> for DateOrDateTime {
+    fn from(date: NaiveDate) -> Self {
+        Self::Date(date)
+    }
+}
+
+impl From<NaiveDateTime> for DateOrDateTime {
+    fn from(date_time: NaiveDateTime) -> Self {
+        Self::DateTime(date_time)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_tracking(
+    settings: &Settings,
+    data: &mut Vec<TrackingEvent>,
+    description: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    at: Option<String>,
+    billable: Option<bool>,
+    force: bool,
+) -> Result<()> {
+    let (should_add, last_description) = match data.last() {
+        None => (true, None),
+        Some(event) => (event.is_stop(), event.description()),
+    };
+    if let Some(at) = at {
+        let start = TrackingEvent::Start(TrackingData {
+            description,
+            project,
+            tags,
+            billable,
+            ..TrackingData::new(None, parse_date_time(&at)?)
+        });
+        insert_at(data, start, force)?;
+    } else if should_add {
+        data.push(TrackingEvent::Start(TrackingData {
+            description,
+            project,
+            tags,
+            billable,
+            ..TrackingData::new(None, Local::now().into())
+        }));
+    } else if settings.auto_insert_stop && at.is_none() {
+        match (description, last_description) {
+            (Some(description), Some(last_description)) if description == last_description => {
+                eprintln!(
+                    "Timetracking with the description \"{}\" is already running!",
+                    description
+                )
+            }
+            (description, _) => {
+                data.push(TrackingEvent::Stop(TrackingData::new(
+                    None,
+                    Local::now().into(),
+                )));
+                data.push(TrackingEvent::Start(TrackingData {
+                    description,
+                    project,
+                    tags,
+                    billable,
+                    ..TrackingData::new(None, Local::now().into())
+                }));
+            }
+        }
+    } else {
+        eprintln!("Time tracking is already running!");
+    }
+
+    Ok(())
+}
+
+fn format_event_time(event: &TrackingEvent) -> String {
+    event
+        .time(true)
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// inserts a start or stop event with an explicit time where it belongs, instead of appending it.
+/// a start within or right before an entry or a stop which doesn't end a running entry would mess
+/// up the totals, so they are refused, unless `force` splits the entry or ends the new one where
+/// the next one starts (start) or moves its end (stop).
+fn insert_at(data: &mut Vec<TrackingEvent>, event: TrackingEvent, force: bool) -> Result<()> {
+    let time = event.time(true);
+    let position = data.partition_point(|e| e.time(true) <= time);
+    let previous = position.checked_sub(1).map(|index| data[index].clone());
+    let next = data.get(position).cloned();
+    let at = format_event_time(&event);
+    match (&event, previous, next) {
+        (TrackingEvent::Start(_), Some(previous), _) if previous.is_start() => {
+            if !force {
+                return Err(anyhow!(
+                    "The start at {} is within the entry started at {}! Use --force to split that entry.",
+                    at,
+                    format_event_time(&previous)
+                ));
+            }
+            println!(
+                "Splitting the entry started at {}.",
+                format_event_time(&previous)
+            );
+            data.insert(position, TrackingEvent::Stop(TrackingData::new(None, time)));
+            data.insert(position + 1, event);
+        }
+        (TrackingEvent::Start(_), _, Some(next)) if next.is_start() => {
+            if !force {
+                return Err(anyhow!(
+                    "The start at {} runs into the entry started at {}! Use --force to stop it there.",
+                    at,
+                    format_event_time(&next)
+                ));
+            }
+            println!(
+                "Stopping at {}, where the next entry starts.",
+                format_event_time(&next)
+            );
+            data.insert(position, event);
+            data.insert(
+                position + 1,
+                TrackingEvent::Stop(TrackingData::new(None, next.time(true))),
+            );
+        }
+        (TrackingEvent::Start(_), _, _) => data.insert(position, event),
+        (_, None, _) => return Err(anyhow!("Time tracking was not running at {}!", at)),
+        (_, Some(previous), Some(next)) if previous.is_start() && next.is_stop() => {
+            if !force {
+                return Err(anyhow!(
+                    "The entry started at {} already stops at {}! Use --force to stop it at {} instead.",
+                    format_event_time(&previous),
+                    format_event_time(&next),
+                    at
+                ));
+            }
+            println!("Replacing the stop at {}.", format_event_time(&next));
+            data[position] = event;
+        }
+        (_, Some(previous), _) if previous.is_start() => data.insert(position, event),
+        (_, Some(previous), _) => {
+            if !force {
+                return Err(anyhow!(
+                    "Time tracking was not running at {}, the last entry stopped at {}! Use --force to extend that entry.",
+                    at,
+                    format_event_time(&previous)
+                ));
+            }
+            println!("Replacing the stop at {}.", format_event_time(&previous));
+            data[position - 1] = event;
+        }
+    }
+    Ok(())
+}
+
+fn add_interval(
+    data: &mut Vec<TrackingEvent>,
+    start: String,
+    end: String,
+    description: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    billable: Option<bool>,
+) -> Result<()> {
+    let start = parse_date_time(&start)?;
+    let end = match natural_time::parse_time(&end) {
+        Some(time) => Local
+            .from_local_datetime(
+                &start
+                    .with_timezone(&Local)
+                    .date()
+                    .naive_local()
+                    .and_time(time),
+            )
+            .single()
+            .context("invalid time")?
+            .with_timezone(&Utc),
+        None => match natural_time::parse_duration(&end) {
+            Some(duration) => start
+                .checked_add_signed(duration)
+                .context("invalid duration")?,
+            None => parse_date_time(&end)?,
+        },
+    };
+    if end <= start {
+        return Err(anyhow!("The interval has to end after it started!"));
+    }
+
+    let mut data_iterator = data.iter();
+    while let Some(other_start) = data_iterator.find(|e| e.is_start()) {
+        let other_end = data_iterator
+            .find(|e| e.is_stop())
+            .map_or_else(Utc::now, |stop| stop.time(true));
+        if start < other_end && other_start.time(true) < end {
+            return Err(anyhow!(
+                "The interval overlaps with the entry started at {}!",
+                other_start
+                    .time(true)
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+    }
+
+    data.push(TrackingEvent::Start(TrackingData {
+        description,
+        project,
+        tags,
+        billable,
+        ..TrackingData::new(None, start)
+    }));
+    data.push(TrackingEvent::Stop(TrackingData::new(None, end)));
+
+    Ok(())
+}
+
+/// adds the stop event of `start --for`, if a start event was just added
+fn add_planned_stop(data: &mut Vec<TrackingEvent>, duration: Duration) {
+    if let Some(TrackingEvent::Start(start)) = data.last() {
+        let time = start.time + duration;
+        println!(
+            "Time tracking stops at {}.",
+            time.with_timezone(&Local).format("%H:%M")
+        );
+        data.push(TrackingEvent::Stop(TrackingData::new(None, time)));
+    }
+}
+
+/// the time of the last event, if it is a stop in the future, like the one written by `start --for`
+fn planned_stop(data: &[TrackingEvent]) -> Option<DateTime<Utc>> {
+    match data.last() {
+        Some(TrackingEvent::Stop(stop)) if stop.time > Utc::now() => Some(stop.time),
+        _ => None,
+    }
+}
+
+/// ends the planned interval now, because time tracking is started (or stopped) now. returns the
+/// message about it
+fn end_planned_stop(data: &mut Vec<TrackingEvent>, starting: bool) -> Option<String> {
+    let stop = planned_stop(data)?.with_timezone(&Local).format("%H:%M");
+    if starting {
+        if let Some(TrackingEvent::Stop(event)) = data.last_mut() {
+            event.time = Local::now().into();
+        }
+    } else {
+        // replaced by the stop event of the command
+        data.pop();
+    }
+    Some(format!("Ending the entry planned until {} now.", stop))
+}
+
+/// starting or stopping now ends the planned interval early. other commands only get a warning,
+/// because the running entry looks stopped until then.
+fn handle_planned_stop(command: &Command, data: &mut Vec<TrackingEvent>) {
+    let message = match command {
+        Command::Start { at: None, .. } => end_planned_stop(data, true),
+        Command::Stop { at: None, .. } => end_planned_stop(data, false),
+        _ => {
+            if let Some(stop) = planned_stop(data) {
+                eprintln!(
+                    "Warning: time tracking is planned to stop at {}.",
+                    stop.with_timezone(&Local).format("%H:%M")
+                );
+            }
+            None
+        }
+    };
+    if let Some(message) = message {
+        println!("{}", message);
+    }
+}
+
+fn stop_tracking(
+    data: &mut Vec<TrackingEvent>,
+    description: Option<String>,
+    at: Option<String>,
+    force: bool,
+) -> Result<()> {
+    // stopping during a break ends the session when the break started, instead of after it
+    if let Some(TrackingEvent::Pause(pause)) = data.last() {
+        let time = match at {
+            Some(at) => parse_date_time(&at)?,
+            None => pause.time,
+        };
+        data.pop();
+        data.push(TrackingEvent::Stop(TrackingData::new(description, time)));
+        return Ok(());
+    }
+
+    let should_add = match data.last() {
+        None => true,
+        Some(event) => event.is_start(),
+    };
+    if let Some(at) = at {
+        let stop = TrackingEvent::Stop(TrackingData::new(description, parse_date_time(&at)?));
+        insert_at(data, stop, force)?;
+    } else if should_add {
+        data.push(TrackingEvent::Stop(TrackingData::new(
+            description,
+            Local::now().into(),
+        )))
+    } else {
+        eprintln!("Time tracking is already stopped!");
+    }
+
+    Ok(())
+}
+
+fn pause_tracking(
+    data: &mut Vec<TrackingEvent>,
+    description: Option<String>,
+    at: Option<String>,
+) -> Result<()> {
+    match data.last() {
+        Some(event) if event.is_start() => data.push(TrackingEvent::Pause(TrackingData::new(
+            description,
+            at.map_or_else(|| Ok(Local::now().into()), |at| parse_date_time(&at))?,
+        ))),
+        _ => eprintln!("Time tracking is not running!"),
+    }
+
+    Ok(())
+}
+
+fn resume_tracking(data: &mut Vec<TrackingEvent>, at: Option<String>) -> Result<()> {
+    if let Some(TrackingEvent::Pause(_)) = data.last() {
+        let time = at.map_or_else(|| Ok(Local::now().into()), |at| parse_date_time(&at))?;
+        let last_start = data
+            .iter()
+            .rev()
+            .find(|t| t.is_start())
+            .map_or_else(|| TrackingData::new(None, time), |t| t.data().clone());
+        data.push(TrackingEvent::Resume(last_start.with_time(time)));
+    } else {
+        eprintln!("Time tracking is not paused!");
+    }
+
+    Ok(())
+}
+
+/// the started entries with distinct descriptions, the most recent first, like `tt recent`
+fn recent_tasks(data: &[TrackingEvent]) -> Vec<&TrackingData> {
+    let mut tasks: Vec<&TrackingData> = Vec::new();
+    for task in data.iter().rev().filter(|e| e.is_start()).map(|e| e.data()) {
+        if task.description.is_some() && !tasks.iter().any(|t| t.description == task.description) {
+            tasks.push(task);
+        }
+    }
+    tasks
+}
+
+/// asks which of the tasks to continue
+fn pick_task<'a>(tasks: &[&'a TrackingData]) -> Result<&'a TrackingData> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "{} tasks match, please give a more specific text or the number of the task!",
+            tasks.len()
+        ));
+    }
+    for (i, task) in tasks.iter().enumerate() {
+        println!("({}) {}", i + 1, to_human_readable("Last started", task));
+    }
+    loop {
+        println!();
+        println!("Please enter the number of the task to continue: ");
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 || input.trim().is_empty() {
+            return Err(anyhow!("Nothing continued."));
+        }
+        match input.trim().parse::<usize>() {
+            Ok(n) if n >= 1 && n <= tasks.len() => return Ok(tasks[n - 1]),
+            _ => println!("Please enter a number between 1 and {}!", tasks.len()),
+        }
+    }
+}
+
+/// the line for a task in the fuzzy finder, like "fix bug [website] #urgent"
+fn task_label(task: &TrackingData) -> String {
+    let mut label = task.description.clone().unwrap_or_default();
+    if let Some(project) = &task.project {
+        label.push_str(&format!(" [{}]", project));
+    }
+    for tag in &task.tags {
+        label.push_str(&format!(" #{}", tag));
+    }
+    label.trim_start().to_string()
+}
+
+/// the started entries with distinct descriptions and projects, the most recent first
+fn pickable_tasks(data: &[TrackingEvent]) -> Vec<&TrackingData> {
+    let mut tasks: Vec<&TrackingData> = Vec::new();
+    for task in data.iter().rev().filter(|e| e.is_start()).map(|e| e.data()) {
+        if (task.description.is_some() || task.project.is_some())
+            && !tasks
+                .iter()
+                .any(|t| t.description == task.description && t.project == task.project)
+        {
+            tasks.push(task);
+        }
+    }
+    tasks
+}
+
+/// opens a fuzzy finder over the previous tasks
+fn fuzzy_pick_task(data: &[TrackingEvent]) -> Result<TrackingData> {
+    let tasks = pickable_tasks(data);
+    if tasks.is_empty() {
+        return Err(anyhow!("There are no previous tasks to pick from!"));
+    }
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("--pick needs an interactive terminal!"));
+    }
+    let labels: Vec<_> = tasks.iter().map(|task| task_label(task)).collect();
+    let selection = dialoguer::FuzzySelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Task to start")
+        .items(&labels)
+        .default(0)
+        .interact_opt()?
+        .ok_or_else(|| anyhow!("Nothing started."))?;
+    Ok(tasks[selection].clone())
+}
+
+fn continue_tracking(
+    data: &mut Vec<TrackingEvent>,
+    number: Option<usize>,
+    description_contains: Option<String>,
+) -> Result<()> {
+    if !matches!(
+        data.last(),
+        Some(TrackingEvent::Stop { .. }) | Some(TrackingEvent::Pause { .. })
+    ) {
+        eprintln!("Time tracking couldn't be continued, because there are no entries. Use the start command instead!");
+        return Ok(());
+    }
+    let tasks = recent_tasks(data);
+    let task = match (number, description_contains) {
+        (Some(number), _) => number
+            .checked_sub(1)
+            .and_then(|i| tasks.get(i).copied())
+            .ok_or_else(|| anyhow!("There is no task with number {}!", number))?,
+        (None, Some(text)) => {
+            let matching: Vec<_> = tasks
+                .iter()
+                .copied()
+                .filter(|task| {
+                    matches!(&task.description, Some(description) if description.contains(&text))
+                })
+                .collect();
+            match matching.len() {
+                0 => return Err(anyhow!("No task contains \"{}\"!", text)),
+                1 => matching[0],
+                _ => pick_task(&matching)?,
+            }
+        }
+        (None, None) => match data.iter().rev().find(|t| t.is_start()) {
+            Some(last_start) => last_start.data(),
+            None => return Ok(()),
+        },
+    };
+    let start = task.clone().with_time(Local::now().into());
+    data.push(TrackingEvent::Start(start));
     Ok(())
 }
 
-fn stop_tracking(
-    data: &mut Vec<TrackingEvent>,
-    description: Option<String>,
-    at: Option<String>,
-) -> Result<()> {
-    let should_add = match data.last() {
-        None => true,
-        Some(event) => event.is_start(),
-    };
-    if should_add || at.is_some() {
-        data.push(TrackingEvent::Stop(TrackingData {
-            description,
-            time: at.map_or_else(|| Ok(Local::now().into()), |at| parse_date_time(&at))?,
-        }))
-    } else {
-        eprintln!("Time tracking is already stopped!");
+fn annotate(data: &mut [TrackingEvent], description: String, index: Option<usize>) -> Result<()> {
+    if index.is_none() && !matches!(data.last(), Some(event) if event.is_start()) {
+        eprintln!("Time tracking is not running!");
+        return Ok(());
     }
 
+    let index = index.unwrap_or_default();
+    let start = (0..data.len())
+        .rev()
+        .filter(|i| matches!(data[*i], TrackingEvent::Start(_)))
+        .nth(index)
+        .ok_or_else(|| anyhow!("There is no entry with index {}!", index))?;
+    // the resumes after the breaks of the entry repeat its description
+    let entry = &mut data[start..];
+    let end = entry
+        .iter()
+        .skip(1)
+        .position(|t| matches!(t, TrackingEvent::Start(_) | TrackingEvent::Stop(_)))
+        .map_or(entry.len(), |end| end + 1);
+    for event in &mut entry[..end] {
+        if let TrackingEvent::Start(data) | TrackingEvent::Resume(data) = event {
+            data.description = Some(description.clone());
+        }
+    }
     Ok(())
 }
 
-fn continue_tracking(data: &mut Vec<TrackingEvent>) {
-    if let Some(TrackingEvent::Stop { .. }) = data.last() {
-        if let Some(TrackingEvent::Start(TrackingData { description, .. })) =
-            data.iter().rev().find(|t| t.is_start()).cloned()
-        {
-            data.push(TrackingEvent::Start(TrackingData {
-                description,
-                time: Local::now().into(),
-            }))
+fn add_note(data: &mut [TrackingEvent], text: Option<String>, index: Option<usize>) -> Result<()> {
+    if index.is_none() && !matches!(data.last(), Some(event) if event.is_start()) {
+        eprintln!("Time tracking is not running!");
+        return Ok(());
+    }
+
+    let text = match text {
+        Some(text) => text,
+        None => {
+            let mut text = String::new();
+            io::Read::read_to_string(&mut io::stdin(), &mut text)?;
+            text
         }
-    } else {
-        eprintln!("Time tracking couldn't be continued, because there are no entries. Use the start command instead!");
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("The note is empty!"));
+    }
+
+    let index = index.unwrap_or_default();
+    match data.iter_mut().rev().filter(|t| t.is_start()).nth(index) {
+        Some(event) => {
+            event.data_mut().notes.push(Note {
+                time: Utc::now(),
+                text: text.to_string(),
+            });
+            Ok(())
+        }
+        None => Err(anyhow!("There is no entry with index {}!", index)),
     }
 }
 
@@ -362,27 +1964,62 @@ fn split_duration(duration: Duration) -> (i64, i64, i64) {
     (hours, minutes, seconds)
 }
 
-fn filter_events(
-    data: &[TrackingEvent],
-    from: &Option<String>,
-    to: &Option<String>,
-    filter: &Option<String>,
-) -> Result<Vec<TrackingEvent>> {
-    let (filter, from, to) = match filter {
-        Some(from) if from == "week" => {
-            let now = Local::today();
-            let weekday = now.weekday();
-            let offset = weekday.num_days_from_monday();
-            let (monday_offset, sunday_offset) = (offset, 6 - offset);
-            let from = DateOrDateTime::Date(
-                (now - Duration::days(i64::from(monday_offset))).naive_local(),
-            );
-            let to = DateOrDateTime::Date(
-                (now + Duration::days(i64::from(sunday_offset))).naive_local(),
-            );
-            (None, Some(from), Some(to))
+/// the first and last day of the range described by a filter keyword like "week" or "last-month"
+fn keyword_range(
+    keyword: &str,
+    today: NaiveDate,
+    week_starts_on: Weekday,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let first_of_month = |year, month| NaiveDate::from_ymd(year, month, 1);
+    let next_month = |date: NaiveDate| match date.month() {
+        12 => first_of_month(date.year() + 1, 1),
+        month => first_of_month(date.year(), month + 1),
+    };
+    let this_month = first_of_month(today.year(), today.month());
+    let this_week = settings::start_of_week(today, week_starts_on);
+    match keyword {
+        "today" => Some((today, today)),
+        "yesterday" => Some((today.pred(), today.pred())),
+        "week" => Some((this_week, this_week + Duration::days(6))),
+        "last-week" => Some((this_week - Duration::days(7), this_week.pred())),
+        "month" => Some((this_month, next_month(this_month).pred())),
+        "last-month" => {
+            let last_month = first_of_month(this_month.pred().year(), this_month.pred().month());
+            Some((last_month, this_month.pred()))
         }
-        f => {
+        "year" => Some((
+            NaiveDate::from_ymd(today.year(), 1, 1),
+            NaiveDate::from_ymd(today.year(), 12, 31),
+        )),
+        _ => None,
+    }
+}
+
+/// the times between which the data of a command is needed. `None` is unbounded.
+type Bounds = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// the description filter and the range of the filter
+fn filter_range(
+    filter: &FilterData,
+    week_starts_on: Weekday,
+) -> Result<(
+    Option<String>,
+    Option<DateOrDateTime>,
+    Option<DateOrDateTime>,
+)> {
+    let FilterData {
+        from, to, filter, ..
+    } = filter;
+    let range = filter
+        .as_deref()
+        .and_then(|f| keyword_range(f, Local::today().naive_local(), week_starts_on));
+    let (filter, from, to) = match range {
+        Some((from, to)) => (
+            None,
+            Some(DateOrDateTime::Date(from)),
+            Some(DateOrDateTime::Date(to)),
+        ),
+        None => {
             let from = from.as_deref().map_or_else(
                 || Ok(DateOrDateTime::Date(Local::today().naive_local())),
                 parse_date_or_date_time,
@@ -397,30 +2034,59 @@ fn filter_events(
                         from @ DateOrDateTime::Date(..) => from,
                     })
                 })?;
-            (f.clone(), Some(from), Some(to))
+            (filter.clone(), Some(from), Some(to))
         }
     };
+    Ok((filter, from, to))
+}
+
+/// the times between which the data file has to be read for the filter. entries are filtered in
+/// the timezone they were recorded in, so a day is added on both sides.
+fn filter_bounds(filter: &FilterData, week_starts_on: Weekday) -> Result<Bounds> {
+    let (filter, from, to) = filter_range(filter, week_starts_on)?;
+    if filter.as_deref() == Some("all") {
+        return Ok((None, None));
+    }
+    let since = from.map(|from| match from {
+        DateOrDateTime::Date(from) => from.and_hms(0, 0, 0),
+        DateOrDateTime::DateTime(from) => from,
+    });
+    let until = to.map(|to| match to {
+        DateOrDateTime::Date(to) => to.succ().and_hms(0, 0, 0),
+        DateOrDateTime::DateTime(to) => to,
+    });
+    Ok((
+        since.map(|since| Utc.from_utc_datetime(&(since - Duration::days(1)))),
+        until.map(|until| Utc.from_utc_datetime(&(until + Duration::days(1)))),
+    ))
+}
+
+fn filter_events(
+    data: &[TrackingEvent],
+    filter_data: &FilterData,
+    week_starts_on: Weekday,
+) -> Result<Vec<TrackingEvent>> {
+    let (filter, from, to) = filter_range(filter_data, week_starts_on)?;
+    let FilterData {
+        project,
+        tags,
+        descriptions,
+        match_all,
+        host,
+        ..
+    } = filter_data;
     let data_iterator = data
         .iter()
         .filter(|entry| {
             iif!(
                 filter.clone().unwrap_or_default() == "all",
                 true,
+                // days are compared in the timezone the entry was recorded in
                 match from {
                     None => true,
-                    Some(DateOrDateTime::Date(from)) => {
-                        entry.time(true).timestamp_millis()
-                            >= TimeZone::from_local_date(&Local, &from)
-                                .unwrap()
-                                .and_time(NaiveTime::from_hms(0, 0, 0))
-                                .expect("Failed to add time from date")
-                                .timestamp_millis()
-                    }
+                    Some(DateOrDateTime::Date(from)) => entry.local_date() >= from,
                     Some(DateOrDateTime::DateTime(from)) => {
-                        entry.time(true).timestamp_millis()
-                            >= TimeZone::from_local_datetime(&Local, &from)
-                                .unwrap()
-                                .timestamp_millis()
+                        entry.local_time().naive_local() >= from
                     }
                 }
             )
@@ -431,34 +2097,39 @@ fn filter_events(
                 true,
                 match to {
                     None => true,
-                    Some(DateOrDateTime::Date(to)) => {
-                        entry.time(true).timestamp_millis()
-                            <= TimeZone::from_local_date(&Local, &to)
-                                .unwrap()
-                                .and_time(NaiveTime::from_hms(23, 59, 59))
-                                .expect("Failed to add time from date")
-                                .timestamp_millis()
-                    }
-                    Some(DateOrDateTime::DateTime(to)) => {
-                        entry.time(true).timestamp_millis()
-                            <= TimeZone::from_local_datetime(&Local, &to)
-                                .unwrap()
-                                .timestamp_millis()
-                    }
+                    Some(DateOrDateTime::Date(to)) => entry.local_date() <= to,
+                    Some(DateOrDateTime::DateTime(to)) => entry.local_time().naive_local() <= to,
                 }
             )
         })
-        .filter(|entry| match entry {
-            TrackingEvent::Start(TrackingData { description, .. })
-            | TrackingEvent::Stop(TrackingData { description, .. }) => match (&filter, description)
+        .filter(|entry| match (&filter, &entry.data().description) {
+            (Some(filter), Some(description)) => filter == "all" || description.contains(filter),
+            (Some(filter), None) => filter == "all",
+            (None, _) => true,
+        })
+        // project, tags, descriptions and hosts are taken from the start events, so stop events are
+        // kept if they end a matching interval
+        .scan(false, |in_matching_interval, entry| {
+            let keep = if project.is_none()
+                && tags.is_empty()
+                && descriptions.is_empty()
+                && host.is_none()
             {
-                (Some(filter), Some(description)) => {
-                    filter == "all" || description.contains(filter)
-                }
-                (Some(filter), None) => filter == "all",
-                (None, _) => true,
-            },
+                true
+            } else if entry.is_start() {
+                *in_matching_interval = entry.matches_project_and_tags(project, tags)
+                    && (descriptions.is_empty()
+                        || entry.matches_descriptions(descriptions, *match_all))
+                    && host
+                        .as_ref()
+                        .is_none_or(|host| entry.data().host.as_ref() == Some(host));
+                *in_matching_interval
+            } else {
+                std::mem::replace(in_matching_interval, false)
+            };
+            Some((keep, entry))
         })
+        .filter_map(|(keep, entry)| iif!(keep, Some(entry), None))
         .skip_while(|entry| TrackingEvent::is_stop(entry));
 
     Ok(data_iterator.cloned().collect())
@@ -469,15 +2140,11 @@ fn get_data_as_days(data: &[TrackingEvent]) -> Vec<Vec<TrackingEvent>> {
         return vec![];
     }
 
-    let mut current_day = data
-        .first()
-        .expect("Tracking event is empty")
-        .time(true)
-        .date();
+    let mut current_day = data.first().expect("Tracking event is empty").local_date();
     let mut result = Vec::new();
     let mut current = Vec::new();
     for d in data {
-        let date = d.time(true).date();
+        let date = d.local_date();
         if current_day == date {
             current.push(d.clone());
         } else {
@@ -495,28 +2162,65 @@ fn get_data_as_days(data: &[TrackingEvent]) -> Vec<Vec<TrackingEvent>> {
 
 const CHECKED_ADD_DURATION_ERROR: &str = "couldn't add up durations";
 
-fn get_time_from_day(
+struct DaySummary {
+    first_start: Option<DateTime<Utc>>,
+    last_stop: Option<DateTime<Utc>>,
+    running: bool,
+    work_time: Duration,
+    billable_time: Duration,
+}
+
+impl DaySummary {
+    fn break_time(&self) -> Duration {
+        match (self.first_start, self.last_stop) {
+            (Some(first), Some(last)) => (last - first - self.work_time).max(Duration::zero()),
+            _ => Duration::zero(),
+        }
+    }
+}
+
+fn summarize_day(
     settings: &Settings,
     data: &[TrackingEvent],
-    include_seconds: bool,
-) -> Duration {
+    options: CalculationOptions,
+) -> DaySummary {
+    let include_seconds = options.include_seconds || precision::include_seconds();
+    let round = |duration| {
+        iif!(
+            options.no_round,
+            duration,
+            settings.rounding.apply(duration)
+        )
+    };
     let mut data_iterator = data.iter();
     let mut work_day = Duration::zero();
+    let mut billable = Duration::zero();
     let mut first = None;
     let mut last = None;
+    let mut running = false;
     loop {
         let start = data_iterator.find(|e| e.is_start());
         let stop = data_iterator.find(|e| e.is_stop());
         match (start, stop) {
             (Some(start), Some(stop)) => {
+                let start_time = start.time(include_seconds);
+                let duration = match options
+                    .limit_interval(settings, stop.time(include_seconds) - start_time)
+                {
+                    Some(duration) => duration,
+                    None => continue,
+                };
                 if let None = first {
-                    first = Some(start.time(include_seconds));
+                    first = Some(start_time);
                 }
-                last = Some(stop.time(include_seconds));
-                let duration = stop.time(include_seconds) - start.time(include_seconds);
+                last = Some(start_time + duration);
+                let duration = round(duration);
                 work_day = work_day
                     .checked_add(&duration)
                     .expect(CHECKED_ADD_DURATION_ERROR);
+                if start.data().is_billable(settings) {
+                    billable += duration;
+                }
             }
             (Some(start), None) => {
                 if let None = first {
@@ -528,37 +2232,59 @@ fn get_time_from_day(
                     Utc::now().with_second(0).unwrap()
                 };
                 last = Some(now);
-                let duration = now - start.time(include_seconds);
+                running = true;
+                let duration = options
+                    .limit_interval(settings, now - start.time(include_seconds))
+                    .map_or_else(Duration::zero, round);
                 work_day = work_day
                     .checked_add(&duration)
                     .expect(CHECKED_ADD_DURATION_ERROR);
+                if start.data().is_billable(settings) {
+                    billable += duration;
+                }
                 break;
             }
             (_, _) => break,
         }
     }
-    if settings.min_daily_break > 0 {
+    if settings.min_daily_break > Duration::zero() {
         let now = Utc::now();
         let total = last.unwrap_or(now) - first.unwrap_or(now);
         let pause = total - work_day;
-        let min_break_duration = Duration::minutes(i64::from(settings.min_daily_break));
+        let min_break_duration = settings.min_daily_break;
         if pause > Duration::zero() && pause < min_break_duration {
             let difference = min_break_duration - pause;
             work_day = work_day - difference;
         }
     }
-    work_day.max(Duration::zero())
+    let work_time = work_day.max(Duration::zero());
+    DaySummary {
+        first_start: first,
+        last_stop: last,
+        running,
+        work_time,
+        // the minimum break is taken from the billable time last
+        billable_time: billable.min(work_time),
+    }
+}
+
+fn get_time_from_day(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Duration {
+    summarize_day(settings, data, options).work_time
 }
 
 fn get_time_from_events(
     settings: &Settings,
     data: &[TrackingEvent],
-    include_seconds: bool,
+    options: CalculationOptions,
 ) -> Duration {
     let days = get_data_as_days(data);
     let mut time = Duration::zero();
     for day in days {
-        let time_for_day = get_time_from_day(&settings, &day, include_seconds);
+        let time_for_day = get_time_from_day(&settings, &day, options);
         time = time
             .checked_add(&time_for_day)
             .expect(CHECKED_ADD_DURATION_ERROR);
@@ -566,31 +2292,60 @@ fn get_time_from_events(
     time
 }
 
+fn format_duration(duration: Duration) -> String {
+    let (hours, minutes, seconds) = split_duration(duration);
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
 fn get_remaining_minutes(settings: &Settings, filter: &str, hours: i64, minutes: i64) -> i64 {
     let total = minutes + (hours * 60);
+    let today = Local::today().naive_local();
     let time_goal = if filter == "week" {
-        &settings.time_goal.weekly
+        settings.weekly_goal_for_date(today)
+    } else {
+        settings.goal_for_date(today)
+    };
+    time_goal.num_minutes() - total
+}
+
+/// fills the placeholders of the `--format` of `tt show`
+fn format_work_time(
+    format: &str,
+    (hours, minutes, seconds): (i64, i64, i64),
+    options: CalculationOptions,
+) -> String {
+    let seconds = if options.include_seconds || precision::include_seconds() {
+        seconds
     } else {
-        &settings.time_goal.daily
+        0
     };
-    let required = i64::from(time_goal.minutes) + (i64::from(time_goal.hours) * 60);
-    required - total
+    format
+        .replace("{hh}", &format!("{:02}", hours))
+        .replace("{mm}", &format!("{:02}", minutes))
+        .replace("{ss}", &format!("{:02}", seconds))
+        .replace("{h}", &format!("{}", hours))
+        .replace("{m}", &format!("{}", minutes))
+        .replace("{s}", &format!("{}", seconds))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn show(
     settings: &Settings,
     data: &[TrackingEvent],
-    filter: &FilterData,
+    filter_data: &FilterData,
     format: Option<String>,
-    include_seconds: bool,
+    options: CalculationOptions,
     plain: bool,
     remaining: bool,
+    group_by: Option<SummaryKey>,
 ) -> Result<()> {
-    let FilterData { from, to, filter } = filter;
-    let filtered_data = filter_events(data, &from, &to, &filter)?;
-    let work_time = get_time_from_events(&settings, &filtered_data, include_seconds);
+    let filtered_data = filter_events(data, filter_data, settings.week_starts_on)?;
+    let work_time = get_time_from_events(&settings, &filtered_data, options);
     let (mut hours, mut minutes, mut seconds) = split_duration(work_time);
 
+    let FilterData {
+        from, to, filter, ..
+    } = filter_data;
     let filter = filter.clone().unwrap_or_default();
     if remaining {
         if (filter == "week" || filter.is_empty()) && from.is_none() && to.is_none() {
@@ -598,17 +2353,24 @@ fn show(
             let mut remaining_minutes = get_remaining_minutes(&settings, &filter, hours, minutes);
 
             if filter != "week" {
-                let filtered_data_week =
-                    filter_events(&data, &None, &None, &Some("week".to_string()))?;
-                let week_work_time =
-                    get_time_from_events(&settings, &filtered_data_week, include_seconds);
+                let filtered_data_week = filter_events(
+                    &data,
+                    &FilterData {
+                        filter: Some("week".to_string()),
+                        project: filter_data.project.clone(),
+                        tags: filter_data.tags.clone(),
+                        ..FilterData::default()
+                    },
+                    settings.week_starts_on,
+                )?;
+                let week_work_time = get_time_from_events(&settings, &filtered_data_week, options);
                 let (week_hours, week_minutes, _) = split_duration(week_work_time);
                 let remaining_minutes_week =
                     get_remaining_minutes(&settings, "week", week_hours, week_minutes);
 
                 let today = Local::today().weekday();
-                
-                if today == settings.last_day_of_work_week {
+
+                if today == settings.last_day_of_work_week() {
                     // on last day in a work week, always show remaining minutes for week
                     remaining_minutes = remaining_minutes_week;
                 } else {
@@ -626,157 +2388,315 @@ fn show(
             return Ok(());
         }
     }
-    let seconds_final = if include_seconds { seconds } else { 0 };
     let format = format.unwrap_or_else(|| "{hh}:{mm}:{ss}".to_string());
-    let time = format
-        .replace("{hh}", &format!("{:02}", hours))
-        .replace("{mm}", &format!("{:02}", minutes))
-        .replace("{ss}", &format!("{:02}", seconds_final))
-        .replace("{h}", &format!("{}", hours))
-        .replace("{m}", &format!("{}", minutes))
-        .replace("{s}", &format!("{}", seconds_final));
+    if let Some(key) = group_by {
+        let totals = report::sum_by_key(settings, &filtered_data, key, options);
+        let width = totals
+            .iter()
+            .map(|(group, _, _)| group.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (group, duration, _) in totals {
+            let time = format_work_time(&format, split_duration(duration), options);
+            println!("{:<width$}  {}", group, time, width = width);
+        }
+    }
+    let time = format_work_time(&format, (hours, minutes, seconds), options);
     if plain {
         println!("{}", time);
     } else if remaining {
-        println!("Remaining Work Time: {}", time);
+        let style = iif!(hours == 0 && minutes == 0, Style::Active, Style::Header);
+        println!("Remaining Work Time: {}", paint(time, style));
     } else {
-        println!("Work Time: {}", time);
+        println!("Work Time: {}", paint(time, Style::Header));
     }
 
     Ok(())
 }
 
-fn cleanup(data: &[TrackingEvent]) -> Vec<TrackingEvent> {
-    let mut cleaned = Vec::with_capacity(data.len());
+/// the numbered events of the entries which start with one of the selected events: the start,
+/// its breaks and the stop
+fn whole_entries(
+    data: &[TrackingEvent],
+    selected: &[(usize, TrackingEvent)],
+) -> Vec<(usize, TrackingEvent)> {
+    let mut entries = Vec::new();
+    let mut in_entry = false;
+    for (i, event) in data.iter().enumerate() {
+        if let TrackingEvent::Start(_) = event {
+            in_entry = selected.iter().any(|(number, _)| *number == i);
+        }
+        if in_entry {
+            entries.push((i, event.clone()));
+        }
+        if let TrackingEvent::Stop(_) = event {
+            in_entry = false;
+        }
+    }
+    entries
+}
 
-    let mut data_iter = data.iter();
-    let mut conflicting = Vec::new();
+/// pairs the filtered events with their position in the data file, which doesn't change when
+/// another filter is used
+fn number_events(
+    data: &[TrackingEvent],
+    filtered_data: Vec<TrackingEvent>,
+) -> Vec<(usize, TrackingEvent)> {
+    let mut positions = data.iter().enumerate();
+    filtered_data
+        .into_iter()
+        .filter_map(|event| {
+            positions
+                .find(|(_, e)| **e == event)
+                .map(|(i, _)| (i, event))
+        })
+        .collect()
+}
 
-    let mut is_start = None;
+/// the color of an entry in `tt list`: the running entry is active, a start without a stop is an
+/// error and entries of previous days are dimmed
+fn list_style(data: &[TrackingEvent], position: usize) -> Option<Style> {
+    let event = &data[position];
+    match data.get(position + 1) {
+        None if event.is_start() => Some(Style::Active),
+        Some(next) if event.is_start() && next.is_start() => Some(Style::Error),
+        _ => iif!(
+            event.local_date() < Local::today().naive_local(),
+            Some(Style::Dim),
+            None
+        ),
+    }
+}
 
-    let mut all_conflicting = Vec::new();
+/// how `tt list` shows the entries
+struct ListOptions {
+    /// only the last n entries
+    last: Option<usize>,
+    /// the newest entries first
+    reverse: bool,
+    /// with the notes
+    verbose: bool,
+    ids: bool,
+    /// without cutting the descriptions to the width of the terminal
+    wide: bool,
+    /// a line of json per entry instead of the table
+    json: bool,
+}
 
-    while let Some(e) = data_iter.next() {
-        match is_start {
-            None => {
-                is_start = Some(e.is_start());
-                cleaned.push(e);
-            }
-            Some(true) => {
-                if e.is_start() {
-                    if conflicting.is_empty() {
-                        if let Some(last_cleaned_e) = cleaned.pop() {
-                            conflicting.push(last_cleaned_e);
-                        }
-                    }
-                    conflicting.push(e);
-                } else {
-                    if !conflicting.is_empty() {
-                        all_conflicting.push(conflicting);
-                        conflicting = Vec::new();
-                    }
-                    cleaned.push(e);
-                    is_start.replace(false);
-                }
-            }
-            Some(false) => {
-                if e.is_stop() {
-                    if conflicting.is_empty() {
-                        if let Some(last_cleaned_e) = cleaned.pop() {
-                            conflicting.push(last_cleaned_e);
-                        }
-                    }
-                    conflicting.push(e);
-                } else {
-                    if !conflicting.is_empty() {
-                        all_conflicting.push(conflicting);
-                        conflicting = Vec::new();
-                    }
-                    cleaned.push(e);
-                    is_start.replace(true);
-                }
-            }
+/// the entry with the number `index` as it is printed by `tt list --json`
+/// the index of archived events is null, since they have no number
+fn list_json(index: Option<usize>, event: &TrackingEvent) -> serde_json::Value {
+    let data = event.data();
+    serde_json::json!({
+        "index": index,
+        "id": data.id,
+        "type": event.name().to_lowercase(),
+        "time": data.time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "description": data.description,
+        "project": data.project,
+        "tags": data.tags,
+    })
+}
+
+/// the number of an entry in `tt list`, archived entries have none
+fn format_number(number: Option<usize>) -> String {
+    number.map_or_else(|| "-".to_string(), |number| number.to_string())
+}
+
+/// the first `archived` events in `data` come from the archives and are listed without a number,
+/// since they can't be changed. `first` is the number of the first event after them, which might
+/// not be the beginning of the data file.
+fn list(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    archived: usize,
+    first: usize,
+    filter: &FilterData,
+    options: ListOptions,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let mut entries: Vec<_> = number_events(data, filtered_data)
+        .into_iter()
+        .map(|(i, event)| {
+            let number = iif!(i < archived, None, Some(first + i - archived));
+            (number, list_style(data, i), event)
+        })
+        .collect();
+    if let Some(last) = options.last {
+        entries.drain(..entries.len().saturating_sub(last));
+    }
+    if options.reverse {
+        entries.reverse();
+    }
+    if options.json {
+        for (i, _, event) in &entries {
+            println!("{}", list_json(*i, event));
         }
+        return Ok(());
     }
 
-    for mut conflicting in all_conflicting {
-        let event_type = iif!(
-            conflicting
-                .first()
-                .expect("Nothing first tracking event founded")
-                .is_start(),
-            "start",
-            "stop"
+    let width = entries
+        .iter()
+        .map(|(number, _, _)| format_number(*number).len())
+        .max()
+        .unwrap_or_default();
+    let mut table = Table::new(vec![
+        table::Column::right(""),
+        table::Column::left(""),
+        table::Column::left(""),
+        table::Column::left(""),
+        table::Column::left("").truncated(),
+        table::Column::left(""),
+        table::Column::left("").truncated(),
+        table::Column::left(""),
+    ])
+    .without_header();
+    for (number, style, event) in &entries {
+        let data = event.data();
+        let id = iif!(options.ids, data.id.to_string(), String::new());
+        let origin = match (&data.user, &data.host) {
+            _ if !options.verbose => String::new(),
+            (Some(user), Some(host)) => format!("{}@{}", user, host),
+            (Some(name), None) | (None, Some(name)) => name.clone(),
+            (None, None) => String::new(),
+        };
+        let project = data
+            .project
+            .as_ref()
+            .map(|p| format!("[{}]", p))
+            .unwrap_or_default();
+        let tags: Vec<_> = data.tags.iter().map(|t| format!("#{}", t)).collect();
+        table.add_styled_row(
+            vec![
+                format!("({})", format_number(*number)).into(),
+                id.into(),
+                event.name().into(),
+                locale::format_date_time(&data.local_time()).into(),
+                data.description.clone().unwrap_or_default().into(),
+                project.into(),
+                tags.join(" ").into(),
+                origin.into(),
+            ],
+            *style,
         );
-        println!("Repeated {} events found:", event_type);
-        for (i, event) in conflicting.iter().enumerate() {
-            println!(
-                "({}) {}",
-                i,
-                to_human_readable(
-                    &format!("S{}", &event_type[1..]),
-                    &event.time(true).with_timezone(&Local),
-                    event.description()
-                )
-            );
-        }
-        loop {
-            println!();
-            println!("Please enter the number of the entry to keep (<num>|skip) [default: skip]: ");
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) => {
-                    let text = input.trim();
-                    if text == "skip" || text.is_empty() {
-                        cleaned.append(&mut conflicting);
-                        break;
-                    } else {
-                        let parsed: Result<usize, _> = text.parse();
-                        match parsed {
-                            Ok(n) => match conflicting.get(n) {
-                                Some(value) => {
-                                    cleaned.push(value);
-                                    break;
-                                }
-                                None => println!("Please use one of the numbers given above!"),
-                            },
-                            Err(_) => println!("Could not parse number!"),
-                        }
-                    }
-                }
-                Err(_) => println!("Could not read from stdin!"),
+        if options.verbose {
+            // the notes start below the event
+            for line in notes_to_human_readable(data, width + 4) {
+                table.add_text(line);
             }
         }
     }
+    pager::print_lines(&table.render(options.wide))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn delete(
+    settings: &Settings,
+    data: &mut Vec<TrackingEvent>,
+    filter: &FilterData,
+    indices: &[usize],
+    ids: &[Uuid],
+    at: Option<String>,
+    dry_run: bool,
+    yes: bool,
+) -> Result<bool> {
+    let at = at.as_deref().map(parse_date_time).transpose()?;
+    for index in indices {
+        if *index >= data.len() {
+            return Err(anyhow!("There is no entry with number {}!", index));
+        }
+    }
+    for id in ids {
+        if !data.iter().any(|event| event.data().id == *id) {
+            return Err(anyhow!("There is no entry with id {}!", id));
+        }
+    }
+    let entries = if indices.is_empty() && ids.is_empty() {
+        number_events(data, filter_events(data, filter, settings.week_starts_on)?)
+    } else {
+        data.iter()
+            .cloned()
+            .enumerate()
+            .filter(|(i, event)| indices.contains(i) || ids.contains(&event.data().id))
+            .collect()
+    };
+    let to_delete: Vec<_> = entries
+        .into_iter()
+        .filter(|(_, event)| match at {
+            Some(at) => event.time(true).timestamp() == at.timestamp(),
+            None => true,
+        })
+        .collect();
+    // a range deletes whole entries, including the breaks and the stop after the end of the range
+    let to_delete = if indices.is_empty() && at.is_none() {
+        whole_entries(data, &to_delete)
+    } else {
+        to_delete
+    };
+
+    if to_delete.is_empty() {
+        println!("No entries found!");
+        return Ok(false);
+    }
+    println!("The following entries will be deleted:");
+    for (i, event) in &to_delete {
+        println!("({}) {}", i, to_human_readable(event.name(), event.data()));
+    }
+    if dry_run {
+        return Ok(false);
+    }
+    if !yes {
+        println!();
+        println!("Delete {} entries? (y|n) [default: n]: ", to_delete.len());
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !matches!(input.trim(), "y" | "yes") {
+            println!("Nothing deleted.");
+            return Ok(false);
+        }
+    }
 
-    cleaned.iter().map(Clone::clone).cloned().collect()
+    for (_, event) in &to_delete {
+        if let Some(position) = data.iter().position(|e| e.data().id == event.data().id) {
+            data.remove(position);
+        }
+    }
+    Ok(true)
 }
 
-fn status(data: &[TrackingEvent]) {
+fn status(settings: &Settings, data: &[TrackingEvent], goals: bool) -> Result<()> {
     if let Some(event) = data.last() {
         let time = event.time(true).with_timezone(&Local);
         let active = event.is_start();
-        let text = iif!(active, "Start", "End");
+        let text = match event {
+            TrackingEvent::Stop(_) => "End",
+            event => event.name(),
+        };
+        let active_text = iif!(active, paint(active, Style::Active), active.to_string());
         if let Some(description) = event.description() {
-            println!("Active: {}", active);
+            println!("Active: {}", active_text);
             println!("Description: {}", description,);
-            println!(
-                "{} Time: {:02}:{:02}:{:02}",
-                text,
-                time.hour(),
-                time.minute(),
-                time.second()
-            );
+            print_project_and_tags(event.data());
+            println!("{} Time: {}", text, locale::format_time(&time));
         } else {
-            println!("Active: {}", active);
+            println!("Active: {}", active_text);
+            print_project_and_tags(event.data());
+            println!("{} Time: {}", text, locale::format_time(&time));
+        }
+        if let Some(balance) = overtime::balance(settings, data) {
             println!(
-                "{} Time: {:02}:{:02}:{:02}",
-                text,
-                time.hour(),
-                time.minute(),
-                time.second()
+                "Overtime Balance: {}",
+                paint(
+                    timesheet::format_delta(balance),
+                    iif!(balance < Duration::zero(), Style::Error, Style::Active)
+                )
             );
         }
+        if goals {
+            for line in progress::goal_lines(settings, data)? {
+                println!("{}", line);
+            }
+        }
         std::process::exit(iif!(active, 0, -1));
     } else {
         println!("No Events found!");
@@ -784,115 +2704,872 @@ fn status(data: &[TrackingEvent]) {
     }
 }
 
-fn to_human_readable<Tz: TimeZone>(
-    prefix: &str,
-    time: &DateTime<Tz>,
-    description: Option<String>,
-) -> String {
-    let description = description
+fn format_hours_minutes(duration: Duration) -> String {
+    let (hours, minutes, _) = split_duration(duration);
+    format!("{:02}:{:02}", hours, minutes)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionState {
+    Running,
+    Paused,
+    Stopped,
+}
+
+impl SessionState {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Running => "▶",
+            Self::Paused => "⏸",
+            Self::Stopped => "■",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+/// the current session and the work time of today, as shown by `tt prompt` and `tt statusbar`
+struct CurrentSession {
+    state: SessionState,
+    elapsed: Duration,
+    today: Duration,
+    /// the data of the running or paused session
+    data: Option<TrackingData>,
+    /// when the goal of today is reached, if there is one
+    eta: Option<DateTime<Utc>>,
+}
+
+impl CurrentSession {
+    /// only reads the end of the data file, so it's fast enough to run on every prompt
+    fn read(settings: &Settings, path: &str) -> Self {
+        Self::read_with_options(settings, path, CalculationOptions::default())
+    }
+
+    /// like `read`, with the options for calculating the work time of today
+    fn read_with_options(settings: &Settings, path: &str, options: CalculationOptions) -> Self {
+        let today = Local::today().and_hms(0, 0, 0).with_timezone(&Utc);
+        let data = read_tail(path, today).unwrap_or_default();
+        Self::from_events(settings, &data, options)
+    }
+
+    /// the session at the end of `data`, which has to contain at least the events of today
+    fn from_events(
+        settings: &Settings,
+        data: &[TrackingEvent],
+        options: CalculationOptions,
+    ) -> Self {
+        let today = Local::today().and_hms(0, 0, 0).with_timezone(&Utc);
+        let (state, elapsed) = match data.last() {
+            Some(event) if event.is_start() => {
+                (SessionState::Running, Utc::now() - event.time(true))
+            }
+            Some(TrackingEvent::Pause(_)) => (SessionState::Paused, Duration::zero()),
+            _ => (SessionState::Stopped, Duration::zero()),
+        };
+        let current = match data.last() {
+            Some(TrackingEvent::Stop(_)) | None => None,
+            Some(_) => data.iter().rev().find(|e| e.is_start()).map(|e| e.data()),
+        };
+        let todays_events: Vec<_> = data
+            .iter()
+            .filter(|e| e.time(true) >= today)
+            .skip_while(|e| e.is_stop())
+            .cloned()
+            .collect();
+        Self {
+            state,
+            elapsed,
+            today: get_time_from_events(settings, &todays_events, options),
+            data: current.cloned(),
+            eta: eta::from_events(settings, &todays_events, options),
+        }
+    }
+
+    /// replaces the placeholders {state}, {elapsed}, {today}, {eta}, {description}, {project} and
+    /// {tags}
+    fn format(&self, format: &str) -> String {
+        let current = self.data.as_ref();
+        let line = format
+            .replace("{state}", self.state.symbol())
+            .replace("{elapsed}", &format_hours_minutes(self.elapsed))
+            .replace("{today}", &format_hours_minutes(self.today))
+            .replace("{eta}", &self.eta.map(eta::format).unwrap_or_default())
+            .replace(
+                "{description}",
+                current
+                    .and_then(|c| c.description.as_deref())
+                    .unwrap_or_default(),
+            )
+            .replace(
+                "{project}",
+                current
+                    .and_then(|c| c.project.as_deref())
+                    .unwrap_or_default(),
+            )
+            .replace(
+                "{tags}",
+                &current.map(|c| c.tags.join(",")).unwrap_or_default(),
+            );
+        line.trim().to_string()
+    }
+}
+
+fn prompt(
+    settings: &Settings,
+    path: &str,
+    config_file: &Option<String>,
+    format: Option<String>,
+) -> Result<()> {
+    let format = format.unwrap_or_else(|| settings.prompt_format.clone());
+    let request = daemon::Request::Prompt {
+        format: format.clone(),
+    };
+    if let Some(response) = daemon::send(path, config_file, request) {
+        return daemon::print(response?);
+    }
+    let session = CurrentSession::read(settings, path);
+    println!("{}", session.format(&format));
+
+    Ok(())
+}
+
+fn print_project_and_tags(data: &TrackingData) {
+    if let Some(project) = &data.project {
+        println!("Project: {}", project);
+    }
+    if !data.tags.is_empty() {
+        println!("Tags: {}", data.tags.join(", "));
+    }
+}
+
+fn to_human_readable(prefix: &str, data: &TrackingData) -> String {
+    let time = data.local_time();
+    let description = data
+        .description
+        .as_ref()
         .map(|d| format!(" \"{}\"", d))
         .unwrap_or_default();
+    let project = data
+        .project
+        .as_ref()
+        .map(|p| format!(" [{}]", p))
+        .unwrap_or_default();
+    let tags: String = data.tags.iter().map(|t| format!(" #{}", t)).collect();
     format!(
-        "{} at {:04}-{:02}-{:02} {:02}:{:02}:{:02}{}",
+        "{} at {}{}{}{}",
         prefix,
-        time.year(),
-        time.month(),
-        time.day(),
-        time.hour(),
-        time.minute(),
-        time.second(),
+        locale::format_date_time(&time),
         description,
+        project,
+        tags,
     )
 }
 
+/// the notes of the data as lines indented by `indent`. the lines of notes spanning multiple
+/// lines are aligned with the first one
+fn notes_to_human_readable(data: &TrackingData, indent: usize) -> Vec<String> {
+    let offset = *data.local_time().offset();
+    let mut lines = Vec::new();
+    for note in &data.notes {
+        let time = locale::format_time(&note.time.with_timezone(&offset));
+        for (i, line) in note.text.lines().enumerate() {
+            let prefix = iif!(i == 0, format!("- {} ", time), " ".repeat(time.len() + 3));
+            lines.push(format!("{}{}{}", " ".repeat(indent), prefix, line));
+        }
+    }
+    lines
+}
+
 fn get_human_readable(data: &[TrackingEvent]) -> Vec<String> {
-    data.iter()
-        .map(|event| match event {
-            TrackingEvent::Start(TrackingData { time, description }) => {
-                to_human_readable("Start", &time.with_timezone(&Local), description.clone())
-            }
-            TrackingEvent::Stop(TrackingData { time, description }) => {
-                to_human_readable("Stop ", &time.with_timezone(&Local), description.clone())
-            }
-        })
-        .collect::<Vec<_>>()
+    let mut lines = Vec::new();
+    for event in data {
+        lines.push(to_human_readable(
+            &format!("{:<5}", event.name()),
+            event.data(),
+        ));
+        lines.extend(notes_to_human_readable(event.data(), 2));
+    }
+    lines
+}
+
+/// the entries which are exported. without a date range, all entries are exported
+fn export_filter(
+    data: &[TrackingEvent],
+    mut filter: FilterData,
+    week_starts_on: Weekday,
+) -> Result<Vec<TrackingEvent>> {
+    if filter.filter.is_none() && filter.from.is_none() && filter.to.is_none() {
+        // without any filter, the data is exported unchanged, so it can be imported again
+        if filter.project.is_none() && filter.tags.is_empty() && filter.descriptions.is_empty() {
+            return Ok(data.to_vec());
+        }
+        filter.filter = Some("all".to_string());
+    }
+    filter_events(data, &filter, week_starts_on)
+}
+
+/// like `get_human_readable`, grouped by day with the work time of every day and of every week
+fn get_summarized_human_readable(settings: &Settings, data: &[TrackingEvent]) -> Vec<String> {
+    let week_line = |(start, end): (NaiveDate, NaiveDate), total| {
+        format!(
+            "Week {} - {}: {}",
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d"),
+            format_hours_minutes(total)
+        )
+    };
+    let mut lines = Vec::new();
+    let mut week = None;
+    let mut weeks = 0;
+    let mut week_total = Duration::zero();
+    let mut total = Duration::zero();
+    for day in get_data_as_days(data) {
+        let date = day[0].local_date();
+        let current_week = timesheet::week_of(settings, date);
+        if let Some(week) = week.filter(|week| *week != current_week) {
+            lines.push(week_line(week, week_total));
+            lines.push(String::new());
+            week_total = Duration::zero();
+        }
+        if week != Some(current_week) {
+            week = Some(current_week);
+            weeks += 1;
+        }
+
+        let work_time = get_time_from_day(settings, &day, CalculationOptions::default());
+        week_total += work_time;
+        total += work_time;
+        lines.push(date.format("%A, %Y-%m-%d").to_string());
+        lines.extend(
+            get_human_readable(&day)
+                .iter()
+                .map(|line| format!("  {}", line)),
+        );
+        lines.push(format!("  Total: {}", format_hours_minutes(work_time)));
+        lines.push(String::new());
+    }
+    if let Some(week) = week {
+        lines.push(week_line(week, week_total));
+    }
+    if weeks > 1 {
+        lines.push(String::new());
+        lines.push(format!("Total: {}", format_hours_minutes(total)));
+    }
+    lines
+}
+
+fn export_human_readable(
+    settings: &Settings,
+    path: String,
+    data: &[TrackingEvent],
+    summarized: bool,
+) {
+    let lines = if summarized {
+        get_summarized_human_readable(settings, data)
+    } else {
+        get_human_readable(data)
+    };
+    write_output(path, lines.join("\n")).expect("could not export file");
 }
 
-fn export_human_readable(path: String, data: &[TrackingEvent]) {
-    let lines = get_human_readable(data);
-    std::fs::write(path, lines.join("\n")).expect("could not export file");
+/// the global options as arguments for another call of tt, with absolute paths
+fn global_args(
+    data_file: &Option<PathBuf>,
+    config_file: &Option<String>,
+    profile: &Option<String>,
+) -> Vec<String> {
+    let absolute = |path: &str| {
+        std::fs::canonicalize(path)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string())
+    };
+    let mut args = Vec::new();
+    if let Some(data_file) = data_file {
+        args.push("--data-file".to_string());
+        args.push(absolute(&data_file.to_string_lossy()));
+    }
+    if let Some(config_file) = config_file {
+        args.push("--config-file".to_string());
+        args.push(absolute(config_file));
+    }
+    if let Some(profile) = profile {
+        args.push("--profile".to_string());
+        args.push(profile.clone());
+    }
+    args
 }
 
 fn main() -> Result<()> {
-    let Options { command, data_file, config_file } = Options::from_args();
+    let Options {
+        command,
+        data_file,
+        config_file,
+        profile,
+        allow_out_of_order,
+    } = Options::from_args();
+    // an empty TT_DATA_FILE or TT_CONFIG_FILE is the same as none, so wrapper scripts can unset them
+    let data_file = data_file.filter(|path| !path.as_os_str().is_empty());
+    let config_file = config_file.filter(|path| !path.is_empty());
+
+    let mut settings = Settings::new(&config_file)?;
+    color::init(settings.color);
+    precision::init(&settings);
+    clock_jump::init(allow_out_of_order);
+    if settings.timezone != "local" {
+        // chrono takes the local timezone from TZ, so this applies to all times tt reads and shows
+        std::env::set_var("TZ", &settings.timezone);
+    }
+    locale::init(&settings)?;
+    host::init(&settings);
 
-    let settings = Settings::new(&config_file)?;
+    let command = command.unwrap_or_default().expand_shortcut();
+    if let Command::Profile { command } = command {
+        return profile::run(&settings, command);
+    }
+    if let Command::Goal { command } = command {
+        return goal::goal(&settings, command, &config_file);
+    }
+    if let Command::Completions { shell } = command {
+        completions::completions(Options::clap(), shell);
+        return Ok(());
+    }
+    if let Command::Cron { command } = command {
+        // the report is created by another call of tt, which reads the data file on its own
+        let global_args = global_args(&data_file, &config_file, &profile);
+        return cron::cron(&settings, command, &global_args);
+    }
+    // the sleep hook is installed with sudo, so the data file of root must not be touched
+    if let Command::Hook {
+        command: HookCommand::InstallSleep,
+    } = command
+    {
+        return sleep::install(&global_args(&data_file, &config_file, &profile));
+    }
+    if let Command::Hook {
+        command: HookCommand::UninstallSleep,
+    } = command
+    {
+        return sleep::uninstall();
+    }
 
-    let path = match data_file {
+    let path: PathBuf = match data_file {
         Some(path) => path,
-        None => shellexpand::full(&settings.data_file)?.parse()?,
+        None => {
+            if settings.data_file.is_empty() {
+                migration::migrate_default_data_file()?;
+            }
+            let path: PathBuf =
+                shellexpand::full(&profile::data_file(&settings, profile)?)?.parse()?;
+            // the default data file is in a directory of its own, which may not exist yet
+            if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("could not create \"{}\"", dir.display()))?;
+            }
+            path
+        }
     };
     let expanded_path = shellexpand::full(&path.to_string_lossy())
         .expect("could not expand path")
         .to_string();
-    let mut data = read_data(&expanded_path).unwrap_or_default();
+    settings.absences = absence::read(&expanded_path)?;
+    encryption::init(&settings, &expanded_path)?;
+    if let Command::Stop {
+        description,
+        at,
+        all_profiles: true,
+        ..
+    } = command
+    {
+        // every data file gets locked and written on its own
+        return profile::stop_all(&settings, description, at);
+    }
+    if let Command::Status { all: true, .. } = command {
+        return profile::status_all(&settings);
+    }
+    if let Command::Status { watch: true, .. } = command {
+        // reads only the end of the data file every second, like the statusbar
+        return live_status::watch(&settings, &expanded_path);
+    }
+    if let Command::Eta = command {
+        // only reads the end of the data file like the prompt
+        return eta::eta(&settings, &expanded_path);
+    }
+    if let Command::Prompt { format } = command {
+        // only reads the end of the data file, so it's fast enough to run on every prompt
+        return prompt(&settings, &expanded_path, &config_file, format);
+    }
+    if let Command::Statusbar { style, format } = command {
+        // reads only the end of the data file like the prompt, so it can be polled every second
+        return statusbar::statusbar(&settings, &expanded_path, style, format);
+    }
+    if let Command::Watch { idle_minutes } = command {
+        // runs until it gets killed and reads the data file on its own
+        return watch::watch(&settings, &expanded_path, idle_minutes);
+    }
+    if let Command::Nag = command {
+        // only reads the last event, so it's cheap to run from cron
+        return nag::nag(&settings, &expanded_path);
+    }
+    if let Command::Snooze { duration } = command {
+        return break_reminder::snooze(&settings, &expanded_path, duration);
+    }
+    if let Command::Kiosk {
+        description,
+        project,
+        tags,
+    } = command
+    {
+        // runs until it is quit and locks the data file only while writing to it
+        return kiosk::kiosk(&settings, &expanded_path, description, project, tags);
+    }
+    if let Command::Pomodoro { options } = command {
+        // runs for all cycles and locks the data file only while writing to it
+        return pomodoro::pomodoro(&settings, &expanded_path, options);
+    }
+    if let Command::Daemon = command {
+        // runs until it gets killed and locks the data file only while changing it
+        return daemon::run(&settings, &expanded_path);
+    }
+    if let Command::Serve { port } = command {
+        // runs until it gets killed and locks the data file only while changing it
+        return serve::serve(&settings, &expanded_path, port);
+    }
 
-    let data_changed = match command.unwrap_or_default() {
-        Command::Start { description, at } => {
-            start_tracking(&settings, &mut data, description, at)?;
+    // `--allow-out-of-order` only applies to this process, so the command makes the change itself
+    let request =
+        daemon::Request::from_command(&settings, &command).filter(|_| !allow_out_of_order);
+    if let Some(request) = request {
+        // a running daemon owns the data, so it makes the change
+        if let Some(response) = daemon::send(&expanded_path, &config_file, request) {
+            return daemon::print(response?);
+        }
+    }
+
+    // held until the end of main, so nothing else changes the file between reading and writing
+    let _lock = lock(&expanded_path)?;
+    let auto_sync = settings.sync.auto && command.changes_data();
+    if auto_sync {
+        sync::pull(&settings, &expanded_path)?;
+    }
+    let journal_path = journal::journal_path(&expanded_path);
+    // commands which only need a date range don't read all of a large data file
+    let (since, until) = command.read_bounds(&settings)?;
+    let (first, mut data, to_end) = if Path::new(&expanded_path).exists() {
+        read_range(&expanded_path, since, until)?
+    } else {
+        (0, Vec::new(), true)
+    };
+    let mut original_data = data.clone();
+    // only the data up to the end of the file shows whether a session is still running, and
+    // changes can't be saved after events which don't end the file
+    if to_end {
+        correct_data(
+            &settings,
+            &expanded_path,
+            &mut original_data,
+            &mut data,
+            command.session_check(),
+            !command.starts_or_stops(),
+            true,
+        )?;
+    }
+    handle_planned_stop(&command, &mut data);
+    // archived events are never written back, so only commands which don't change the data get them
+    let mut archived = 0;
+    if let Some(bounds) = command.archive_bounds(&settings)? {
+        let count = data.len();
+        data = archive::load(&expanded_path, bounds, data)?;
+        archived = data.len() - count;
+    }
+
+    let record_change = !matches!(command, Command::Undo | Command::Redo);
+    let data_changed = match command {
+        Command::Start {
+            mut description,
+            mut project,
+            mut tags,
+            mut at,
+            duration,
+            billable,
+            force,
+            pick,
+            interactive,
+            ..
+        } => {
+            let mut billable = billable.value();
+            if interactive {
+                let entry = interactive::Entry {
+                    description,
+                    project,
+                    tags,
+                    at,
+                };
+                let entry = interactive::ask_entry(&data, entry)?;
+                description = entry.description;
+                project = entry.project;
+                tags = entry.tags;
+                at = entry.at;
+            }
+            if pick {
+                let task = fuzzy_pick_task(&data)?;
+                description = task.description;
+                project = project.or(task.project);
+                if tags.is_empty() {
+                    tags = task.tags;
+                }
+                billable = billable.or(task.billable);
+            }
+            let duration = duration
+                .map(|duration| {
+                    natural_time::parse_duration(&duration).ok_or_else(|| {
+                        anyhow!(
+                            "invalid duration \"{}\", expected a duration like \"1h\"",
+                            duration
+                        )
+                    })
+                })
+                .transpose()?;
+            let length = data.len();
+            start_tracking(
+                &settings,
+                &mut data,
+                description,
+                project,
+                tags,
+                at,
+                billable,
+                force,
+            )?;
+            if let (Some(duration), true) = (duration, data.len() > length) {
+                add_planned_stop(&mut data, duration);
+            }
+            true
+        }
+        Command::Add {
+            start,
+            end,
+            description,
+            project,
+            tags,
+            billable,
+        } => {
+            add_interval(
+                &mut data,
+                start,
+                end,
+                description,
+                project,
+                tags,
+                billable.value(),
+            )?;
+            true
+        }
+        Command::Stop {
+            description,
+            at,
+            force,
+            ..
+        } => {
+            stop_tracking(&mut data, description, at, force)?;
             true
         }
-        Command::Stop { description, at } => {
-            stop_tracking(&mut data, description, at)?;
+        Command::Pause { description, at } => {
+            pause_tracking(&mut data, description, at)?;
             true
         }
-        Command::Continue => {
-            continue_tracking(&mut data);
+        Command::Resume { at } => {
+            resume_tracking(&mut data, at)?;
             true
         }
-        Command::List { filter } => {
-            let data = filter_events(&data, &filter.from, &filter.to, &filter.filter)?;
-            for s in get_human_readable(&data) {
-                println!("{}", s);
-            }
+        Command::Continue {
+            number,
+            description_contains,
+        } => {
+            continue_tracking(&mut data, number, description_contains)?;
+            true
+        }
+        Command::Annotate { description, index } => {
+            annotate(&mut data, description, index)?;
+            true
+        }
+        Command::Note { text, index } => {
+            add_note(&mut data, text, index)?;
+            true
+        }
+        Command::Eta
+        | Command::Prompt { .. }
+        | Command::Statusbar { .. }
+        | Command::Watch { .. }
+        | Command::Nag
+        | Command::Snooze { .. }
+        | Command::Pomodoro { .. }
+        | Command::Kiosk { .. }
+        | Command::Daemon
+        | Command::Serve { .. }
+        | Command::Profile { .. }
+        | Command::Goal { .. }
+        | Command::Completions { .. } => {
+            unreachable!("handled before reading the data file")
+        }
+        Command::Vacation { command } => {
+            vacation::vacation(&expanded_path, command)?;
+            false
+        }
+        Command::Absence { command } => {
+            absence::absence(&expanded_path, command)?;
+            false
+        }
+        Command::Sync { remote } => {
+            sync::sync(&settings, &expanded_path, remote.as_deref())?;
+            false
+        }
+        Command::Backup { command } => {
+            backup::backup(&expanded_path, settings.backup.keep, command)?;
+            false
+        }
+        Command::Restore { index } => {
+            data = backup::restore(&expanded_path, index)?;
+            println!("Restored backup ({}).", index);
+            true
+        }
+        Command::Hook { command } => hook::hook(&settings, &mut data, command, &expanded_path)?,
+        Command::Undo => {
+            journal::undo(&journal_path, &mut data)?;
+            true
+        }
+        Command::Redo => {
+            journal::redo(&journal_path, &mut data)?;
+            true
+        }
+        Command::List {
+            filter,
+            last,
+            reverse,
+            verbose,
+            ids,
+            wide,
+            json,
+        } => {
+            let options = ListOptions {
+                last,
+                reverse,
+                verbose,
+                ids,
+                wide,
+                json,
+            };
+            list(&settings, &data, archived, first, &filter, options)?;
             false
         }
         Command::Path => {
             println!("{}", expanded_path);
             false
         }
+        Command::Complete { values } => {
+            for value in completions::values(&settings, &data, values) {
+                println!("{}", value);
+            }
+            false
+        }
+        Command::Log { filter, wide } => {
+            interval_log::log(&settings, &data, &filter, wide)?;
+            false
+        }
+        Command::Search {
+            pattern,
+            ignore_case,
+            regex,
+        } => {
+            search::search(&data, &search::Pattern::new(&pattern, ignore_case, regex)?)?;
+            false
+        }
+        Command::Recent { number, projects } => {
+            for value in completions::recent(&data, number, projects) {
+                println!("{}", value);
+            }
+            false
+        }
         Command::Show {
             format,
             filter,
-            include_seconds,
+            options,
             plain,
             remaining,
+            breakdown,
+            daily_window,
+            group_by,
+            per_project,
+            ..
         } => {
+            let settings = settings.for_project(&filter.project);
+            let clipped;
+            let data = match daily_window {
+                Some(window) => {
+                    clipped = window.clip(&data);
+                    &clipped
+                }
+                None => &data,
+            };
+            if breakdown {
+                report::breakdown(&settings, data, &filter, options)?;
+            }
+            let group_by = iif!(per_project, Some(SummaryKey::Project), group_by);
             show(
-                &settings,
-                &data,
-                &filter,
-                format,
-                include_seconds,
-                plain,
-                remaining,
+                &settings, data, &filter, format, options, plain, remaining, group_by,
             )?;
             false
         }
-        Command::Status => {
-            status(&data);
+        Command::Status { goals, .. } => {
+            status(&settings, &data, goals || settings.status_goals)?;
             false
         }
-        Command::Cleanup => {
-            data = cleanup(&data);
-            true
+        Command::Report {
+            filter,
+            group_by,
+            template,
+            options,
+            daily_window,
+            wide,
+        } => {
+            let settings = settings.for_project(&filter.project);
+            let clipped;
+            let data = match daily_window {
+                Some(window) => {
+                    clipped = window.clip(&data);
+                    &clipped
+                }
+                None => &data,
+            };
+            match template {
+                Some(template) => template::report(&settings, data, &filter, &template, options)?,
+                None => report::report(&settings, data, &filter, group_by, options, wide)?,
+            }
+            false
+        }
+        Command::Overtime { filter, options } => {
+            let settings = settings.for_project(&filter.project);
+            overtime::overtime(&settings, &data, &filter, options)?;
+            false
+        }
+        Command::Summary {
+            filter,
+            by,
+            options,
+            wide,
+        } => {
+            let settings = settings.for_project(&filter.project);
+            report::summary(&settings, &data, &filter, by, options, wide)?;
+            false
+        }
+        #[cfg(feature = "push")]
+        Command::Push { service, dry_run } => {
+            push::push(&settings, &data, &expanded_path, service, dry_run)?;
+            false
+        }
+        #[cfg(feature = "chart")]
+        Command::Chart {
+            output,
+            range,
+            project,
+            options,
+        } => {
+            let output = shellexpand::full(&output.to_string_lossy())
+                .expect("could not expand path")
+                .to_string();
+            let settings = settings.for_project(&project);
+            let filter = chart::filter(&range, &project);
+            chart::chart(&settings, &data, &filter, Path::new(&output), options)?;
+            false
+        }
+        Command::Earnings { filter, options } => {
+            earnings::earnings(&settings, &data, &filter, options)?;
+            false
+        }
+        Command::Stats {
+            mut filter,
+            weeks,
+            options,
+        } => {
+            if filter.filter.is_none() && filter.from.is_none() && filter.to.is_none() {
+                filter.filter = Some("all".to_string());
+            }
+            let settings = settings.for_project(&filter.project);
+            stats::stats(&settings, &data, &filter, weeks, options)?;
+            false
+        }
+        Command::Calendar {
+            period,
+            date,
+            options,
+        } => {
+            calendar::calendar(&settings, &data, period, &date, options)?;
+            false
+        }
+        Command::Delete {
+            filter,
+            indices,
+            ids,
+            at,
+            dry_run,
+            yes,
+        } => delete(
+            &settings, &mut data, &filter, &indices, &ids, at, dry_run, yes,
+        )?,
+        Command::Check { max_hours } => {
+            check::check(&settings, &data, max_hours)?;
+            false
+        }
+        Command::Fix => match fix::fix(&data)? {
+            Some(fixed) => {
+                data = fixed;
+                true
+            }
+            None => false,
+        },
+        Command::Archive { before } => {
+            let before = match parse_date_or_date_time(&before)? {
+                DateOrDateTime::Date(date) => date,
+                DateOrDateTime::DateTime(date_time) => date_time.date(),
+            };
+            archive::archive(&mut data, &expanded_path, before)?
+        }
+        Command::Cleanup { strategy, dry_run } => {
+            let cleaned = cleanup(&data, strategy);
+            if dry_run {
+                false
+            } else {
+                data = cleaned;
+                true
+            }
         }
         #[cfg(not(feature = "binary"))]
-        Command::Export { path } => {
+        Command::Export {
+            path,
+            format,
+            week,
+            anonymize,
+            summarized,
+            filter,
+        } => {
             let expanded_path = shellexpand::full(&path.to_string_lossy())
                 .expect("could not expand path")
                 .to_string();
-            export_human_readable(expanded_path, &data);
+            let mut data = export_filter(&data, filter, settings.week_starts_on)?;
+            if anonymize {
+                data = anonymize::anonymize(&data);
+            }
+            match format {
+                Some(format) => {
+                    exporter::find(&format)?.export(&settings, &data, week, &expanded_path)?
+                }
+                None => export_human_readable(&settings, expanded_path, &data, summarized),
+            }
             false
         }
 
@@ -901,43 +3578,177 @@ fn main() -> Result<()> {
             path,
             readable,
             pretty,
+            format,
+            week,
+            anonymize,
+            summarized,
+            filter,
         } => {
             let expanded_path = shellexpand::full(&path.to_string_lossy())
                 .expect("could not expand path")
                 .to_string();
-            if readable {
-                export_human_readable(expanded_path, &data);
+            let mut data = export_filter(&data, filter, settings.week_starts_on)?;
+            if anonymize {
+                data = anonymize::anonymize(&data);
+            }
+            if let Some(format) = format {
+                exporter::find(&format)?.export(&settings, &data, week, &expanded_path)?;
+            } else if readable {
+                export_human_readable(&settings, expanded_path, &data, summarized);
             } else {
                 write_json_data(expanded_path, &data, pretty).expect("Could not write file");
             }
             false
         }
-        #[cfg(feature = "binary")]
-        Command::Import { path } => {
-            data = read_json_data(path)?;
+        Command::Import { format, path } => {
+            match import::import(path, format)? {
+                (ImportFormat::Json, events) => data = events,
+                (ImportFormat::Toggl, events)
+                | (ImportFormat::Timewarrior, events)
+                | (ImportFormat::Watson, events) => data.extend(events),
+            }
             true
         }
+        Command::Diff { path, backup } => {
+            let path = match path {
+                Some(path) => {
+                    PathBuf::from(shellexpand::full(&path.to_string_lossy())?.to_string())
+                }
+                None => backup::backup_path(&expanded_path, backup.unwrap_or(0))?,
+            };
+            diff::diff(&data, &path)?;
+            false
+        }
+        Command::Merge {
+            path,
+            strategy,
+            dry_run,
+        } => {
+            let path = shellexpand::full(&path.to_string_lossy())?.to_string();
+            let merged = merge::merge(&data, path.as_ref(), strategy)?;
+            if dry_run {
+                false
+            } else {
+                data = merged;
+                true
+            }
+        }
         #[allow(unreachable_patterns)]
         _ => unimplemented!(),
     };
 
     if data_changed {
-        data.sort_by_key(|e| e.time(true));
-        data.dedup();
-        write_data(expanded_path, &data).expect("Could not write file!");
+        write_changes(
+            &settings,
+            &expanded_path,
+            &original_data,
+            &mut data,
+            record_change,
+        )?;
+        if auto_sync {
+            sync::push(&settings, &expanded_path)?;
+        }
     }
 
     Ok(())
 }
 
+fn write_changes(
+    settings: &Settings,
+    path: &str,
+    original_data: &[TrackingEvent],
+    data: &mut Vec<TrackingEvent>,
+    record_change: bool,
+) -> Result<()> {
+    clock_jump::resolve(settings, original_data, data)?;
+    data.sort_by_key(|e| e.time(true));
+    data.dedup();
+    strict::check_change(settings, original_data, data)?;
+    save_changes(settings, path, original_data, data, record_change)
+}
+
+/// writes a change which tt made on its own, like stopping a forgotten session, which strict mode
+/// allows even if it's further in the past. it still gets logged.
+fn write_correction(
+    settings: &Settings,
+    path: &str,
+    original_data: &[TrackingEvent],
+    data: &mut Vec<TrackingEvent>,
+) -> Result<()> {
+    data.sort_by_key(|e| e.time(true));
+    data.dedup();
+    save_changes(settings, path, original_data, data, true)
+}
+
+/// the corrections every command makes before it uses the data: stopping a session at the
+/// automatic stop, offering to stop a long session (with the strict flag of `session_check`) and
+/// to continue the tracking which was stopped when the computer went to sleep. without
+/// `interactive`, like in the daemon, nothing is asked. every correction is saved right away and
+/// becomes the `original_data` of the command.
+fn correct_data(
+    settings: &Settings,
+    path: &str,
+    original_data: &mut Vec<TrackingEvent>,
+    data: &mut Vec<TrackingEvent>,
+    session_check: Option<bool>,
+    ask_after_wake: bool,
+    interactive: bool,
+) -> Result<()> {
+    if auto_stop::check(settings, data)? {
+        write_correction(settings, path, original_data, data)?;
+        *original_data = data.clone();
+    }
+    if let Some(strict) = session_check {
+        if long_session::check(settings, data, strict, interactive)? {
+            write_correction(settings, path, original_data, data)?;
+            *original_data = data.clone();
+        }
+    }
+    if sleep::check_wake(data, path, ask_after_wake && interactive)? {
+        write_correction(settings, path, original_data, data)?;
+        *original_data = data.clone();
+    }
+    Ok(())
+}
+
+fn save_changes(
+    settings: &Settings,
+    path: &str,
+    original_data: &[TrackingEvent],
+    data: &[TrackingEvent],
+    record_change: bool,
+) -> Result<()> {
+    if settings.backup.keep > 0 && original_data != data {
+        backup::create(path, settings.backup.keep)?;
+    }
+    save_data(path, original_data, data).expect("Could not write file!");
+    strict::record(settings, path, original_data, data)?;
+    #[cfg(feature = "rest-sync")]
+    rest_sync::record(path, original_data, data)?;
+    if record_change {
+        journal::record(journal::journal_path(path), original_data, data)?;
+    }
+    event_hooks::run(settings, original_data, data);
+    event_hooks::check_goal(settings, path)
+}
+
 fn parse_date_time(s: &str) -> Result<DateTime<Utc>> {
-    let from_time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M:%S");
+    if let Some(date_time) = natural_time::parse(s, Local::now().naive_local()) {
+        let date_time = match date_time {
+            DateOrDateTime::Date(date) => date.and_hms(0, 0, 0),
+            DateOrDateTime::DateTime(date_time) => date_time,
+        };
+        return Local
+            .from_local_datetime(&date_time)
+            .single()
+            .map(|date_time| date_time.with_timezone(&Utc))
+            .context("invalid time");
+    }
+
     let from_date_time = |s: &str| Local.datetime_from_str(s, "%Y-%m-%d %H:%M:%S");
 
-    from_time(s)
-        .or_else(|_| from_time(&format!("{}:0", s)))
-        .or_else(|_| from_time(&format!("{}:0:0", s)))
-        .map_err(Into::into)
+    natural_time::parse_time(s)
+        .context("invalid time")
         .and_then(|time| Local::today().and_time(time).context("invalid time"))
         .or_else(|_| {
             from_date_time(s)
@@ -949,6 +3760,9 @@ fn parse_date_time(s: &str) -> Result<DateTime<Utc>> {
 }
 
 fn parse_date_or_date_time(s: &str) -> Result<DateOrDateTime> {
+    if let Some(date_or_date_time) = natural_time::parse(s, Local::now().naive_local()) {
+        return Ok(date_or_date_time);
+    }
     if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
         return Ok(date.into());
     }
@@ -1007,4 +3821,370 @@ mod tests {
             parse_date_or_date_time("2020-04-01 12").unwrap()
         );
     }
+
+    #[test]
+    fn test_keyword_range() {
+        let today = NaiveDate::from_ymd(2021, 3, 4);
+        let range = |keyword| keyword_range(keyword, today, Weekday::Mon);
+        let date = |m, d| NaiveDate::from_ymd(2021, m, d);
+        assert_eq!(Some((today, today)), range("today"));
+        assert_eq!(Some((date(3, 3), date(3, 3))), range("yesterday"));
+        assert_eq!(Some((date(3, 1), date(3, 7))), range("week"));
+        assert_eq!(Some((date(2, 22), date(2, 28))), range("last-week"));
+        assert_eq!(Some((date(3, 1), date(3, 31))), range("month"));
+        assert_eq!(Some((date(2, 1), date(2, 28))), range("last-month"));
+        assert_eq!(Some((date(1, 1), date(12, 31))), range("year"));
+        assert_eq!(
+            Some((date(2, 28), date(3, 6))),
+            keyword_range("week", today, Weekday::Sun)
+        );
+        assert_eq!(
+            Some((
+                NaiveDate::from_ymd(2020, 12, 1),
+                NaiveDate::from_ymd(2020, 12, 31)
+            )),
+            keyword_range("last-month", NaiveDate::from_ymd(2021, 1, 15), Weekday::Mon)
+        );
+        assert_eq!(None, range("all"));
+        assert_eq!(None, range("meeting"));
+    }
+
+    #[test]
+    fn test_add_note() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let mut data = vec![
+            TrackingEvent::Start(TrackingData::new(Some("a".to_string()), at(8))),
+            TrackingEvent::Stop(TrackingData::new(None, at(9))),
+            TrackingEvent::Start(TrackingData::new(Some("b".to_string()), at(10))),
+        ];
+        add_note(&mut data, Some("fixed the login\n".to_string()), None).unwrap();
+        add_note(&mut data, Some("reviewed".to_string()), Some(1)).unwrap();
+        assert!(add_note(&mut data, Some(" ".to_string()), None).is_err());
+        assert!(add_note(&mut data, Some("x".to_string()), Some(2)).is_err());
+
+        let texts = |event: &TrackingEvent| -> Vec<String> {
+            event.data().notes.iter().map(|n| n.text.clone()).collect()
+        };
+        assert_eq!(vec!["reviewed"], texts(&data[0]));
+        assert_eq!(vec!["fixed the login"], texts(&data[2]));
+        // continuing the entry starts a new interval without the notes
+        assert!(data[2].data().clone().with_time(at(11)).notes.is_empty());
+    }
+
+    #[test]
+    fn test_planned_stop() {
+        let start = Utc::now();
+        let mut data = vec![TrackingEvent::Start(TrackingData::new(None, start))];
+        add_planned_stop(&mut data, Duration::hours(1));
+        assert_eq!(Some(start + Duration::hours(1)), planned_stop(&data));
+
+        let stop = Command::Stop {
+            description: None,
+            at: None,
+            all_profiles: false,
+            force: false,
+        };
+        handle_planned_stop(&stop, &mut data);
+        assert_eq!(1, data.len());
+        assert_eq!(None, planned_stop(&data));
+    }
+
+    #[test]
+    fn test_insert_at() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let start = |h| TrackingEvent::Start(TrackingData::new(None, at(h)));
+        let stop = |h| TrackingEvent::Stop(TrackingData::new(None, at(h)));
+        let mut data = vec![start(8), stop(12)];
+
+        // a start within an entry splits it
+        assert!(insert_at(&mut data, start(10), false).is_err());
+        insert_at(&mut data, start(10), true).unwrap();
+        assert_eq!(vec![start(8), stop(10), start(10), stop(12)], data);
+
+        // a stop within an entry which already stops moves its end
+        assert!(insert_at(&mut data, stop(11), false).is_err());
+        insert_at(&mut data, stop(11), true).unwrap();
+        assert_eq!(vec![start(8), stop(10), start(10), stop(11)], data);
+
+        // a stop after the last entry extends it
+        assert!(insert_at(&mut data, stop(13), false).is_err());
+        insert_at(&mut data, stop(13), true).unwrap();
+        assert_eq!(vec![start(8), stop(10), start(10), stop(13)], data);
+
+        // a start before an entry would run into it, so it ends where the entry starts
+        assert!(insert_at(&mut data, start(6), false).is_err());
+        insert_at(&mut data, start(6), true).unwrap();
+        assert_eq!(
+            vec![start(6), stop(8), start(8), stop(10), start(10), stop(13)],
+            data
+        );
+        insert_at(&mut data, stop(7), true).unwrap();
+        assert_eq!(
+            vec![start(6), stop(7), start(8), stop(10), start(10), stop(13)],
+            data
+        );
+
+        // a start after the last entry is appended
+        insert_at(&mut data, start(14), false).unwrap();
+        assert_eq!(Some(&start(14)), data.last());
+    }
+
+    #[test]
+    fn test_pickable_tasks() {
+        let start = |h, description: &str, project: Option<&str>| {
+            TrackingEvent::Start(TrackingData {
+                project: project.map(str::to_string),
+                ..TrackingData::new(
+                    Some(description.to_string()),
+                    Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+                )
+            })
+        };
+        let data = vec![
+            start(8, "fix bug", Some("website")),
+            start(9, "review", None),
+            start(10, "fix bug", Some("app")),
+            start(11, "fix bug", Some("website")),
+        ];
+        let labels: Vec<_> = pickable_tasks(&data).into_iter().map(task_label).collect();
+        assert_eq!(vec!["fix bug [website]", "fix bug [app]", "review"], labels);
+    }
+
+    #[test]
+    fn test_list_json() {
+        let event = TrackingEvent::Stop(TrackingData {
+            tags: vec!["urgent".to_string()],
+            ..TrackingData::new(
+                Some("fix bug".to_string()),
+                Utc.ymd(2021, 4, 1).and_hms(17, 30, 0),
+            )
+        });
+        let id = event.data().id;
+        assert_eq!(
+            format!(
+                r#"{{"description":"fix bug","id":"{}","index":3,"project":null,"tags":["urgent"],"time":"2021-04-01T17:30:00Z","type":"stop"}}"#,
+                id
+            ),
+            list_json(Some(3), &event).to_string()
+        );
+    }
+
+    #[test]
+    fn test_filter_events_by_project() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let start = |h, project: &str| {
+            TrackingEvent::Start(TrackingData {
+                project: Some(project.to_string()),
+                ..TrackingData::new(None, at(h))
+            })
+        };
+        let stop = |h| TrackingEvent::Stop(TrackingData::new(None, at(h)));
+        let data = vec![
+            start(8, "a"),
+            stop(9),
+            start(10, "b"),
+            stop(11),
+            start(12, "a"),
+            stop(13),
+        ];
+        let filter = FilterData {
+            filter: Some("all".to_string()),
+            project: Some("a".to_string()),
+            ..FilterData::default()
+        };
+        assert_eq!(
+            vec![start(8, "a"), stop(9), start(12, "a"), stop(13)],
+            filter_events(&data, &filter, Weekday::Mon).unwrap()
+        );
+
+        let filtered_data = filter_events(&data, &filter, Weekday::Mon).unwrap();
+        let numbers: Vec<_> = number_events(&data, filtered_data)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(vec![0, 1, 4, 5], numbers);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let data = vec![
+            TrackingEvent::Start(TrackingData::new(None, at(8))),
+            TrackingEvent::Pause(TrackingData::new(None, at(12))),
+            TrackingEvent::Resume(TrackingData::new(None, at(13))),
+            TrackingEvent::Stop(TrackingData::new(None, at(17))),
+        ];
+        let options = CalculationOptions {
+            include_seconds: true,
+            no_round: true,
+            ..CalculationOptions::default()
+        };
+        let summary = summarize_day(&Settings::from_default_config(), &data, options);
+        assert_eq!(Duration::hours(8), summary.work_time);
+        assert_eq!(Duration::hours(1), summary.break_time());
+
+        let mut paused = data[..2].to_vec();
+        stop_tracking(&mut paused, None, None, false).unwrap();
+        assert_eq!(
+            vec![
+                TrackingEvent::Start(TrackingData::new(None, at(8))),
+                TrackingEvent::Stop(TrackingData::new(None, at(12))),
+            ],
+            paused
+        );
+    }
+
+    #[test]
+    fn test_annotate_after_resume() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let event = |h| TrackingData::new(Some("a".to_string()), at(h));
+        let mut data = vec![
+            TrackingEvent::Start(event(8)),
+            TrackingEvent::Stop(event(9)),
+            TrackingEvent::Start(event(10)),
+            TrackingEvent::Pause(TrackingData::new(None, at(11))),
+            TrackingEvent::Resume(event(12)),
+        ];
+        annotate(&mut data, "b".to_string(), None).unwrap();
+        let descriptions: Vec<_> = data
+            .iter()
+            .map(|t| t.data().description.as_deref())
+            .collect();
+        assert_eq!(
+            vec![Some("a"), Some("a"), Some("b"), None, Some("b")],
+            descriptions
+        );
+
+        annotate(&mut data, "c".to_string(), Some(1)).unwrap();
+        assert_eq!(Some("c".to_string()), data[0].description());
+        assert_eq!(Some("b".to_string()), data[2].description());
+        assert!(annotate(&mut data, "d".to_string(), Some(2)).is_err());
+    }
+
+    #[test]
+    fn test_delete_range_deletes_whole_entries() {
+        let at = |h| Local.ymd(2021, 4, 1).and_hms(h, 0, 0).with_timezone(&Utc);
+        let mut data = vec![
+            TrackingEvent::Start(TrackingData::new(None, at(8))),
+            TrackingEvent::Pause(TrackingData::new(None, at(9))),
+            TrackingEvent::Resume(TrackingData::new(None, at(10))),
+            TrackingEvent::Stop(TrackingData::new(None, at(11))),
+            TrackingEvent::Start(TrackingData::new(None, at(12))),
+            TrackingEvent::Stop(TrackingData::new(None, at(13))),
+        ];
+        let remaining = data[4..].to_vec();
+        let filter = FilterData {
+            from: Some("2021-04-01 08:00:00".to_string()),
+            to: Some("2021-04-01 10:30:00".to_string()),
+            ..FilterData::default()
+        };
+        let settings = Settings::from_default_config();
+        assert!(delete(&settings, &mut data, &filter, &[], &[], None, false, true).unwrap());
+        assert_eq!(remaining, data);
+    }
+
+    #[test]
+    fn test_export_filter() {
+        let at = |day, h| Utc.ymd(2021, 4, day).and_hms(h, 0, 0);
+        let data = vec![
+            TrackingEvent::Start(TrackingData::new(None, at(1, 12))),
+            TrackingEvent::Stop(TrackingData::new(None, at(1, 13))),
+            TrackingEvent::Start(TrackingData::new(None, at(2, 12))),
+            TrackingEvent::Stop(TrackingData::new(None, at(2, 13))),
+        ];
+        let export = |from: Option<&str>| {
+            let filter = FilterData {
+                from: from.map(String::from),
+                to: from.map(String::from),
+                ..FilterData::default()
+            };
+            export_filter(&data, filter, Weekday::Mon).unwrap()
+        };
+        assert_eq!(data, export(None));
+        assert_eq!(data[2..], export(Some("2021-04-02"))[..]);
+
+        // filtering skips a stop at the beginning, exporting everything keeps it
+        let data = data[1..].to_vec();
+        assert_eq!(
+            data,
+            export_filter(&data, FilterData::default(), Weekday::Mon).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_filter_events_by_descriptions() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let start = |h, description: &str| {
+            TrackingEvent::Start(TrackingData::new(Some(description.to_string()), at(h)))
+        };
+        let stop = |h| TrackingEvent::Stop(TrackingData::new(None, at(h)));
+        let data = vec![
+            start(8, "ABC-1 login"),
+            stop(9),
+            start(10, "ABC-2 logout"),
+            stop(11),
+            start(12, "ABC-3 login"),
+            stop(13),
+        ];
+        let mut filter = FilterData {
+            filter: Some("all".to_string()),
+            descriptions: vec!["ABC-1".to_string(), "ABC-2".to_string()],
+            ..FilterData::default()
+        };
+        assert_eq!(
+            &data[..4],
+            &filter_events(&data, &filter, Weekday::Mon).unwrap()[..]
+        );
+
+        filter.descriptions = vec!["login".to_string(), "ABC-3".to_string()];
+        filter.match_all = true;
+        assert_eq!(
+            &data[4..],
+            &filter_events(&data, &filter, Weekday::Mon).unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_get_data_as_days_uses_recorded_offset() {
+        let at = |h, offset| {
+            TrackingEvent::Start(TrackingData {
+                offset: Some(offset * 3600),
+                ..TrackingData::new(None, Utc.ymd(2021, 4, 1).and_hms(h, 0, 0))
+            })
+        };
+        // 22:00 and 23:00 UTC are already the next day in UTC+2, but not in UTC-5
+        let data = vec![at(20, 2), at(22, 2), at(23, -5)];
+        let days = get_data_as_days(&data);
+        assert_eq!(
+            vec![vec![at(20, 2)], vec![at(22, 2)], vec![at(23, -5)]],
+            days
+        );
+        assert_eq!(NaiveDate::from_ymd(2021, 4, 2), data[1].local_date());
+        assert_eq!(NaiveDate::from_ymd(2021, 4, 1), data[2].local_date());
+    }
+
+    #[test]
+    fn test_event_ids() {
+        // a start and a stop with the same data only differ in their position
+        let json = br#"[
+            {"Start":{"description":"work","time":1617264000}},
+            {"Stop":{"description":"work","time":1617264000}}
+        ]"#;
+        let events = storage::parse_json_data(json).unwrap();
+        let again = storage::parse_json_data(json).unwrap();
+        assert!(!events[0].data().id.is_nil());
+        assert_ne!(events[0].data().id, events[1].data().id);
+        assert_eq!(events[0].data().id, again[0].data().id);
+        assert_eq!(events[1].data().id, again[1].data().id);
+
+        let written = serde_json::to_string(&events[0]).unwrap();
+        let read: TrackingEvent = serde_json::from_str(&written).unwrap();
+        assert_eq!(events[0].data().id, read.data().id);
+
+        let time = Utc.ymd(2021, 4, 1).and_hms(8, 0, 0);
+        let (first, second) = (TrackingData::new(None, time), TrackingData::new(None, time));
+        assert_ne!(first.id, second.id);
+        assert_eq!(first, second);
+        assert_ne!(first.id, first.clone().with_time(time).id);
+    }
 }
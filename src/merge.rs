@@ -0,0 +1,95 @@
+//! Combining the data of another data file, e.g. from a second machine which tracked time before
+//! sync was set up.
+
+use anyhow::{Context, Result};
+
+use std::path::Path;
+
+use crate::cleanup::{cleanup, has_conflicts, CleanupStrategy};
+use crate::storage::{parse_json_data, read_data, read_input};
+use crate::TrackingEvent;
+
+/// reads a data file in any format tt ever wrote, or a json file written by `tt export`
+pub fn read_other(path: &Path) -> Result<Vec<TrackingEvent>> {
+    let data = read_input(path)?;
+    let looks_like_json = matches!(
+        data.iter().find(|b| !b.is_ascii_whitespace()),
+        Some(b'[') | Some(b'{')
+    );
+    // old binary files can start with the same byte, so they get another try below
+    if looks_like_json {
+        if let Ok(events) = parse_json_data(&data) {
+            return Ok(events);
+        }
+    }
+    read_data(path).with_context(|| format!("could not read \"{}\"", path.display()))
+}
+
+/// the union of both event lists without duplicates, sorted by time
+fn union(data: &[TrackingEvent], other: &[TrackingEvent]) -> Vec<TrackingEvent> {
+    let mut merged: Vec<_> = data
+        .iter()
+        .chain(other.iter().filter(|event| !data.contains(event)))
+        .cloned()
+        .collect();
+    merged.sort_by_key(|e| e.time(true));
+    merged.dedup();
+    merged
+}
+
+/// adds the events of the file at `path` to the data and resolves the events which now conflict
+/// with `strategy` or by asking
+pub fn merge(
+    data: &[TrackingEvent],
+    path: &Path,
+    strategy: Option<CleanupStrategy>,
+) -> Result<Vec<TrackingEvent>> {
+    let other = read_other(path)?;
+    let mut merged = union(data, &other);
+    println!(
+        "Adding {} of the {} events in \"{}\".",
+        merged.len().saturating_sub(data.len()),
+        other.len(),
+        path.display()
+    );
+    if has_conflicts(&merged) {
+        println!("The merged events conflict, please clean them up.");
+        merged = cleanup(&merged, strategy);
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    fn event(start: bool, h: u32) -> TrackingEvent {
+        let data = TrackingData::new(None, Utc.ymd(2021, 4, 1).and_hms(h, 0, 0));
+        if start {
+            TrackingEvent::Start(data)
+        } else {
+            TrackingEvent::Stop(data)
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        let laptop = vec![event(true, 8), event(false, 10), event(true, 14)];
+        let desktop = vec![event(true, 11), event(false, 12), event(true, 14)];
+        let merged = union(&laptop, &desktop);
+        assert_eq!(
+            vec![
+                event(true, 8),
+                event(false, 10),
+                event(true, 11),
+                event(false, 12),
+                event(true, 14),
+            ],
+            merged
+        );
+        assert!(!has_conflicts(&merged));
+        assert!(has_conflicts(&union(&laptop, &[event(true, 9)])));
+    }
+}
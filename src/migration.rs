@@ -0,0 +1,132 @@
+//! Moves the data file of older versions from the home directory into the data directory of the
+//! platform (`$XDG_DATA_HOME/timetracking` on linux), together with the files kept next to it, like
+//! the journal, the backups and the archives. It's only done when the `data_file` setting is empty.
+
+use anyhow::{Context, Result};
+
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::settings::{old_default_data_file, DATA_FILE_NAME};
+
+/// the files next to the data file, like "<data_file>.journal" and the archives. the socket of a
+/// running daemon stays where it is
+fn companions(data_path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = data_path.parent().unwrap_or_else(|| Path::new("."));
+    let prefix = format!("{}.", data_path.to_string_lossy());
+    let mut companions: Vec<_> = archive::archives(data_path)?
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.to_string_lossy();
+        if name.starts_with(&prefix) && !name.ends_with(".sock") {
+            companions.push(path);
+        }
+    }
+    Ok(companions)
+}
+
+/// moves the data file and its companions from `old` to `new`, unless `old` doesn't exist or
+/// `new` already exists. returns whether it was moved
+fn move_data_file(old: &Path, new: &Path) -> Result<bool> {
+    if !old.exists() || new.exists() {
+        return Ok(false);
+    }
+    if let Some(dir) = new.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create \"{}\"", dir.display()))?;
+    }
+    let companions = companions(old)?;
+    // the data file first, so nothing is moved if the data directory is on another file system
+    std::fs::rename(old, new)?;
+    let dir = new.parent().unwrap_or_else(|| Path::new("."));
+    let old_name = old.to_string_lossy().to_string();
+    let new_name = new.to_string_lossy().to_string();
+    for path in companions {
+        let target = match path.to_string_lossy().strip_prefix(&old_name) {
+            // "<data_file>.journal"
+            Some(suffix) => PathBuf::from(format!("{}{}", new_name, suffix)),
+            // the archives keep their names, because the data file has the same name
+            None => dir.join(path.file_name().unwrap_or_default()),
+        };
+        std::fs::rename(&path, &target).with_context(|| {
+            format!(
+                "could not move \"{}\" to \"{}\"",
+                path.display(),
+                target.display()
+            )
+        })?;
+    }
+    Ok(true)
+}
+
+/// moves the data file of older versions into the data directory, if it wasn't moved yet
+pub fn migrate_default_data_file() -> Result<()> {
+    let new = match dirs::data_dir() {
+        Some(dir) => dir.join("timetracking").join(DATA_FILE_NAME),
+        None => return Ok(()),
+    };
+    let old = PathBuf::from(old_default_data_file());
+    match move_data_file(&old, &new) {
+        Ok(true) => eprintln!(
+            "Moved the data file from \"{}\" to \"{}\". Set data_file in the config to use another \
+             location.",
+            old.display(),
+            new.display()
+        ),
+        Ok(false) => {}
+        // the old file is used as before
+        Err(e) => eprintln!(
+            "Warning: could not move the data file \"{}\" to \"{}\": {}",
+            old.display(),
+            new.display(),
+            e
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_data_file() {
+        let dir = std::env::temp_dir().join(format!("tt-test-migration-{}", std::process::id()));
+        let home = dir.join("home");
+        let old = home.join(DATA_FILE_NAME);
+        let new = dir.join("data").join("timetracking").join(DATA_FILE_NAME);
+        let next_to = |suffix: &str| format!("{}{}", DATA_FILE_NAME, suffix);
+        let extension = old.extension().unwrap().to_string_lossy().to_string();
+        let archive = format!("timetracking-2022.{}", extension);
+        let backup = next_to(".backups/20240304-080000.000-timetracking");
+
+        std::fs::create_dir_all(home.join(next_to(".backups"))).unwrap();
+        for name in &[
+            DATA_FILE_NAME.to_string(),
+            next_to(".journal"),
+            backup.clone(),
+            archive.clone(),
+            next_to(".sock"),
+            "notes.txt".to_string(),
+        ] {
+            std::fs::write(home.join(name), name).unwrap();
+        }
+
+        assert!(move_data_file(&old, &new).unwrap());
+        let moved = |name: &str| new.parent().unwrap().join(name).exists();
+        assert!(moved(DATA_FILE_NAME));
+        assert!(moved(&next_to(".journal")));
+        assert!(moved(&backup));
+        assert!(moved(&archive));
+        assert!(!moved(&next_to(".sock")));
+        assert!(home.join("notes.txt").exists());
+        assert!(!old.exists());
+        // moved already
+        assert!(!move_data_file(&old, &new).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+//! Reminders to start time tracking. During the `work_hours` of a work day, a desktop notification
+//! is sent once nothing was tracked for longer than `remind_after`. `tt watch` checks this while
+//! it runs, `tt nag` checks it once, e.g. from cron.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use crate::notification;
+use crate::settings::Settings;
+use crate::storage::read_tail;
+use crate::TrackingEvent;
+
+/// since when nothing was tracked, counted from the time the work hours opened at the earliest.
+/// `None` while an interval is running
+fn untracked_since(last: Option<&TrackingEvent>, opened: NaiveDateTime) -> Option<NaiveDateTime> {
+    match last {
+        Some(event) if event.is_start() => None,
+        Some(event) => Some(opened.max(event.time(true).with_timezone(&Local).naive_local())),
+        None => Some(opened),
+    }
+}
+
+/// how long nothing was tracked, if that is longer than `remind_after` and `now` is within the
+/// work hours of a work day
+pub fn untracked_for(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    now: NaiveDateTime,
+) -> Option<Duration> {
+    let remind_after = settings.watch.remind_after?;
+    let opened = settings.watch.work_hours.opened_at(now)?;
+    let day = opened.date();
+    if !settings.is_work_day(day.weekday()) || settings.absence_on(day).is_some() {
+        return None;
+    }
+    let untracked = now - untracked_since(data.last(), opened)?;
+    (untracked >= remind_after).then_some(untracked)
+}
+
+pub fn remind(untracked: Duration) {
+    let message = format!(
+        "Nothing was tracked for {} minutes. Did you forget to start time tracking?",
+        untracked.num_minutes()
+    );
+    println!("{}", message);
+    notification::send(&message);
+}
+
+/// checks once whether to remind, so it can be run from cron
+pub fn nag(settings: &Settings, path: &str) -> Result<()> {
+    // the last event is enough, which makes this fast enough to run every few minutes
+    let data = read_tail(path, Utc::now()).unwrap_or_default();
+    if let Some(untracked) = untracked_for(settings, &data, Local::now().naive_local()) {
+        remind(untracked);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_untracked_since() {
+        let at = |h| Local.ymd(2024, 3, 4).and_hms(h, 0, 0);
+        let opened = at(9).naive_local();
+        let event = |h| TrackingData::new(None, at(h).with_timezone(&Utc));
+        assert_eq!(Some(opened), untracked_since(None, opened));
+        assert_eq!(
+            None,
+            untracked_since(Some(&TrackingEvent::Start(event(10))), opened)
+        );
+        // stopped before the work hours
+        assert_eq!(
+            Some(opened),
+            untracked_since(Some(&TrackingEvent::Stop(event(7))), opened)
+        );
+        assert_eq!(
+            Some(at(11).naive_local()),
+            untracked_since(Some(&TrackingEvent::Pause(event(11))), opened)
+        );
+    }
+}
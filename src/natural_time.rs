@@ -0,0 +1,216 @@
+//! Parsing of relative time expressions like "5m ago", "yesterday 14:00" or "last monday".
+
+use chrono::{prelude::*, Duration};
+use iif::iif;
+
+use crate::DateOrDateTime;
+
+/// parses "H", "H:M" or "H:M:S"
+pub fn parse_time(s: &str) -> Option<NaiveTime> {
+    let from_time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M:%S");
+    from_time(s)
+        .or_else(|_| from_time(&format!("{}:0", s)))
+        .or_else(|_| from_time(&format!("{}:0:0", s)))
+        .ok()
+}
+
+fn parse_unit(unit: &str) -> Option<Duration> {
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(Duration::seconds(1)),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(Duration::minutes(1)),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(Duration::hours(1)),
+        "d" | "day" | "days" => Some(Duration::days(1)),
+        "w" | "week" | "weeks" => Some(Duration::weeks(1)),
+        _ => None,
+    }
+}
+
+/// `number` times `unit`. even an i32 of weeks is far from the limit of a duration, but not from
+/// the one of a date
+fn times(number: &str, unit: &str) -> Option<Duration> {
+    Some(parse_unit(unit)? * number.parse::<i32>().ok()?)
+}
+
+/// parses durations like "90m", "2h30m" or "1h 15min"
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim().to_lowercase();
+    let mut total = Duration::zero();
+    let mut rest = s.as_str();
+    while !rest.trim_start().is_empty() {
+        rest = rest.trim_start();
+        let split = rest.find(|c: char| !c.is_ascii_digit())?;
+        let (number, unit_and_rest) = rest.split_at(split);
+        let unit_and_rest = unit_and_rest.trim_start();
+        let split = unit_and_rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(unit_and_rest.len());
+        let (unit, remaining) = unit_and_rest.split_at(split);
+        total = total.checked_add(&times(number, unit)?)?;
+        rest = remaining;
+    }
+    iif!(total > Duration::zero(), Some(total), None)
+}
+
+/// parses the durations of the config, which are like "7h30m" and "45m", or "8:00" for hours and
+/// minutes. a plain number is a number of minutes, so "0" is no time at all
+pub fn parse_duration_setting(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if let Ok(minutes) = s.parse::<u32>() {
+        return Some(Duration::minutes(i64::from(minutes)));
+    }
+    if let Some((hours, minutes)) = s.split_once(':') {
+        let hours: u32 = hours.trim().parse().ok()?;
+        let minutes: u32 = minutes.trim().parse().ok()?;
+        return (minutes < 60)
+            .then(|| Duration::hours(i64::from(hours)) + Duration::minutes(i64::from(minutes)));
+    }
+    parse_duration(s)
+}
+
+/// parses "<n><unit> ago" and "<n> <unit> ago"
+fn parse_ago(words: &[&str], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let amount = match words {
+        [amount, "ago"] => *amount,
+        [number, unit, "ago"] => return now.checked_sub_signed(times(number, unit)?),
+        _ => return None,
+    };
+    let split = amount.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = amount.split_at(split);
+    now.checked_sub_signed(times(number, unit)?)
+}
+
+/// parses "today", "yesterday", "tomorrow", "<weekday>" and "last <weekday>".
+/// a plain weekday is the most recent one including today, "last" excludes today.
+fn parse_day<'a>(words: &'a [&'a str], today: NaiveDate) -> Option<(NaiveDate, &'a [&'a str])> {
+    let days_back = |weekday: Weekday, exclude_today: bool| {
+        let days =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        i64::from(if days == 0 && exclude_today { 7 } else { days })
+    };
+    match words {
+        ["today", rest @ ..] => Some((today, rest)),
+        ["yesterday", rest @ ..] => Some((today - Duration::days(1), rest)),
+        ["tomorrow", rest @ ..] => Some((today + Duration::days(1), rest)),
+        ["last", weekday, rest @ ..] => {
+            let weekday = weekday.parse().ok()?;
+            Some((today - Duration::days(days_back(weekday, true)), rest))
+        }
+        [weekday, rest @ ..] => {
+            let weekday = weekday.parse().ok()?;
+            Some((today - Duration::days(days_back(weekday, false)), rest))
+        }
+        [] => None,
+    }
+}
+
+/// parses a relative time expression. returns `None` if `s` is not one
+pub fn parse(s: &str, now: NaiveDateTime) -> Option<DateOrDateTime> {
+    let s = s.trim().to_lowercase();
+    let words: Vec<&str> = s.split_whitespace().collect();
+    if words == ["now"] {
+        return Some(now.into());
+    }
+    if let Some(date_time) = parse_ago(&words, now) {
+        return Some(date_time.into());
+    }
+    match parse_day(&words, now.date())? {
+        (date, []) => Some(date.into()),
+        (date, [time]) => Some(date.and_time(parse_time(time)?).into()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a thursday
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd(2021, 4, 1).and_hms(12, 30, 0)
+    }
+
+    fn date_time(d: u32, h: u32, m: u32) -> Option<DateOrDateTime> {
+        Some(NaiveDate::from_ymd(2021, 4, d).and_hms(h, m, 0).into())
+    }
+
+    fn date(m: u32, d: u32) -> Option<DateOrDateTime> {
+        Some(NaiveDate::from_ymd(2021, m, d).into())
+    }
+
+    #[test]
+    fn test_now() {
+        assert_eq!(Some(now().into()), parse("now", now()));
+        assert_eq!(Some(now().into()), parse(" Now ", now()));
+    }
+
+    #[test]
+    fn test_ago() {
+        assert_eq!(date_time(1, 12, 25), parse("5m ago", now()));
+        assert_eq!(date_time(1, 12, 25), parse("5 minutes ago", now()));
+        assert_eq!(date_time(1, 10, 30), parse("2h ago", now()));
+        assert_eq!(date_time(1, 11, 30), parse("1 hour ago", now()));
+        assert_eq!(date_time(1, 12, 29), parse("60s ago", now()));
+        assert_eq!(
+            Some(NaiveDate::from_ymd(2021, 3, 30).and_hms(12, 30, 0).into()),
+            parse("2 days ago", now())
+        );
+        assert_eq!(None, parse("5x ago", now()));
+        assert_eq!(None, parse("ago", now()));
+        assert_eq!(None, parse("2000000000w ago", now()));
+        assert_eq!(None, parse("2000000000 weeks ago", now()));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Some(Duration::minutes(90)), parse_duration("90m"));
+        assert_eq!(Some(Duration::minutes(150)), parse_duration("2h30m"));
+        assert_eq!(Some(Duration::minutes(75)), parse_duration("1h 15min"));
+        assert_eq!(Some(Duration::hours(2)), parse_duration("2 hours"));
+        assert_eq!(None, parse_duration("0m"));
+        assert_eq!(None, parse_duration("15:30"));
+        assert_eq!(None, parse_duration("2x"));
+        assert_eq!(None, parse_duration(""));
+        assert_eq!(None, parse_duration(&"2000000000w ".repeat(8)));
+    }
+
+    #[test]
+    fn test_parse_duration_setting() {
+        assert_eq!(
+            Some(Duration::minutes(450)),
+            parse_duration_setting("7h30m")
+        );
+        assert_eq!(Some(Duration::minutes(45)), parse_duration_setting("45m"));
+        assert_eq!(Some(Duration::hours(8)), parse_duration_setting("8:00"));
+        assert_eq!(Some(Duration::minutes(30)), parse_duration_setting("30"));
+        assert_eq!(Some(Duration::zero()), parse_duration_setting("0"));
+        assert_eq!(None, parse_duration_setting("8:75"));
+        assert_eq!(None, parse_duration_setting("-5"));
+        assert_eq!(None, parse_duration_setting("soon"));
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(date(4, 1), parse("today", now()));
+        assert_eq!(date(3, 31), parse("yesterday", now()));
+        assert_eq!(date(4, 2), parse("tomorrow", now()));
+        assert_eq!(date_time(1, 9, 0), parse("today 9", now()));
+        assert_eq!(
+            Some(NaiveDate::from_ymd(2021, 3, 31).and_hms(14, 0, 0).into()),
+            parse("yesterday 14:00", now())
+        );
+        assert_eq!(None, parse("yesterday noon", now()));
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(date(3, 29), parse("monday", now()));
+        assert_eq!(date(3, 29), parse("last monday", now()));
+        assert_eq!(date(3, 29), parse("last mon", now()));
+        assert_eq!(date(4, 1), parse("thursday", now()));
+        assert_eq!(date(3, 25), parse("last thursday", now()));
+        assert_eq!(date(3, 26), parse("last friday", now()));
+        assert_eq!(date_time(1, 8, 15), parse("thu 8:15", now()));
+        assert_eq!(None, parse("last", now()));
+        assert_eq!(None, parse("someday", now()));
+    }
+}
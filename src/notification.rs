@@ -0,0 +1,20 @@
+//! Desktop notifications, shown with osascript on macOS and notify-send everywhere else.
+
+pub fn send(message: &str) {
+    let result = if cfg!(target_os = "macos") {
+        std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification \"{}\" with title \"tt\"",
+                message
+            ))
+            .status()
+    } else {
+        std::process::Command::new("notify-send")
+            .args(["tt", message])
+            .status()
+    };
+    if result.is_err() {
+        eprintln!("Could not send a desktop notification!");
+    }
+}
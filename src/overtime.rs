@@ -0,0 +1,236 @@
+//! Overtime: the difference between the tracked time and the daily goals, summed up to a
+//! running balance (flextime).
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use std::collections::HashMap;
+
+use crate::settings::Settings;
+use crate::timesheet::format_delta;
+use crate::{
+    filter_events, format_hours_minutes, get_data_as_days, keyword_range, parse_date_or_date_time,
+    summarize_day, CalculationOptions, DateOrDateTime, FilterData, TrackingEvent,
+    CHECKED_ADD_DURATION_ERROR,
+};
+
+struct OvertimeDay {
+    date: NaiveDate,
+    work_time: Duration,
+    goal: Duration,
+    difference: Duration,
+}
+
+/// the work time per local date
+fn work_time_per_day(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> HashMap<NaiveDate, Duration> {
+    let mut work_times = HashMap::new();
+    for day in get_data_as_days(data) {
+        if let Some(event) = day.first() {
+            let date = event.local_date();
+            let work_time = summarize_day(settings, &day, options).work_time;
+            let total = work_times.entry(date).or_insert_with(Duration::zero);
+            *total = total
+                .checked_add(&work_time)
+                .expect(CHECKED_ADD_DURATION_ERROR);
+        }
+    }
+    work_times
+}
+
+/// the overtime of every day from `from` to `to`, including days without entries
+fn overtime_days(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    from: NaiveDate,
+    to: NaiveDate,
+    options: CalculationOptions,
+) -> Vec<OvertimeDay> {
+    let work_times = work_time_per_day(settings, data, options);
+    let mut days = Vec::new();
+    let mut date = from;
+    while date <= to {
+        let work_time = work_times
+            .get(&date)
+            .copied()
+            .unwrap_or_else(Duration::zero);
+        let goal = settings.goal_for_date(date);
+        let difference = if goal == Duration::zero() && !settings.overtime.count_days_off {
+            Duration::zero()
+        } else {
+            work_time - goal
+        };
+        days.push(OvertimeDay {
+            date,
+            work_time,
+            goal,
+            difference,
+        });
+        date = date.succ();
+    }
+    days
+}
+
+fn first_date(data: &[TrackingEvent]) -> Option<NaiveDate> {
+    data.first().map(TrackingEvent::local_date)
+}
+
+/// the days the filter covers. future days are left out, as they can't have overtime yet
+fn date_range(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+) -> Result<(NaiveDate, NaiveDate)> {
+    let today = Local::today().naive_local();
+    let to_date = |date_or_date_time| match date_or_date_time {
+        DateOrDateTime::Date(date) => date,
+        DateOrDateTime::DateTime(date_time) => date_time.date(),
+    };
+    let keyword = filter.filter.as_deref().unwrap_or_default();
+    let (from, to) = match keyword_range(keyword, today, settings.week_starts_on) {
+        Some(range) => range,
+        None if keyword == "all" => (first_date(data).unwrap_or(today), today),
+        None => {
+            let from = filter
+                .from
+                .as_deref()
+                .map(parse_date_or_date_time)
+                .transpose()?
+                .map_or(today, to_date);
+            let to = filter
+                .to
+                .as_deref()
+                .map(parse_date_or_date_time)
+                .transpose()?
+                .map_or(from, to_date);
+            (from, to)
+        }
+    };
+    Ok((from, to.min(today)))
+}
+
+/// the overtime balance of all days before today, starting at `overtime.start`. without a start,
+/// there is no balance
+pub fn balance(settings: &Settings, data: &[TrackingEvent]) -> Option<Duration> {
+    let yesterday = Local::today().naive_local().pred();
+    let from = settings.overtime.start?;
+    Some(
+        overtime_days(
+            settings,
+            data,
+            from,
+            yesterday,
+            CalculationOptions::default(),
+        )
+        .iter()
+        .fold(Duration::zero(), |balance, day| balance + day.difference),
+    )
+}
+
+pub fn overtime(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    options: CalculationOptions,
+) -> Result<()> {
+    let (from, to) = date_range(settings, data, filter)?;
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let days = overtime_days(settings, &filtered_data, from, to, options);
+
+    println!(
+        "{:<10}  {:>6}  {:>6}  {:>7}  {:>8}",
+        "Date", "Work", "Goal", "Diff", "Balance"
+    );
+    let mut balance = Duration::zero();
+    for day in &days {
+        balance += day.difference;
+        // the kind of absence explains why a day has no goal
+        let line = format!(
+            "{:<10}  {:>6}  {:>6}  {:>7}  {:>8}  {}",
+            day.date.format("%Y-%m-%d"),
+            format_hours_minutes(day.work_time),
+            format_hours_minutes(day.goal),
+            format_delta(day.difference),
+            format_delta(balance),
+            settings
+                .absence_on(day.date)
+                .map_or_else(String::new, |kind| kind.to_string())
+        );
+        println!("{}", line.trim_end());
+    }
+    println!("{:<10}  {:>33}", "Total", format_delta(balance));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    fn interval(date: NaiveDate, from: u32, to: u32) -> Vec<TrackingEvent> {
+        let at = |hour| {
+            Local
+                .from_local_date(&date)
+                .unwrap()
+                .and_hms(hour, 0, 0)
+                .with_timezone(&Utc)
+        };
+        vec![
+            TrackingEvent::Start(TrackingData::new(None, at(from))),
+            TrackingEvent::Stop(TrackingData::new(None, at(to))),
+        ]
+    }
+
+    #[test]
+    fn test_overtime_days() {
+        let mut settings = Settings::from_default_config();
+        let (thursday, friday, saturday) = (
+            NaiveDate::from_ymd(2021, 4, 1),
+            NaiveDate::from_ymd(2021, 4, 2),
+            NaiveDate::from_ymd(2021, 4, 3),
+        );
+        let data = [interval(thursday, 8, 17), interval(saturday, 10, 12)].concat();
+        let differences = |settings: &Settings| -> Vec<_> {
+            overtime_days(
+                settings,
+                &data,
+                thursday,
+                saturday,
+                CalculationOptions::default(),
+            )
+            .iter()
+            .map(|day| day.difference)
+            .collect()
+        };
+        assert_eq!(
+            vec![Duration::hours(1), Duration::hours(-8), Duration::hours(2)],
+            differences(&settings)
+        );
+
+        settings.holidays = vec![friday];
+        settings.overtime.count_days_off = false;
+        assert_eq!(
+            vec![Duration::hours(1), Duration::zero(), Duration::zero()],
+            differences(&settings)
+        );
+    }
+
+    #[test]
+    fn test_balance() {
+        let mut settings = Settings::from_default_config();
+        let today = Local::today().naive_local();
+        let data = interval(today - Duration::days(2), 8, 18);
+        assert_eq!(None, balance(&settings, &data));
+
+        let start = today - Duration::days(3);
+        settings.overtime.start = Some(start);
+        let goals = (1..=3)
+            .map(|days| settings.goal_for_date(today - Duration::days(days)))
+            .fold(Duration::zero(), |total, goal| total + goal);
+        assert_eq!(Some(Duration::hours(10) - goals), balance(&settings, &data));
+    }
+}
@@ -0,0 +1,58 @@
+//! Output which doesn't fit on the screen is shown in the pager set in $PAGER.
+
+use anyhow::Result;
+use terminal_size::{terminal_size, Height};
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less";
+
+fn spawn_pager(pager: &str) -> std::io::Result<std::process::Child> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", pager]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", pager]);
+        command
+    };
+    // less shows the escape codes of colored output instead of the colors by default
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+    command.stdin(Stdio::piped()).spawn()
+}
+
+/// prints the lines, using the pager if stdout is a terminal and the lines don't fit on it
+pub fn print_lines(lines: &[String]) -> Result<()> {
+    let fits = match terminal_size() {
+        Some((_, Height(height))) => lines.len() < usize::from(height),
+        None => true,
+    };
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    if fits || pager.is_empty() {
+        for line in lines {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    match spawn_pager(&pager) {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the pager closes its input when it quits early, which is not an error
+                let _ = stdin.write_all(lines.join("\n").as_bytes());
+                let _ = stdin.write_all(b"\n");
+            }
+            child.wait()?;
+        }
+        Err(_) => {
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
+}
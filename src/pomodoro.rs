@@ -0,0 +1,127 @@
+//! Pomodoro mode: alternating work and break cycles, where every work cycle is tracked.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+use iif::iif;
+use structopt::StructOpt;
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::notification;
+use crate::settings::Settings;
+use crate::storage::{lock, read_data, read_data_if_exists};
+use crate::{format_duration, write_changes, TrackingData, TrackingEvent};
+
+/// the tag every work cycle gets
+const TAG: &str = "pomodoro";
+
+#[derive(Debug, Default, StructOpt)]
+pub struct PomodoroOptions {
+    /// a description for the work cycles
+    description: Option<String>,
+
+    /// minutes per work cycle [default: pomodoro.work_minutes from the config]
+    #[structopt(short, long)]
+    work: Option<u32>,
+
+    /// minutes per break [default: pomodoro.break_minutes from the config]
+    #[structopt(short, long = "break")]
+    break_minutes: Option<u32>,
+
+    /// how many work cycles to run. 0 runs until interrupted
+    /// [default: pomodoro.cycles from the config]
+    #[structopt(short, long)]
+    cycles: Option<u32>,
+}
+
+fn add_event(settings: &Settings, path: &str, event: TrackingEvent) -> Result<()> {
+    let _lock = lock(path)?;
+    let mut data = read_data_if_exists(path)?;
+    let original_data = data.clone();
+    data.push(event);
+    write_changes(settings, path, &original_data, &mut data, true)
+}
+
+fn notify(settings: &Settings, message: &str) {
+    if settings.pomodoro.notify {
+        notification::send(message);
+    }
+}
+
+/// waits until `duration` has passed and shows the remaining time. returns false if it was
+/// interrupted with Ctrl-C
+fn countdown(label: &str, duration: Duration, running: &AtomicBool) -> Result<bool> {
+    let end = Utc::now() + duration;
+    loop {
+        if !running.load(Ordering::SeqCst) {
+            println!();
+            return Ok(false);
+        }
+        let remaining = end - Utc::now();
+        if remaining <= Duration::zero() {
+            break;
+        }
+        print!("\r{} {} remaining ", label, format_duration(remaining));
+        io::stdout().flush()?;
+        std::thread::sleep(std::time::Duration::from_secs(1).min(remaining.to_std()?));
+    }
+    println!("\r{} done{}", label, " ".repeat(20));
+    Ok(true)
+}
+
+pub fn pomodoro(settings: &Settings, path: &str, options: PomodoroOptions) -> Result<()> {
+    if matches!(read_data(path).unwrap_or_default().last(), Some(event) if event.is_start()) {
+        eprintln!("Time tracking is already running!");
+        return Ok(());
+    }
+
+    let work = Duration::minutes(i64::from(
+        options.work.unwrap_or(settings.pomodoro.work_minutes),
+    ));
+    let pause = Duration::minutes(i64::from(
+        options
+            .break_minutes
+            .unwrap_or(settings.pomodoro.break_minutes),
+    ));
+    let cycles = options.cycles.unwrap_or(settings.pomodoro.cycles);
+
+    // Ctrl-C stops the running work cycle, instead of leaving it running in the data file
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_running = running.clone();
+    ctrlc::set_handler(move || handler_running.store(false, Ordering::SeqCst))?;
+
+    let mut cycle = 1;
+    loop {
+        let total = iif!(cycles == 0, "".to_string(), format!("/{}", cycles));
+        add_event(
+            settings,
+            path,
+            TrackingEvent::Start(TrackingData {
+                tags: vec![TAG.to_string()],
+                ..TrackingData::new(options.description.clone(), Local::now().into())
+            }),
+        )?;
+        let finished = countdown(&format!("Work {}{}:", cycle, total), work, &running)?;
+        add_event(
+            settings,
+            path,
+            TrackingEvent::Stop(TrackingData::new(None, Local::now().into())),
+        )?;
+        if !finished {
+            return Ok(());
+        }
+
+        if cycle == cycles {
+            notify(settings, "All work cycles are done!");
+            return Ok(());
+        }
+        notify(settings, "Time for a break!");
+        if !countdown(&format!("Break {}{}:", cycle, total), pause, &running)? {
+            return Ok(());
+        }
+        notify(settings, "Back to work!");
+        cycle += 1;
+    }
+}
@@ -0,0 +1,99 @@
+//! The precision of the times of the events. By default, they are stored in whole seconds and
+//! calculations use whole minutes. With `millisecond_timestamps`, the milliseconds are stored as
+//! well, and with `include_seconds`, calculations use the exact times as if `-i` was given.
+//!
+//! Json has the timestamp in seconds, with the milliseconds as fraction if there are any, so files
+//! with whole seconds stay the same. Bincode always has the milliseconds, so the layout doesn't
+//! depend on the setting.
+
+use chrono::{prelude::*, serde::ts_seconds};
+use serde::{de, Deserializer, Serializer};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::settings::Settings;
+
+static MILLISECONDS: AtomicBool = AtomicBool::new(false);
+static INCLUDE_SECONDS: AtomicBool = AtomicBool::new(false);
+
+pub fn init(settings: &Settings) {
+    MILLISECONDS.store(settings.millisecond_timestamps, Ordering::Relaxed);
+    INCLUDE_SECONDS.store(settings.include_seconds, Ordering::Relaxed);
+}
+
+/// whether calculations use the exact times, even without `-i`
+pub fn include_seconds() -> bool {
+    INCLUDE_SECONDS.load(Ordering::Relaxed)
+}
+
+/// the timestamp in milliseconds, cut to whole seconds unless `millisecond_timestamps` is set
+fn millis(time: &DateTime<Utc>) -> i64 {
+    if MILLISECONDS.load(Ordering::Relaxed) {
+        time.timestamp_millis()
+    } else {
+        time.timestamp() * 1000
+    }
+}
+
+pub fn serialize<S: Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    let millis = millis(time);
+    if !serializer.is_human_readable() {
+        serializer.serialize_i64(millis)
+    } else if millis % 1000 == 0 {
+        serializer.serialize_i64(millis / 1000)
+    } else {
+        serializer.serialize_f64(millis as f64 / 1000.0)
+    }
+}
+
+struct SecondsVisitor;
+
+impl<'de> de::Visitor<'de> for SecondsVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a unix timestamp in seconds")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        ts_seconds::deserialize(de::value::I64Deserializer::new(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        ts_seconds::deserialize(de::value::U64Deserializer::new(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(Utc.timestamp_millis((value * 1000.0).round() as i64))
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(SecondsVisitor)
+    } else {
+        let millis: i64 = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Utc.timestamp_millis(millis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TrackingData, TrackingEvent};
+
+    #[test]
+    fn test_json_timestamps() {
+        let time = Utc.ymd(2021, 4, 1).and_hms_milli(8, 0, 0, 250);
+        let event: TrackingEvent =
+            serde_json::from_str(r#"{"Start":{"description":null,"time":1617264000.25}}"#).unwrap();
+        assert_eq!(time, event.data().time);
+        // older files only have whole seconds
+        let event: TrackingEvent =
+            serde_json::from_str(r#"{"Start":{"description":null,"time":1617264000}}"#).unwrap();
+        assert_eq!(time.with_nanosecond(0).unwrap(), event.data().time);
+
+        let json = serde_json::to_string(&TrackingData::new(None, time)).unwrap();
+        assert!(json.contains(r#""time":1617264000,"#));
+    }
+}
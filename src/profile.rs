@@ -0,0 +1,234 @@
+//! Profiles select a different data file, e.g. to track work and personal time separately.
+//! The active profile is remembered in a file in the config directory.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use iif::iif;
+use structopt::StructOpt;
+
+use std::path::{Path, PathBuf};
+
+use crate::settings::{config_dir, Settings};
+use crate::storage::{lock, read_data, read_data_if_exists};
+use crate::{stop_tracking, write_changes};
+
+/// the profile which uses the `data_file` setting
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, StructOpt)]
+pub enum ProfileCommand {
+    /// list all profiles and mark the active one
+    List,
+
+    /// switch the active profile. use "default" to switch back to the data_file setting
+    Switch {
+        /// the name of the profile
+        name: String,
+    },
+}
+
+fn active_profile_path() -> PathBuf {
+    PathBuf::from(config_dir()).join("active_profile")
+}
+
+pub fn active_profile() -> Option<String> {
+    read_active_profile(&active_profile_path())
+}
+
+fn read_active_profile(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// returns the data file of the given profile, or the active profile if none is given
+pub fn data_file(settings: &Settings, profile: Option<String>) -> Result<String> {
+    match profile.or_else(active_profile) {
+        Some(name) if name != DEFAULT_PROFILE => settings
+            .profiles
+            .get(&name)
+            .map(|profile| profile.data_file.clone())
+            .ok_or_else(|| anyhow!("Unknown profile \"{}\"!", name)),
+        _ => Ok(settings.data_file()),
+    }
+}
+
+/// remembers `name` as the active profile in the file at `path`
+fn switch(settings: &Settings, path: &Path, name: &str) -> Result<()> {
+    if name != DEFAULT_PROFILE && !settings.profiles.contains_key(name) {
+        return Err(anyhow!("Unknown profile \"{}\"!", name));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, name)?;
+    Ok(())
+}
+
+/// the names and expanded data files of all profiles, starting with the default profile
+fn all_data_files(settings: &Settings) -> Result<Vec<(String, String)>> {
+    let mut names: Vec<_> = settings.profiles.keys().collect();
+    names.sort();
+    let mut data_files = vec![(DEFAULT_PROFILE.to_string(), settings.data_file())];
+    data_files.extend(
+        names
+            .into_iter()
+            .map(|name| (name.clone(), settings.profiles[name].data_file.clone())),
+    );
+    data_files
+        .into_iter()
+        .map(|(name, data_file)| Ok((name, shellexpand::full(&data_file)?.to_string())))
+        .collect()
+}
+
+/// stops time tracking in the data files of all profiles
+pub fn stop_all(
+    settings: &Settings,
+    description: Option<String>,
+    at: Option<String>,
+) -> Result<()> {
+    let mut stopped = false;
+    for (name, path) in all_data_files(settings)? {
+        let _lock = lock(&path)?;
+        let mut data = read_data_if_exists(&path)?;
+        if !matches!(data.last(), Some(event) if event.is_start()) {
+            continue;
+        }
+        let original_data = data.clone();
+        stop_tracking(&mut data, description.clone(), at.clone(), false)?;
+        write_changes(settings, &path, &original_data, &mut data, true)?;
+        println!("Stopped time tracking in profile \"{}\".", name);
+        stopped = true;
+    }
+    if !stopped {
+        eprintln!("Time tracking is already stopped in all profiles!");
+    }
+    Ok(())
+}
+
+/// shows which profiles are currently tracking time
+pub fn status_all(settings: &Settings) -> Result<()> {
+    let active = active_profile().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let data_files = all_data_files(settings)?;
+    let width = data_files
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut running = 0;
+    for (name, path) in &data_files {
+        let data = read_data(path).unwrap_or_default();
+        let state = match data.last() {
+            Some(event) if event.is_start() => {
+                running += 1;
+                let since = event.time(true).with_timezone(&Local);
+                match event.description() {
+                    Some(description) => format!(
+                        "running since {} \"{}\"",
+                        since.format("%Y-%m-%d %H:%M"),
+                        description
+                    ),
+                    None => format!("running since {}", since.format("%Y-%m-%d %H:%M")),
+                }
+            }
+            _ => "stopped".to_string(),
+        };
+        println!(
+            "{} {:<width$}  {}",
+            iif!(*name == active, "*", " "),
+            name,
+            state,
+            width = width
+        );
+    }
+    if running > 1 {
+        println!();
+        println!(
+            "Warning: Time is tracked in {} profiles at the same time!",
+            running
+        );
+    }
+    Ok(())
+}
+
+pub fn run(settings: &Settings, command: ProfileCommand) -> Result<()> {
+    match command {
+        ProfileCommand::List => {
+            let active = active_profile().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+            let mut names: Vec<_> = settings.profiles.keys().collect();
+            names.sort();
+            println!(
+                "{} {} ({})",
+                iif!(active == DEFAULT_PROFILE, "*", " "),
+                DEFAULT_PROFILE,
+                settings.data_file()
+            );
+            for name in names {
+                println!(
+                    "{} {} ({})",
+                    iif!(*name == active, "*", " "),
+                    name,
+                    settings.profiles[name].data_file
+                );
+            }
+        }
+        ProfileCommand::Switch { name } => {
+            switch(settings, &active_profile_path(), &name)?;
+            println!("Switched to profile \"{}\".", name);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Profile;
+
+    fn settings() -> Settings {
+        let mut settings = Settings::from_default_config();
+        settings.profiles.insert(
+            "work".to_string(),
+            Profile {
+                data_file: "~/work.bin".to_string(),
+            },
+        );
+        settings
+    }
+
+    #[test]
+    fn test_data_file() {
+        let settings = settings();
+        assert_eq!(
+            "~/work.bin",
+            data_file(&settings, Some("work".to_string())).unwrap()
+        );
+        assert_eq!(
+            settings.data_file(),
+            data_file(&settings, Some(DEFAULT_PROFILE.to_string())).unwrap()
+        );
+        assert!(data_file(&settings, Some("home".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_switch() {
+        let settings = settings();
+        let path = std::env::temp_dir()
+            .join(format!("tt-test-profile-{}", std::process::id()))
+            .join("active_profile");
+        assert_eq!(None, read_active_profile(&path));
+
+        switch(&settings, &path, "work").unwrap();
+        assert_eq!(Some("work".to_string()), read_active_profile(&path));
+        assert!(switch(&settings, &path, "home").is_err());
+        assert_eq!(Some("work".to_string()), read_active_profile(&path));
+        switch(&settings, &path, DEFAULT_PROFILE).unwrap();
+        assert_eq!(
+            Some(DEFAULT_PROFILE.to_string()),
+            read_active_profile(&path)
+        );
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,91 @@
+//! The progress of the daily and the weekly goal, shown as bars by `tt status --goals`.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+use iif::iif;
+
+use crate::color::{paint, Style};
+use crate::settings::Settings;
+use crate::{
+    filter_events, format_hours_minutes, get_time_from_events, CalculationOptions, FilterData,
+    TrackingEvent,
+};
+
+/// the number of characters of a full bar
+const WIDTH: usize = 20;
+
+/// the bar filled by the share of the goal which is done, and the percentage. more than the goal
+/// fills the bar
+fn bar(done: Duration, goal: Duration) -> (String, i64) {
+    let percent = done.num_seconds() * 100 / goal.num_seconds().max(1);
+    let filled = (percent.clamp(0, 100) as usize * WIDTH) / 100;
+    let bar = format!("[{}{}]", "█".repeat(filled), "░".repeat(WIDTH - filled));
+    (bar, percent)
+}
+
+fn goal_line(label: &str, done: Duration, goal: Duration) -> String {
+    let (bar, percent) = bar(done, goal);
+    let style = iif!(percent >= 100, Style::Active, Style::Warning);
+    format!(
+        "{:<7}{}  {:>3}%  {} of {}",
+        format!("{}:", label),
+        paint(bar, style),
+        percent,
+        format_hours_minutes(done),
+        format_hours_minutes(goal)
+    )
+}
+
+fn work_time(settings: &Settings, data: &[TrackingEvent], filter: &str) -> Result<Duration> {
+    let filter = FilterData {
+        filter: Some(filter.to_string()),
+        ..FilterData::default()
+    };
+    let filtered_data = filter_events(data, &filter, settings.week_starts_on)?;
+    Ok(get_time_from_events(
+        settings,
+        &filtered_data,
+        CalculationOptions::default(),
+    ))
+}
+
+/// the progress of today's and this week's goal. days and weeks without a goal are left out
+pub fn goal_lines(settings: &Settings, data: &[TrackingEvent]) -> Result<Vec<String>> {
+    let today = Local::today().naive_local();
+    let mut lines = Vec::new();
+    let daily_goal = settings.goal_for_date(today);
+    if daily_goal > Duration::zero() {
+        lines.push(goal_line(
+            "Today",
+            work_time(settings, data, "today")?,
+            daily_goal,
+        ));
+    }
+    let weekly_goal = settings.weekly_goal_for_date(today);
+    if weekly_goal > Duration::zero() {
+        lines.push(goal_line(
+            "Week",
+            work_time(settings, data, "week")?,
+            weekly_goal,
+        ));
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(
+            (format!("[{}{}]", "█".repeat(5), "░".repeat(15)), 25),
+            bar(Duration::hours(2), Duration::hours(8))
+        );
+        // more than the goal
+        assert_eq!(
+            (format!("[{}]", "█".repeat(20)), 125),
+            bar(Duration::hours(10), Duration::hours(8))
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Uploading the tracked time to Clockify or Harvest with `tt push`, which is only available with
+//! the push feature. The end of the last uploaded interval is stored next to the data file
+//! (e.g. "<data_file>.push-clockify"), so every interval is only uploaded once.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use serde_json::{json, Value};
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::settings::Settings;
+use crate::{TrackingData, TrackingEvent};
+
+const CLOCKIFY_URL: &str = "https://api.clockify.me/api/v1";
+const HARVEST_URL: &str = "https://api.harvestapp.com/v2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushService {
+    Clockify,
+    Harvest,
+}
+
+impl FromStr for PushService {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "clockify" => Ok(Self::Clockify),
+            "harvest" => Ok(Self::Harvest),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: clockify, harvest",
+                s
+            )),
+        }
+    }
+}
+
+impl PushService {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Clockify => "clockify",
+            Self::Harvest => "harvest",
+        }
+    }
+}
+
+/// a finished interval with the data of its start
+struct Interval {
+    data: TrackingData,
+    stop: DateTime<Utc>,
+}
+
+fn cursor_path(data_path: &str, service: PushService) -> PathBuf {
+    PathBuf::from(format!("{}.push-{}", data_path, service.name()))
+}
+
+/// the end of the last uploaded interval
+fn read_cursor(path: &PathBuf) -> Result<Option<DateTime<Utc>>> {
+    match std::fs::read_to_string(path) {
+        Ok(cursor) => Ok(Some(
+            DateTime::parse_from_rfc3339(cursor.trim())
+                .with_context(|| format!("invalid push cursor in \"{}\"", path.display()))?
+                .with_timezone(&Utc),
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// the finished intervals which started at or after `cursor`. the running one is uploaded once it
+/// is stopped.
+fn new_intervals(data: &[TrackingEvent], cursor: Option<DateTime<Utc>>) -> Vec<Interval> {
+    let mut intervals = Vec::new();
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let stop = match data_iterator.find(|e| e.is_stop()) {
+            Some(stop) => stop.time(true),
+            None => break,
+        };
+        if !matches!(cursor, Some(cursor) if start.time(true) < cursor) {
+            intervals.push(Interval {
+                data: start.data().clone(),
+                stop,
+            });
+        }
+    }
+    intervals
+}
+
+/// sends the request and turns error responses into errors with the message of the service
+fn send(request: ureq::Request, body: Value, service: PushService) -> Result<()> {
+    match request.send_json(body) {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => Err(anyhow!(
+            "{} returned {}: {}",
+            service.name(),
+            code,
+            response.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(e).with_context(|| format!("could not connect to {}", service.name())),
+    }
+}
+
+fn push_clockify(settings: &Settings, interval: &Interval) -> Result<()> {
+    let clockify = &settings.push.clockify;
+    let project_id = interval
+        .data
+        .project
+        .as_ref()
+        .and_then(|project| clockify.projects.get(project));
+    let body = json!({
+        "start": interval.data.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "end": interval.stop.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "description": interval.data.description.clone().unwrap_or_default(),
+        "projectId": project_id,
+        "billable": interval.data.is_billable(settings),
+    });
+    let url = format!(
+        "{}/workspaces/{}/time-entries",
+        CLOCKIFY_URL, clockify.workspace_id
+    );
+    send(
+        ureq::post(&url).set("X-Api-Key", &clockify.api_key),
+        body,
+        PushService::Clockify,
+    )
+}
+
+fn push_harvest(settings: &Settings, interval: &Interval) -> Result<()> {
+    let harvest = &settings.push.harvest;
+    let project = &interval.data.project;
+    let project_id = project
+        .as_ref()
+        .and_then(|project| harvest.projects.get(project))
+        .copied()
+        .unwrap_or(harvest.project_id);
+    // harvest only gets the hours, so they are rounded like in reports
+    let duration = settings
+        .for_project(project)
+        .rounding
+        .apply(interval.stop - interval.data.time);
+    let body = json!({
+        "project_id": project_id,
+        "task_id": harvest.task_id,
+        "spent_date": interval.data.local_time().format("%Y-%m-%d").to_string(),
+        "hours": duration.num_seconds() as f64 / 3600.0,
+        "notes": interval.data.description,
+    });
+    send(
+        ureq::post(&format!("{}/time_entries", HARVEST_URL))
+            .set("Authorization", &format!("Bearer {}", harvest.token))
+            .set("Harvest-Account-Id", &harvest.account_id)
+            .set("User-Agent", "timetracking (tt)"),
+        body,
+        PushService::Harvest,
+    )
+}
+
+fn check_settings(settings: &Settings, service: PushService) -> Result<()> {
+    let missing = match service {
+        PushService::Clockify if settings.push.clockify.api_key.is_empty() => "clockify.api_key",
+        PushService::Clockify if settings.push.clockify.workspace_id.is_empty() => {
+            "clockify.workspace_id"
+        }
+        PushService::Harvest if settings.push.harvest.token.is_empty() => "harvest.token",
+        PushService::Harvest if settings.push.harvest.account_id.is_empty() => "harvest.account_id",
+        _ => return Ok(()),
+    };
+    Err(anyhow!("push.{} is empty in the config!", missing))
+}
+
+/// uploads the intervals which were finished since the last push
+pub fn push(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    data_path: &str,
+    service: PushService,
+    dry_run: bool,
+) -> Result<()> {
+    check_settings(settings, service)?;
+    let path = cursor_path(data_path, service);
+    let intervals = new_intervals(data, read_cursor(&path)?);
+    if intervals.is_empty() {
+        println!("Nothing to upload.");
+        return Ok(());
+    }
+    for interval in &intervals {
+        let line = format!(
+            "{} - {} {}",
+            interval.data.local_time().format("%Y-%m-%d %H:%M"),
+            interval
+                .stop
+                .with_timezone(interval.data.local_time().offset())
+                .format("%H:%M"),
+            interval.data.description.clone().unwrap_or_default()
+        );
+        if dry_run {
+            println!("Would upload {}", line);
+            continue;
+        }
+        match service {
+            PushService::Clockify => push_clockify(settings, interval)?,
+            PushService::Harvest => push_harvest(settings, interval)?,
+        }
+        // written after every entry, so a failed upload doesn't upload the previous ones again
+        std::fs::write(
+            &path,
+            interval.stop.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )?;
+        println!("Uploaded {}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_intervals() {
+        let at = |h| Utc.ymd(2021, 4, 1).and_hms(h, 0, 0);
+        let data = vec![
+            TrackingEvent::Start(TrackingData::new(None, at(8))),
+            TrackingEvent::Stop(TrackingData::new(None, at(9))),
+            TrackingEvent::Start(TrackingData::new(None, at(10))),
+            TrackingEvent::Stop(TrackingData::new(None, at(11))),
+            TrackingEvent::Start(TrackingData::new(None, at(12))),
+        ];
+        let stops = |cursor| -> Vec<_> {
+            new_intervals(&data, cursor)
+                .iter()
+                .map(|interval| interval.stop)
+                .collect()
+        };
+        assert_eq!(vec![at(9), at(11)], stops(None));
+        assert_eq!(vec![at(11)], stops(Some(at(9))));
+        assert!(stops(Some(at(11))).is_empty());
+    }
+}
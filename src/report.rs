@@ -0,0 +1,531 @@
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use iif::iif;
+
+use std::str::FromStr;
+
+use crate::color::Style;
+use crate::settings::Settings;
+use crate::table::{Cell, Column, Table};
+use crate::timesheet::format_delta;
+use crate::{
+    filter_events, filter_range, format_duration, get_data_as_days, summarize_day,
+    CalculationOptions, DateOrDateTime, DaySummary, FilterData, TrackingEvent,
+    CHECKED_ADD_DURATION_ERROR,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: day, week, month",
+                s
+            )),
+        }
+    }
+}
+
+impl GroupBy {
+    fn label(self, settings: &Settings, date: NaiveDate) -> String {
+        match self {
+            Self::Day => date.format("%Y-%m-%d").to_string(),
+            // iso weeks only make sense for weeks starting on monday, otherwise use the first day
+            Self::Week if settings.week_starts_on == Weekday::Mon => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Week => settings.start_of_week(date).format("%Y-%m-%d").to_string(),
+            Self::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+
+    fn time_format(self) -> &'static str {
+        match self {
+            Self::Day => "%H:%M",
+            Self::Week | Self::Month => "%m-%d %H:%M",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SummaryKey {
+    Description,
+    Project,
+    Tag,
+}
+
+impl FromStr for SummaryKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "description" => Ok(Self::Description),
+            "project" => Ok(Self::Project),
+            "tag" => Ok(Self::Tag),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: description, project, tag",
+                s
+            )),
+        }
+    }
+}
+
+impl SummaryKey {
+    /// the groups the time of the entry is counted for. an entry with several tags counts for
+    /// each of them
+    fn of(self, event: &TrackingEvent) -> Vec<String> {
+        let keys = match self {
+            Self::Description => event.description().into_iter().collect(),
+            Self::Project => event.data().project.clone().into_iter().collect(),
+            Self::Tag => event.data().tags.clone(),
+        };
+        iif!(keys.is_empty(), vec!["-".to_string()], keys)
+    }
+}
+
+struct Group {
+    label: String,
+    date: NaiveDate,
+    days: Vec<DaySummary>,
+}
+
+impl Group {
+    fn first_start(&self) -> Option<DateTime<Utc>> {
+        self.days.iter().filter_map(|day| day.first_start).min()
+    }
+
+    fn last_stop(&self) -> Option<DateTime<Utc>> {
+        if matches!(self.days.last(), Some(day) if day.running) {
+            None
+        } else {
+            self.days.iter().filter_map(|day| day.last_stop).max()
+        }
+    }
+
+    fn work_time(&self) -> Duration {
+        sum(self.days.iter().map(|day| day.work_time))
+    }
+
+    fn break_time(&self) -> Duration {
+        sum(self.days.iter().map(DaySummary::break_time))
+    }
+
+    fn billable_time(&self) -> Duration {
+        sum(self.days.iter().map(|day| day.billable_time))
+    }
+}
+
+fn sum<I: Iterator<Item = Duration>>(durations: I) -> Duration {
+    durations.fold(Duration::zero(), |total, duration| {
+        total
+            .checked_add(&duration)
+            .expect(CHECKED_ADD_DURATION_ERROR)
+    })
+}
+
+fn group_days(
+    settings: &Settings,
+    days: &[Vec<TrackingEvent>],
+    group_by: GroupBy,
+    options: CalculationOptions,
+) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    for day in days {
+        let date = match day.first() {
+            Some(event) => event.local_date(),
+            None => continue,
+        };
+        let label = group_by.label(settings, date);
+        let summary = summarize_day(settings, day, options);
+        match groups.last_mut() {
+            Some(group) if group.label == label => group.days.push(summary),
+            _ => groups.push(Group {
+                label,
+                date,
+                days: vec![summary],
+            }),
+        }
+    }
+    groups
+}
+
+/// empty groups for the absent days in the range of the filter, which have no entries
+fn absent_days(settings: &Settings, filter: &FilterData, groups: &[Group]) -> Result<Vec<Group>> {
+    let (keyword, from, to) = filter_range(filter, settings.week_starts_on)?;
+    let to_date = |date_or_date_time| match date_or_date_time {
+        DateOrDateTime::Date(date) => date,
+        DateOrDateTime::DateTime(date_time) => date_time.date(),
+    };
+    let (from, to) = if keyword.as_deref() == Some("all") {
+        (
+            groups.first().map(|g| g.date),
+            groups.last().map(|g| g.date),
+        )
+    } else {
+        (from.map(to_date), to.map(to_date))
+    };
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return Ok(Vec::new()),
+    };
+    Ok(settings
+        .absences
+        .iter()
+        .filter(|absence| absence.date >= from && absence.date <= to)
+        .filter(|absence| !groups.iter().any(|group| group.date == absence.date))
+        .map(|absence| Group {
+            label: GroupBy::Day.label(settings, absence.date),
+            date: absence.date,
+            days: Vec::new(),
+        })
+        .collect())
+}
+
+/// the billable time, the non-billable time and the share of the billable time in percent, as
+/// the cells shown after the work time
+fn billable_cells(work_time: Duration, billable_time: Duration) -> Vec<Cell> {
+    let percentage = if work_time > Duration::zero() {
+        let (billable, work) = (billable_time.num_seconds(), work_time.num_seconds());
+        format!("{}%", (billable * 100 + work / 2) / work)
+    } else {
+        "-".to_string()
+    };
+    vec![
+        format_duration(billable_time).into(),
+        format_duration(work_time - billable_time).into(),
+        percentage.into(),
+    ]
+}
+
+fn billable_columns() -> Vec<Column> {
+    vec![
+        Column::right("Billable"),
+        Column::right("Non-Billable"),
+        Column::right("%"),
+    ]
+}
+
+fn format_time(time: Option<DateTime<Utc>>, format: &str) -> String {
+    time.map_or_else(
+        || "-".to_string(),
+        |time| time.with_timezone(&Local).format(format).to_string(),
+    )
+}
+
+pub fn report(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    group_by: GroupBy,
+    options: CalculationOptions,
+    wide: bool,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let days = get_data_as_days(&filtered_data);
+    let mut groups = group_days(settings, &days, group_by, options);
+    if group_by == GroupBy::Day {
+        groups.extend(absent_days(settings, filter, &groups)?);
+        groups.sort_by_key(|group| group.date);
+    }
+
+    let goal = |group: &Group| match group_by {
+        GroupBy::Day => Some(settings.goal_for_date(group.date)),
+        GroupBy::Week => Some(settings.weekly_goal_for_date(group.date)),
+        GroupBy::Month => None,
+    };
+    let time_format = group_by.time_format();
+    // the billable time is only shown, if it is used
+    let show_billable = groups
+        .iter()
+        .any(|group| group.billable_time() > Duration::zero());
+
+    let mut columns = vec![
+        Column::left("Date"),
+        Column::left("Start"),
+        Column::left("Stop"),
+        Column::right("Work"),
+        Column::right("Break"),
+    ];
+    if show_billable {
+        columns.extend(billable_columns());
+    }
+    columns.push(Column::left("Goal"));
+    let mut table = Table::new(columns);
+    for group in &groups {
+        let absence = iif!(
+            group_by == GroupBy::Day,
+            settings.absence_on(group.date),
+            None
+        );
+        // absent days have no goal, so the kind of absence is shown instead
+        let goal_met = match (absence, goal(group)) {
+            (Some(kind), _) => kind.to_string().into(),
+            (None, Some(goal)) if group.work_time() >= goal => Cell::styled("yes", Style::Active),
+            (None, Some(_)) => Cell::styled("no", Style::Error),
+            (None, None) => "-".into(),
+        };
+        let mut cells: Vec<Cell> = vec![
+            group.label.clone().into(),
+            format_time(group.first_start(), time_format).into(),
+            format_time(group.last_stop(), time_format).into(),
+            format_duration(group.work_time()).into(),
+            format_duration(group.break_time()).into(),
+        ];
+        if show_billable {
+            cells.extend(billable_cells(group.work_time(), group.billable_time()));
+        }
+        cells.push(goal_met);
+        table.add_row(cells);
+    }
+    let work_time = sum(groups.iter().map(Group::work_time));
+    let mut total: Vec<Cell> = vec![
+        "Total".into(),
+        "".into(),
+        "".into(),
+        format_duration(work_time).into(),
+        format_duration(sum(groups.iter().map(Group::break_time))).into(),
+    ];
+    if show_billable {
+        total.extend(billable_cells(
+            work_time,
+            sum(groups.iter().map(Group::billable_time)),
+        ));
+    }
+    table.add_styled_row(total, Some(Style::Header));
+    for line in table.render(wide) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// prints a line per day of the filtered data, with the first start, the last stop, the work
+/// time and the difference to the goal of the day
+pub fn breakdown(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    options: CalculationOptions,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    for day in get_data_as_days(&filtered_data) {
+        let date = match day.first() {
+            Some(event) => event.local_date(),
+            None => continue,
+        };
+        let summary = summarize_day(settings, &day, options);
+        let last_stop = iif!(summary.running, None, summary.last_stop);
+        let line = format!(
+            "{}  {}  {:<5}  {:<5}  {}  {}  {}",
+            date.format("%Y-%m-%d"),
+            date.format("%a"),
+            format_time(summary.first_start, "%H:%M"),
+            format_time(last_stop, "%H:%M"),
+            format_duration(summary.work_time),
+            format_delta(summary.work_time - settings.goal_for_date(date)),
+            settings
+                .absence_on(date)
+                .map_or_else(String::new, |kind| kind.to_string())
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// the duration of every interval, limited and rounded like the work time, with its start
+fn interval_durations<'a>(
+    settings: &Settings,
+    data: &'a [TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<(&'a TrackingEvent, Duration)> {
+    let include_seconds = options.include_seconds;
+    let mut intervals = Vec::new();
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let stop = data_iterator
+            .find(|e| e.is_stop())
+            .map_or_else(Utc::now, |stop| stop.time(include_seconds));
+        let mut duration =
+            match options.limit_interval(settings, stop - start.time(include_seconds)) {
+                Some(duration) => duration,
+                None => continue,
+            };
+        if !options.no_round {
+            duration = settings.rounding.apply(duration);
+        }
+        intervals.push((start, duration));
+    }
+    intervals
+}
+
+/// the total and the billable time of every key
+pub fn sum_by_key(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    key: SummaryKey,
+    options: CalculationOptions,
+) -> Vec<(String, Duration, Duration)> {
+    let mut totals: Vec<(String, Duration, Duration)> = Vec::new();
+    for (start, duration) in interval_durations(settings, data, options) {
+        let billable = iif!(
+            start.data().is_billable(settings),
+            duration,
+            Duration::zero()
+        );
+        for key in key.of(start) {
+            match totals.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, total, total_billable)) => {
+                    *total = total
+                        .checked_add(&duration)
+                        .expect(CHECKED_ADD_DURATION_ERROR);
+                    *total_billable += billable;
+                }
+                None => totals.push((key, duration, billable)),
+            }
+        }
+    }
+    totals.sort_by(|(a_key, a, _), (b_key, b, _)| b.cmp(a).then_with(|| a_key.cmp(b_key)));
+    totals
+}
+
+pub fn summary(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    key: SummaryKey,
+    options: CalculationOptions,
+    wide: bool,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let totals = sum_by_key(settings, &filtered_data, key, options);
+    // the billable time is only shown, if it is used
+    let show_billable = totals
+        .iter()
+        .any(|(_, _, billable)| *billable > Duration::zero());
+
+    let mut columns = vec![Column::right("Work")];
+    if show_billable {
+        columns.extend(billable_columns());
+    }
+    columns.push(Column::left("").truncated());
+    let mut table = Table::new(columns);
+    if !show_billable {
+        table = table.without_header();
+    }
+    let row = |key: &str, duration, billable| {
+        let mut cells: Vec<Cell> = vec![format_duration(duration).into()];
+        if show_billable {
+            cells.extend(billable_cells(duration, billable));
+        }
+        cells.push(key.into());
+        cells
+    };
+    for (key, duration, total_billable) in &totals {
+        table.add_row(row(key, *duration, *total_billable));
+    }
+    // the time of an entry with several tags is in several rows, but only once in the total
+    let intervals = interval_durations(settings, &filtered_data, options);
+    let total = sum(intervals.iter().map(|(_, duration)| *duration));
+    let total_billable = sum(intervals
+        .iter()
+        .filter(|(start, _)| start.data().is_billable(settings))
+        .map(|(_, duration)| *duration));
+    table.add_row(row("Total", total, total_billable));
+    for line in table.render(wide) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    fn interval(day: u32, from: u32, to: u32) -> Vec<TrackingEvent> {
+        let at = |hour| Utc.ymd(2021, 4, day).and_hms(hour, 0, 0);
+        vec![
+            TrackingEvent::Start(TrackingData::new(None, at(from))),
+            TrackingEvent::Stop(TrackingData::new(None, at(to))),
+        ]
+    }
+
+    #[test]
+    fn test_group_days() {
+        let settings = Settings::from_default_config();
+        let data = [interval(1, 8, 12), interval(1, 13, 17), interval(2, 9, 11)].concat();
+        let days = get_data_as_days(&data);
+        let options = CalculationOptions {
+            include_seconds: true,
+            no_round: true,
+            ..CalculationOptions::default()
+        };
+
+        let groups = group_days(&settings, &days, GroupBy::Day, options);
+        let labels: Vec<_> = groups.iter().map(|group| group.label.as_str()).collect();
+        assert_eq!(vec!["2021-04-01", "2021-04-02"], labels);
+        assert_eq!(Duration::hours(8), groups[0].work_time());
+        assert_eq!(Duration::hours(1), groups[0].break_time());
+        assert_eq!(Duration::hours(2), groups[1].work_time());
+        assert_eq!(Duration::zero(), groups[1].break_time());
+
+        let groups = group_days(&settings, &days, GroupBy::Week, options);
+        assert_eq!(1, groups.len());
+        assert_eq!("2021-W13", groups[0].label);
+        assert_eq!(Duration::hours(10), groups[0].work_time());
+        assert_eq!(Duration::hours(1), groups[0].break_time());
+    }
+
+    #[test]
+    fn test_sum_by_description() {
+        let at = |hour| Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0);
+        let interval = |description: Option<&str>, from, to| {
+            vec![
+                TrackingEvent::Start(TrackingData::new(description.map(String::from), at(from))),
+                TrackingEvent::Stop(TrackingData::new(None, at(to))),
+            ]
+        };
+        let mut data = [
+            interval(Some("a"), 8, 10),
+            interval(Some("b"), 10, 11),
+            interval(None, 11, 12),
+            interval(Some("a"), 13, 14),
+        ]
+        .concat();
+        if let TrackingEvent::Start(start) = &mut data[6] {
+            start.billable = Some(true);
+        }
+        let options = CalculationOptions {
+            include_seconds: true,
+            no_round: true,
+            ..CalculationOptions::default()
+        };
+        let totals = sum_by_key(
+            &Settings::from_default_config(),
+            &data,
+            SummaryKey::Description,
+            options,
+        );
+        assert_eq!(
+            vec![
+                ("a".to_string(), Duration::hours(3), Duration::hours(1)),
+                ("-".to_string(), Duration::hours(1), Duration::zero()),
+                ("b".to_string(), Duration::hours(1), Duration::zero()),
+            ],
+            totals
+        );
+    }
+}
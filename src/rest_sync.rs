@@ -0,0 +1,267 @@
+//! Synchronization with a self-hosted sync server over http, which is only available with the
+//! rest-sync feature. Unlike the git sync, the server only exchanges the changed events, so phone
+//! clients or other tools can take part. See the README for the protocol.
+//!
+//! The state of the last sync is stored next to the data file ("<data_file>.rest-sync"): the
+//! cursor of the server and the ids and checksums of the events at that time, which tell the
+//! events that were added, edited or deleted locally since then, and when every local change was
+//! made.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use iif::iif;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::cleanup::{cleanup, has_conflicts};
+use crate::journal::difference;
+use crate::settings::Settings;
+use crate::storage::read_data_if_exists;
+use crate::{write_changes, TrackingEvent};
+
+/// an added or edited event, or a deleted one without the event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Change {
+    id: Uuid,
+    /// when the change was made, so the server can keep the last change of every event
+    updated: DateTime<Utc>,
+    event: Option<TrackingEvent>,
+}
+
+#[derive(Serialize)]
+struct SyncRequest {
+    since: u64,
+    changes: Vec<Change>,
+}
+
+#[derive(Deserialize)]
+struct SyncResponse {
+    cursor: u64,
+    changes: Vec<Change>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    url: String,
+    cursor: u64,
+    /// the checksums of the events by their id
+    #[serde(default)]
+    events: BTreeMap<Uuid, u32>,
+    /// when the events were added, edited or deleted locally since the last sync
+    #[serde(default)]
+    modified: BTreeMap<Uuid, DateTime<Utc>>,
+}
+
+fn state_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.rest-sync", path))
+}
+
+/// the state of the last sync with `url`. a sync with another server starts from scratch, only
+/// the times of the local changes are kept
+fn read_state(path: &str, url: &str) -> Result<State> {
+    let state: State = match std::fs::read_to_string(state_path(path)) {
+        Ok(state) => serde_json::from_str(&state)?,
+        Err(_) => State::default(),
+    };
+    Ok(iif!(
+        state.url == url,
+        state,
+        State {
+            modified: state.modified,
+            ..State::default()
+        }
+    ))
+}
+
+fn write_state(path: &str, state: &State) -> Result<()> {
+    std::fs::write(state_path(path), serde_json::to_string(state)?)?;
+    Ok(())
+}
+
+/// remembers when events were added, edited or deleted, so the next sync sends the time of the
+/// change and not the time of the sync. nothing is recorded before the first sync, since all
+/// events are new to the server then anyway
+pub fn record(path: &str, old: &[TrackingEvent], new: &[TrackingEvent]) -> Result<()> {
+    let changed: Vec<_> = difference(old, new)
+        .into_iter()
+        .chain(difference(new, old))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let mut state: State = match std::fs::read_to_string(state_path(path)) {
+        Ok(state) => serde_json::from_str(&state)?,
+        Err(_) => return Ok(()),
+    };
+    let now = Utc::now();
+    for event in &changed {
+        state.modified.insert(event.data().id, now);
+    }
+    write_state(path, &state)
+}
+
+fn checksum(event: &TrackingEvent) -> u32 {
+    crc32fast::hash(&serde_json::to_vec(event).expect("could not serialize event"))
+}
+
+fn checksums(data: &[TrackingEvent]) -> BTreeMap<Uuid, u32> {
+    data.iter()
+        .map(|event| (event.data().id, checksum(event)))
+        .collect()
+}
+
+/// the events which were added, edited or deleted since the last sync, with the time of the
+/// change. events which were added without being recorded, e.g. before the first sync, were added
+/// at their own time at the latest
+fn local_changes(state: &State, data: &[TrackingEvent], now: DateTime<Utc>) -> Vec<Change> {
+    let current = checksums(data);
+    let changed = data
+        .iter()
+        .filter(|event| state.events.get(&event.data().id) != current.get(&event.data().id))
+        .map(|event| Change {
+            id: event.data().id,
+            updated: *state
+                .modified
+                .get(&event.data().id)
+                .unwrap_or(&event.time(true)),
+            event: Some(event.clone()),
+        });
+    let deleted = state
+        .events
+        .keys()
+        .filter(|id| !current.contains_key(id))
+        .map(|id| Change {
+            id: *id,
+            updated: *state.modified.get(id).unwrap_or(&now),
+            event: None,
+        });
+    changed.chain(deleted).collect()
+}
+
+/// applies the changes from the server, which already decided which change of an event wins
+fn apply(data: &[TrackingEvent], changes: &[Change]) -> Vec<TrackingEvent> {
+    // the server also sends back the changes of this client, which replace the same events
+    let changed: HashSet<_> = changes.iter().map(|change| change.id).collect();
+    let mut merged: Vec<_> = data
+        .iter()
+        .filter(|event| !changed.contains(&event.data().id))
+        .cloned()
+        .collect();
+    merged.extend(changes.iter().filter_map(|change| change.event.clone()));
+    merged.sort_by_key(|e| e.time(true));
+    merged
+}
+
+fn send(settings: &Settings, url: &str, request: &SyncRequest) -> Result<SyncResponse> {
+    let mut http_request = ureq::post(&format!("{}/sync", url.trim_end_matches('/')));
+    if !settings.sync.token.is_empty() {
+        http_request =
+            http_request.set("Authorization", &format!("Bearer {}", settings.sync.token));
+    }
+    match http_request.send_json(serde_json::to_value(request)?) {
+        Ok(response) => response
+            .into_json()
+            .context("the sync server sent an invalid response"),
+        Err(ureq::Error::Status(code, response)) => Err(anyhow!(
+            "the sync server returned {}: {}",
+            code,
+            response.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(e).context("could not connect to the sync server"),
+    }
+}
+
+/// sends the local changes to the server and merges the changes of the other clients
+pub fn sync(settings: &Settings, path: &str, url: &str) -> Result<()> {
+    let state = read_state(path, url)?;
+    let local = read_data_if_exists(path)?;
+    let request = SyncRequest {
+        since: state.cursor,
+        changes: local_changes(&state, &local, Utc::now()),
+    };
+    let response = send(settings, url, &request)?;
+
+    let mut merged = apply(&local, &response.changes);
+    if merged != local && has_conflicts(&merged) {
+        println!("The local and the remote changes conflict, please clean them up.");
+        merged = cleanup(&merged, None);
+    }
+    if merged != local {
+        write_changes(settings, path, &local, &mut merged, true)?;
+        println!("Merged the changes from the sync server.");
+    }
+    let state = State {
+        url: url.to_string(),
+        cursor: response.cursor,
+        events: checksums(&merged),
+        modified: BTreeMap::new(),
+    };
+    write_state(path, &state)?;
+    if !request.changes.is_empty() {
+        println!("Sent {} changes to the sync server.", request.changes.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    fn start(h: u32) -> TrackingEvent {
+        TrackingEvent::Start(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+        ))
+    }
+
+    fn stop(h: u32) -> TrackingEvent {
+        TrackingEvent::Stop(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+        ))
+    }
+
+    #[test]
+    fn test_local_changes_and_apply() {
+        let now = Utc::now();
+        let (first, second) = (start(8), stop(9));
+        let deleted_at = Utc.ymd(2021, 4, 2).and_hms(12, 0, 0);
+        let state = State {
+            events: checksums(&[first.clone(), second.clone()]),
+            modified: vec![(second.data().id, deleted_at)].into_iter().collect(),
+            ..State::default()
+        };
+        let mut edited = first.clone();
+        edited.data_mut().description = Some("review".to_string());
+        let data = vec![edited.clone(), start(10)];
+        let changes = local_changes(&state, &data, now);
+        assert_eq!(3, changes.len());
+        assert_eq!(first.data().id, changes[0].id);
+        assert_eq!(Some(edited.clone()), changes[0].event);
+        assert_eq!(Some(data[1].clone()), changes[1].event);
+        assert_eq!(data[1].time(true), changes[1].updated);
+        assert_eq!(second.data().id, changes[2].id);
+        assert_eq!(None, changes[2].event);
+        assert_eq!(deleted_at, changes[2].updated);
+
+        let end = stop(11);
+        let remote = vec![
+            Change {
+                id: end.data().id,
+                updated: now,
+                event: Some(end.clone()),
+            },
+            Change {
+                id: first.data().id,
+                updated: now,
+                event: None,
+            },
+        ];
+        assert_eq!(vec![data[1].clone(), end], apply(&data, &remote));
+        assert_eq!(data, apply(&data, &changes[..2]));
+    }
+}
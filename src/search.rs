@@ -0,0 +1,139 @@
+//! `tt search`, which finds the intervals whose description or notes contain a text or match a
+//! regular expression. It always searches the whole history, including the archives.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    format_hours_minutes, locale, notes_to_human_readable, pager, TrackingData, TrackingEvent,
+};
+
+/// what the descriptions and notes are matched against
+pub enum Pattern {
+    Text(String),
+    /// the text in lowercase
+    IgnoreCase(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, ignore_case: bool, regex: bool) -> Result<Self> {
+        if regex {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .map_err(|e| anyhow!("invalid regular expression: {}", e))?;
+            Ok(Self::Regex(regex))
+        } else if ignore_case {
+            Ok(Self::IgnoreCase(pattern.to_lowercase()))
+        } else {
+            Ok(Self::Text(pattern.to_string()))
+        }
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Text(pattern) => text.contains(pattern.as_str()),
+            Self::IgnoreCase(pattern) => text.to_lowercase().contains(pattern.as_str()),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+
+    fn matches_data(&self, data: &TrackingData) -> bool {
+        data.description
+            .as_deref()
+            .is_some_and(|description| self.is_match(description))
+            || data.notes.iter().any(|note| self.is_match(&note.text))
+    }
+}
+
+/// the start and stop of the intervals whose description or notes match. the stop is `None`
+/// for a running interval
+fn find<'a>(
+    data: &'a [TrackingEvent],
+    pattern: &Pattern,
+) -> Vec<(&'a TrackingEvent, Option<&'a TrackingEvent>)> {
+    let mut intervals = Vec::new();
+    let mut events = data.iter();
+    while let Some(start) = events.find(|e| e.is_start()) {
+        let stop = events.find(|e| e.is_stop());
+        if pattern.matches_data(start.data()) {
+            intervals.push((start, stop));
+        }
+    }
+    intervals
+}
+
+pub fn search(data: &[TrackingEvent], pattern: &Pattern) -> Result<()> {
+    let intervals = find(data, pattern);
+    if intervals.is_empty() {
+        println!("No entries found!");
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+    let mut total = Duration::zero();
+    for (start, stop) in &intervals {
+        let data = start.data();
+        let end = stop.map_or_else(Utc::now, |stop| stop.time(true));
+        let duration = end - start.time(true);
+        total += duration;
+        let until = match stop {
+            Some(stop) => locale::format_time(&stop.local_time()),
+            None => "now".to_string(),
+        };
+        let project = data
+            .project
+            .as_ref()
+            .map(|p| format!(" [{}]", p))
+            .unwrap_or_default();
+        let tags: String = data.tags.iter().map(|t| format!(" #{}", t)).collect();
+        lines.push(format!(
+            "{} - {}  {}  {}{}{}",
+            locale::format_date_time(&start.local_time()),
+            until,
+            format_hours_minutes(duration),
+            data.description.as_deref().unwrap_or_default(),
+            project,
+            tags
+        ));
+        lines.extend(notes_to_human_readable(data, 2));
+    }
+    lines.push(format!(
+        "{} matching intervals with {} in total",
+        intervals.len(),
+        format_hours_minutes(total)
+    ));
+    pager::print_lines(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let event = |description: &str, hour| {
+            TrackingData::new(
+                Some(description.to_string()),
+                Utc.ymd(2024, 3, 1).and_hms(hour, 0, 0),
+            )
+        };
+        let data = vec![
+            TrackingEvent::Start(event("Billing migration", 8)),
+            TrackingEvent::Stop(event("", 9)),
+            TrackingEvent::Start(event("review", 10)),
+            TrackingEvent::Stop(event("", 11)),
+            TrackingEvent::Start(event("billing fixes", 12)),
+        ];
+        let count = |pattern: &str, ignore_case, regex| {
+            find(&data, &Pattern::new(pattern, ignore_case, regex).unwrap()).len()
+        };
+        assert_eq!(1, count("billing", false, false));
+        assert_eq!(2, count("billing", true, false));
+        assert_eq!(1, count("^billing (migration|fixes)$", false, true));
+        assert_eq!(2, count("^billing (migration|fixes)$", true, true));
+        assert!(Pattern::new("(", false, true).is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! A small HTTP API on localhost, so browser extensions, launcher scripts or home automation can
+//! start and stop time tracking without running tt for every action. See the README for the
+//! endpoints.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::settings::Settings;
+use crate::storage::{lock, read_data_if_exists};
+use crate::{
+    filter_events, format_duration, get_time_from_events, number_events, start_tracking,
+    stop_tracking, write_changes, CalculationOptions, FilterData, TrackingEvent,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StartBody {
+    description: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    billable: Option<bool>,
+    at: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StopBody {
+    description: Option<String>,
+    at: Option<String>,
+}
+
+/// an error which is sent to the client with its status code
+struct ApiError(u16, String);
+
+impl ApiError {
+    /// an error of the server instead of the request, e.g. if the data file can't be read
+    fn internal(e: anyhow::Error) -> Self {
+        Self(500, e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(400, e.to_string())
+    }
+}
+
+/// decodes a url encoded query parameter
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let hex = |i: usize| {
+        bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+    };
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex(i)) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 2;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// splits the url into the path and the decoded query parameters
+fn parse_url(url: &str) -> (&str, Vec<(String, String)>) {
+    let (path, query) = match url.find('?') {
+        Some(i) => (&url[..i], &url[i + 1..]),
+        None => (url, ""),
+    };
+    let parameters = query
+        .split('&')
+        .filter(|parameter| !parameter.is_empty())
+        .map(|parameter| match parameter.find('=') {
+            Some(i) => (decode(&parameter[..i]), decode(&parameter[i + 1..])),
+            None => (decode(parameter), String::new()),
+        })
+        .collect();
+    (path, parameters)
+}
+
+/// the filter from the query parameters, which are named like the options of `tt list`. the
+/// repeatable `--filter` option is `description`, because `filter` is the positional filter.
+fn filter_from_query(parameters: &[(String, String)]) -> FilterData {
+    let get = |name| {
+        parameters
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+    let get_all = |name| {
+        parameters
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    };
+    FilterData {
+        from: get("from"),
+        to: get("to"),
+        filter: get("filter"),
+        project: get("project"),
+        tags: get_all("tag"),
+        descriptions: get_all("description"),
+        match_all: matches!(get("match_all").as_deref(), Some("true") | Some("1")),
+        host: get("host"),
+    }
+}
+
+fn event_json(number: usize, event: &TrackingEvent) -> Value {
+    let data = event.data();
+    json!({
+        "number": number,
+        "id": data.id,
+        "event": event.name(),
+        "time": data.local_time().to_rfc3339_opts(SecondsFormat::Secs, true),
+        "description": data.description,
+        "project": data.project,
+        "tags": data.tags,
+        "host": data.host,
+    })
+}
+
+fn status_json(settings: &Settings, data: &[TrackingEvent]) -> Value {
+    match data.last() {
+        Some(event) => {
+            let event_data = event.data();
+            json!({
+                "active": event.is_start(),
+                "event": event.name(),
+                "time": event_data.local_time().to_rfc3339_opts(SecondsFormat::Secs, true),
+                "elapsed_seconds": (Utc::now() - event_data.time).num_seconds(),
+                "description": event_data.description,
+                "project": event_data.project,
+                "tags": event_data.tags,
+                "billable": event_data.is_billable(settings),
+            })
+        }
+        None => json!({ "active": false }),
+    }
+}
+
+fn parse_body<T: Default + serde::de::DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    if body.trim().is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(body).map_err(|e| ApiError(400, format!("invalid body: {}", e)))
+}
+
+fn read(path: &str) -> Result<Vec<TrackingEvent>, ApiError> {
+    read_data_if_exists(path).map_err(ApiError::internal)
+}
+
+/// changes the data with `change` and returns the status afterwards
+fn change<F>(settings: &Settings, path: &str, change: F) -> Result<Value, ApiError>
+where
+    F: FnOnce(&mut Vec<TrackingEvent>) -> Result<()>,
+{
+    let _lock = lock(path).map_err(ApiError::internal)?;
+    let mut data = read(path)?;
+    let original_data = data.clone();
+    change(&mut data)?;
+    if data != original_data {
+        write_changes(settings, path, &original_data, &mut data, true)?;
+    }
+    Ok(status_json(settings, &data))
+}
+
+fn handle(
+    settings: &Settings,
+    path: &str,
+    method: &Method,
+    url: &str,
+    body: &str,
+) -> Result<Value, ApiError> {
+    let (endpoint, parameters) = parse_url(url);
+    match (method, endpoint) {
+        (Method::Get, "/status") => {
+            let data = read(path)?;
+            Ok(status_json(settings, &data))
+        }
+        (Method::Post, "/start") => {
+            let body: StartBody = parse_body(body)?;
+            change(settings, path, |data| {
+                start_tracking(
+                    settings,
+                    data,
+                    body.description,
+                    body.project,
+                    body.tags,
+                    body.at,
+                    body.billable,
+                    false,
+                )
+            })
+        }
+        (Method::Post, "/stop") => {
+            let body: StopBody = parse_body(body)?;
+            change(settings, path, |data| {
+                stop_tracking(data, body.description, body.at, false)
+            })
+        }
+        (Method::Get, "/list") => {
+            let data = read(path)?;
+            let filter = filter_from_query(&parameters);
+            let filtered_data = filter_events(&data, &filter, settings.week_starts_on)?;
+            let events: Vec<_> = number_events(&data, filtered_data)
+                .iter()
+                .map(|(i, event)| event_json(*i, event))
+                .collect();
+            Ok(Value::Array(events))
+        }
+        (Method::Get, "/show") => {
+            let data = read(path)?;
+            let filter = filter_from_query(&parameters);
+            let settings = settings.for_project(&filter.project);
+            let filtered_data = filter_events(&data, &filter, settings.week_starts_on)?;
+            let work_time =
+                get_time_from_events(&settings, &filtered_data, CalculationOptions::default());
+            Ok(json!({
+                "work_time": format_duration(work_time),
+                "work_seconds": work_time.num_seconds(),
+            }))
+        }
+        (_, "/status") | (_, "/start") | (_, "/stop") | (_, "/list") | (_, "/show") => {
+            Err(ApiError(405, "method not allowed".to_string()))
+        }
+        _ => Err(ApiError(404, "not found".to_string())),
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+/// whether a host (`Host` header) or an origin (`Origin` header) is this machine. other hosts
+/// mean a website got its domain resolved to localhost (dns rebinding).
+fn is_local(host_or_origin: &str) -> bool {
+    let host = host_or_origin
+        .trim_start_matches("http://")
+        .trim_start_matches("https://");
+    let host = match host.rfind(':') {
+        Some(i) if !host[i..].contains(']') => &host[..i],
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// whether the request has the token from the config. without a token, every request is allowed
+fn is_authorized(request: &Request, token: &str) -> bool {
+    token.is_empty() || header(request, "Authorization") == Some(&format!("Bearer {}", token))
+}
+
+/// rejects requests which a website in the browser could send without the user noticing
+fn check_request(request: &Request, token: &str) -> Result<(), ApiError> {
+    if matches!(header(request, "Host"), Some(host) if !is_local(host)) {
+        return Err(ApiError(403, "forbidden host".to_string()));
+    }
+    if !is_authorized(request, token) {
+        return Err(ApiError(401, "unauthorized".to_string()));
+    }
+    // with a token, a website can't send the Authorization header, but other origins (e.g.
+    // browser extensions) are allowed
+    let origin = header(request, "Origin");
+    if token.is_empty() && matches!(origin, Some(origin) if !is_local(origin)) {
+        return Err(ApiError(403, "forbidden origin".to_string()));
+    }
+    // other content types can be sent by html forms without a preflight request
+    let is_json = matches!(
+        header(request, "Content-Type"),
+        Some(content_type) if content_type.starts_with("application/json")
+    );
+    if *request.method() == Method::Post && !is_json {
+        return Err(ApiError(
+            415,
+            "the content type has to be application/json".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn respond(mut request: Request, settings: &Settings, path: &str) -> Result<()> {
+    let result = check_request(&request, &settings.serve.token).and_then(|_| {
+        let mut body = String::new();
+        match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(settings, path, request.method(), request.url(), &body),
+            Err(e) => Err(ApiError(400, e.to_string())),
+        }
+    });
+    let (status, value) = match result {
+        Ok(value) => (200, value),
+        Err(ApiError(status, message)) => (status, json!({ "error": message })),
+    };
+    let content_type =
+        Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("invalid header");
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    Ok(request.respond(response)?)
+}
+
+/// answers requests until it gets killed. only listens on localhost, so the data is not
+/// reachable from other machines.
+pub fn serve(settings: &Settings, path: &str, port: Option<u16>) -> Result<()> {
+    let address = ("127.0.0.1", port.unwrap_or(settings.serve.port));
+    let server = Server::http(address).map_err(|e| anyhow!("could not listen: {}", e))?;
+    println!("Listening on http://{}:{}", address.0, address.1);
+    for request in server.incoming_requests() {
+        if let Err(e) = respond(request, settings, path) {
+            eprintln!("Could not answer request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        let (path, parameters) = parse_url("/list?filter=week&tag=a%20b&tag=c+d&project=");
+        assert_eq!("/list", path);
+        assert_eq!(
+            vec![
+                ("filter".to_string(), "week".to_string()),
+                ("tag".to_string(), "a b".to_string()),
+                ("tag".to_string(), "c d".to_string()),
+                ("project".to_string(), String::new()),
+            ],
+            parameters
+        );
+        let filter = filter_from_query(&parameters);
+        assert_eq!(Some("week".to_string()), filter.filter);
+        assert_eq!(vec!["a b", "c d"], filter.tags);
+        assert_eq!(("/status", Vec::new()), parse_url("/status"));
+        assert_eq!("100%", decode("100%"));
+    }
+
+    #[test]
+    fn test_is_local() {
+        assert!(is_local("localhost:7878"));
+        assert!(is_local("127.0.0.1"));
+        assert!(is_local("http://127.0.0.1:7878"));
+        assert!(is_local("[::1]:7878"));
+        assert!(!is_local("example.com:7878"));
+        assert!(!is_local("https://localhost.example.com"));
+        assert!(!is_local("null"));
+    }
+}
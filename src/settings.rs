@@ -1,29 +1,378 @@
-use config::{Config, ConfigError, Environment, File, FileFormat};
-use chrono::Weekday;
-use serde::Deserialize;
+use config::{Config, ConfigError, File, FileFormat};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Deserializer};
 
+use std::collections::HashMap;
 use std::path::Path;
 
-#[derive(Debug, Deserialize)]
-pub struct Time {
-    pub hours: u8,
-    pub minutes: u8,
+use crate::absence::{Absence, AbsenceKind};
+use crate::clock_jump::ClockJumps;
+use crate::color::ColorChoice;
+use crate::natural_time::parse_duration_setting;
+use crate::window::DailyWindow;
+
+/// a duration in the config, either as text like "7h30m", "45m" or "8:00", as a number of minutes,
+/// or in the older form of a table with hours and minutes
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationSetting {
+    Text(String),
+    Minutes(u32),
+    HoursAndMinutes {
+        hours: u32,
+        #[serde(default)]
+        minutes: u32,
+    },
+}
+
+impl DurationSetting {
+    fn to_duration<E: serde::de::Error>(&self) -> Result<Duration, E> {
+        match self {
+            Self::Text(text) => parse_duration_setting(text).ok_or_else(|| {
+                E::custom(format!(
+                    "invalid duration \"{}\", expected a duration like \"7h30m\", \"45m\" or \"8:00\"",
+                    text
+                ))
+            }),
+            Self::Minutes(minutes) => Ok(Duration::minutes(i64::from(*minutes))),
+            Self::HoursAndMinutes { hours, minutes } => {
+                Ok(Duration::hours(i64::from(*hours)) + Duration::minutes(i64::from(*minutes)))
+            }
+        }
+    }
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    DurationSetting::deserialize(deserializer)?.to_duration()
+}
+
+fn deserialize_optional_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    Option::<DurationSetting>::deserialize(deserializer)?
+        .map(|duration| duration.to_duration())
+        .transpose()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TimeGoal {
-    pub daily: Time,
-    pub weekly: Time,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub daily: Duration,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub weekly: Duration,
+    /// the goals of single weekdays, which replace the daily goal on them
+    #[serde(default, deserialize_with = "deserialize_per_day")]
+    pub per_day: HashMap<Weekday, Duration>,
+}
+
+/// reads goals like `fri = "6h"`. "0" is a goal of zero
+fn deserialize_per_day<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<Weekday, Duration>, D::Error> {
+    HashMap::<Weekday, DurationSetting>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(day, goal)| Ok((day, goal.to_duration()?)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rounding {
+    pub interval: u32,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    /// rounds the duration to a multiple of the configured interval
+    pub fn apply(&self, duration: Duration) -> Duration {
+        if self.interval == 0 {
+            return duration;
+        }
+        let interval = i64::from(self.interval) * 60;
+        let seconds = duration.num_seconds();
+        let intervals = match self.mode {
+            RoundingMode::Nearest => (seconds + interval / 2) / interval,
+            RoundingMode::Up => (seconds + interval - 1) / interval,
+            RoundingMode::Down => seconds / interval,
+        };
+        Duration::seconds(intervals * interval)
+    }
+}
+
+/// reads a duration setting which "" or "0" turns off. `name` and `example` are shown if it isn't
+/// a duration
+fn deserialize_duration_or_off<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    name: &str,
+    example: &str,
+) -> Result<Option<Duration>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    match value.trim() {
+        "" | "0" => Ok(None),
+        duration => parse_duration_setting(duration).map(Some).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid {} \"{}\", expected a duration like \"{}\"",
+                name, duration, example
+            ))
+        }),
+    }
+}
+
+fn deserialize_ignore_intervals_shorter_than<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_duration_or_off(deserializer, "ignore_intervals_shorter_than", "1m")
+}
+
+fn deserialize_split_intervals_longer_than<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_duration_or_off(deserializer, "split_intervals_longer_than", "12h")
+}
+
+/// the threshold for the reminder to start tracking
+fn deserialize_remind_after<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_duration_or_off(deserializer, "remind_after", "15m")
+}
+
+/// the work time after which to remind to take a break
+fn deserialize_break_reminder_after<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_duration_or_off(deserializer, "break_reminder_after", "90m")
+}
+
+fn deserialize_daily_window<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DailyWindow, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Watch {
+    pub idle_minutes: u32,
+    pub ask_on_return: bool,
+    pub idle_command: String,
+    #[serde(deserialize_with = "deserialize_remind_after")]
+    pub remind_after: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_daily_window")]
+    pub work_hours: DailyWindow,
+    #[serde(deserialize_with = "deserialize_break_reminder_after")]
+    pub break_reminder_after: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub break_reminder_snooze: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pomodoro {
+    pub work_minutes: u32,
+    pub break_minutes: u32,
+    pub cycles: u32,
+    pub notify: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Serve {
+    pub port: u16,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Statusbar {
+    pub format: String,
+    pub running_color: String,
+    pub paused_color: String,
+    pub stopped_color: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sync {
+    pub remote: String,
+    pub branch: String,
+    pub auto: bool,
+    #[cfg_attr(not(feature = "rest-sync"), allow(dead_code))]
+    pub url: String,
+    #[cfg_attr(not(feature = "rest-sync"), allow(dead_code))]
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StrictMode {
+    pub enabled: bool,
+    pub max_hours_in_past: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Overtime {
+    #[serde(default)]
+    pub start: Option<NaiveDate>,
+    pub count_days_off: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Billing {
+    pub rate: f64,
+    pub currency: String,
+}
+
+#[cfg_attr(not(feature = "push"), allow(dead_code))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Clockify {
+    pub api_key: String,
+    pub workspace_id: String,
+    /// the clockify project ids of the tt projects
+    #[serde(default)]
+    pub projects: HashMap<String, String>,
+}
+
+#[cfg_attr(not(feature = "push"), allow(dead_code))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Harvest {
+    pub account_id: String,
+    pub token: String,
+    pub project_id: u64,
+    pub task_id: u64,
+    /// the harvest project ids of the tt projects, which override `project_id`
+    #[serde(default)]
+    pub projects: HashMap<String, u64>,
+}
+
+/// only used with the push feature
+#[cfg_attr(not(feature = "push"), allow(dead_code))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Push {
+    pub clockify: Clockify,
+    pub harvest: Harvest,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Backup {
+    pub keep: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cron {
+    pub args: Vec<String>,
+    pub day: Weekday,
+    pub time: String,
+    pub output: String,
+    pub mail_to: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Encryption {
+    pub enabled: bool,
+    pub key_file: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Git {
+    pub repositories: Vec<String>,
+    pub use_ticket: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hooks {
+    pub on_start: String,
+    pub on_stop: String,
+    pub on_goal_reached: String,
+}
+
+/// overrides of the global settings for a single project
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectSettings {
+    #[serde(default)]
+    pub time_goal: ProjectTimeGoal,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub min_daily_break: Option<Duration>,
+    pub rounding: Option<Rounding>,
+    pub billable: Option<bool>,
+    pub rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProjectTimeGoal {
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub daily: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub weekly: Option<Duration>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub data_file: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     pub data_file: String,
     pub auto_insert_stop: bool,
+    pub clock_jumps: ClockJumps,
     pub enable_project_settings: bool,
     pub time_goal: TimeGoal,
-    pub min_daily_break: u8,
-    pub last_day_of_work_week: Weekday,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub min_daily_break: Duration,
+    pub max_session_hours: u32,
+    #[serde(deserialize_with = "deserialize_ignore_intervals_shorter_than")]
+    pub ignore_intervals_shorter_than: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_split_intervals_longer_than")]
+    pub split_intervals_longer_than: Option<Duration>,
+    pub include_seconds: bool,
+    pub millisecond_timestamps: bool,
+    pub auto_stop_at: String,
+    pub billable: bool,
+    pub host: String,
+    pub record_host: bool,
+    pub record_user: bool,
+    pub timezone: String,
+    pub locale: String,
+    pub date_format: String,
+    pub time_format: String,
+    pub color: ColorChoice,
+    pub week_starts_on: Weekday,
+    pub work_days: Vec<Weekday>,
+    #[serde(default)]
+    pub last_day_of_work_week: Option<Weekday>,
+    pub holidays: Vec<NaiveDate>,
+    /// read from the absence file of the data file, not from the config
+    #[serde(skip)]
+    pub absences: Vec<Absence>,
+    /// the arguments of the command, which are recorded in the audit log of strict mode
+    #[serde(skip)]
+    pub args: Vec<String>,
+    pub prompt_format: String,
+    pub status_goals: bool,
+    pub rounding: Rounding,
+    pub watch: Watch,
+    pub git: Git,
+    pub hooks: Hooks,
+    pub backup: Backup,
+    pub cron: Cron,
+    pub encryption: Encryption,
+    pub sync: Sync,
+    pub pomodoro: Pomodoro,
+    pub serve: Serve,
+    pub statusbar: Statusbar,
+    pub overtime: Overtime,
+    pub strict_mode: StrictMode,
+    pub billing: Billing,
+    #[cfg_attr(not(feature = "push"), allow(dead_code))]
+    pub push: Push,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    #[serde(default)]
+    pub projects: HashMap<String, ProjectSettings>,
 }
 
 fn add_file_if_exists(s: &mut Config, file: &str) -> Result<bool, ConfigError> {
@@ -36,12 +385,183 @@ fn add_file_if_exists(s: &mut Config, file: &str) -> Result<bool, ConfigError> {
     Ok(result)
 }
 
+/// the directory containing the global config file. `~/.config/timetracking` is still used if it
+/// exists, otherwise it's the config directory of the platform, e.g. `%APPDATA%\timetracking` on
+/// windows or `~/Library/Application Support/timetracking` on macOS.
+pub fn config_dir() -> String {
+    let old_dir = shellexpand::full("~/.config/timetracking")
+        .expect("could not expand path")
+        .to_string();
+    match dirs::config_dir() {
+        Some(dir) if !Path::new(&old_dir).exists() => {
+            path_to_string_lossy(dir.join("timetracking"))
+        }
+        _ => old_dir,
+    }
+}
+
+/// the name of the data file in the data directory
+#[cfg(feature = "binary")]
+pub const DATA_FILE_NAME: &str = "timetracking.bin";
+#[cfg(not(feature = "binary"))]
+pub const DATA_FILE_NAME: &str = "timetracking.json";
+
+/// the data file older versions used when the `data_file` setting was empty
+pub fn old_default_data_file() -> String {
+    shellexpand::full(&format!("~/{}", DATA_FILE_NAME))
+        .expect("could not expand path")
+        .to_string()
+}
+
+/// the data file used when the `data_file` setting is empty, in the data directory of the
+/// platform, e.g. `$XDG_DATA_HOME/timetracking` on linux. the data file of older versions in the
+/// home directory is moved there, but still used if that failed.
+pub fn default_data_file() -> String {
+    let old_file = old_default_data_file();
+    match dirs::data_dir() {
+        Some(dir) if !Path::new(&old_file).exists() => {
+            path_to_string_lossy(dir.join("timetracking").join(DATA_FILE_NAME))
+        }
+        _ => old_file,
+    }
+}
+
+/// the first day of the week containing `date`, for weeks starting on `week_starts_on`
+pub fn start_of_week(date: NaiveDate, week_starts_on: Weekday) -> NaiveDate {
+    let offset =
+        (7 + date.weekday().num_days_from_monday() - week_starts_on.num_days_from_monday()) % 7;
+    date - Duration::days(i64::from(offset))
+}
+
 fn path_to_string_lossy<P: AsRef<Path>>(path: P) -> String {
     path.as_ref().to_string_lossy().to_string()
 }
 
 impl Settings {
+    /// the `data_file` setting or the default data file, if it's empty
+    pub fn data_file(&self) -> String {
+        if self.data_file.is_empty() {
+            default_data_file()
+        } else {
+            self.data_file.clone()
+        }
+    }
+
+    /// the first day of the week containing `date`
+    pub fn start_of_week(&self, date: NaiveDate) -> NaiveDate {
+        start_of_week(date, self.week_starts_on)
+    }
+
+    pub fn is_work_day(&self, day: Weekday) -> bool {
+        self.work_days.contains(&day)
+    }
+
+    /// the last work day of the week. if `last_day_of_work_week` is not set, this is the last of
+    /// the `work_days` counted from `week_starts_on`
+    pub fn last_day_of_work_week(&self) -> Weekday {
+        self.last_day_of_work_week.unwrap_or_else(|| {
+            let mut day = self.week_starts_on.pred();
+            while !self.is_work_day(day) && day != self.week_starts_on {
+                day = day.pred();
+            }
+            day
+        })
+    }
+
+    /// the settings with the overrides of the given project applied
+    pub fn for_project(&self, project: &Option<String>) -> Settings {
+        let mut settings = self.clone();
+        if let Some(overrides) = project.as_ref().and_then(|p| self.projects.get(p)) {
+            if let Some(daily) = overrides.time_goal.daily {
+                // the daily goal of the project applies to every day
+                settings.time_goal.daily = daily;
+                settings.time_goal.per_day.clear();
+            }
+            if let Some(weekly) = overrides.time_goal.weekly {
+                settings.time_goal.weekly = weekly;
+            }
+            if let Some(min_daily_break) = overrides.min_daily_break {
+                settings.min_daily_break = min_daily_break;
+            }
+            if let Some(rounding) = &overrides.rounding {
+                settings.rounding = rounding.clone();
+            }
+        }
+        settings
+    }
+
+    /// the daily goal or the goal of the weekday, which is zero on days that are not work days
+    pub fn daily_goal(&self, day: Weekday) -> Duration {
+        if self.is_work_day(day) {
+            self.time_goal
+                .per_day
+                .get(&day)
+                .copied()
+                .unwrap_or(self.time_goal.daily)
+        } else {
+            Duration::zero()
+        }
+    }
+
+    /// the kind of absence on the date. the holidays from the config count as holidays
+    pub fn absence_on(&self, date: NaiveDate) -> Option<AbsenceKind> {
+        if self.holidays.contains(&date) {
+            return Some(AbsenceKind::Holiday);
+        }
+        self.absences
+            .iter()
+            .find(|absence| absence.date == date)
+            .map(|absence| absence.kind)
+    }
+
+    /// the daily goal for the date, which is zero on holidays, absent days and days that are not
+    /// work days
+    pub fn goal_for_date(&self, date: NaiveDate) -> Duration {
+        if self.absence_on(date).is_some() {
+            Duration::zero()
+        } else {
+            self.daily_goal(date.weekday())
+        }
+    }
+
+    /// the weekly goal for the week containing `date`, reduced by the daily goal of every
+    /// holiday and absent day in it
+    pub fn weekly_goal_for_date(&self, date: NaiveDate) -> Duration {
+        let start = self.start_of_week(date);
+        (0..7)
+            .map(|i| start + Duration::days(i))
+            .fold(self.time_goal.weekly, |goal, day| {
+                goal - (self.daily_goal(day.weekday()) - self.goal_for_date(day))
+            })
+            .max(Duration::zero())
+    }
+
+    /// the settings from the default config, without any config file or environment variable
+    #[cfg(test)]
+    pub fn from_default_config() -> Self {
+        let mut s = Config::new();
+        s.merge(File::from_str(
+            include_str!("../default_config.toml"),
+            FileFormat::Toml,
+        ))
+        .unwrap();
+        s.try_into().unwrap()
+    }
+
     pub fn new(config_file: &Option<String>) -> Result<Self, ConfigError> {
+        let current_dir = std::env::current_dir().expect("Could not get current directory");
+        let mut settings = Self::load(config_file, &current_dir, std::env::vars())?;
+        settings.args = std::env::args().skip(1).collect();
+        Ok(settings)
+    }
+
+    /// the settings of a command run in `dir` with the environment variables `vars`, e.g. of the
+    /// command which sent a request to the daemon
+    pub fn load(
+        config_file: &Option<String>,
+        dir: &Path,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
         let mut s = Config::new();
 
         // Start off by merging in the "default" configuration file
@@ -50,20 +570,11 @@ impl Settings {
             config::FileFormat::Toml,
         ))?;
 
-        #[cfg(not(feature = "binary"))]
-        s.merge(File::from_str(
-            include_str!("../default_config_development.toml"),
-            config::FileFormat::Toml,
-        ))?;
-
-        let config_path = shellexpand::full("~/.config/timetracking/config.toml")
-            .expect("could not expand path")
-            .to_string();
+        let config_path = format!("{}/config.toml", config_dir());
         s.merge(File::with_name(config_path.as_str()).required(false))?;
 
         if s.get_bool("enable_project_settings")? {
-            let current_dir = std::env::current_dir().expect("Could not get current directory");
-            let mut path = current_dir.as_path();
+            let mut path = dir;
             if !add_file_if_exists(
                 &mut s,
                 &format!("{}/timetracking.project.toml", path_to_string_lossy(&path)),
@@ -80,9 +591,18 @@ impl Settings {
             }
         }
 
-        s.merge(File::with_name(".timetracking.config").required(false))?;
+        let local_config = dir.join(".timetracking.config");
+        s.merge(File::with_name(&path_to_string_lossy(&local_config)).required(false))?;
 
-        s.merge(Environment::with_prefix("tt"))?;
+        // like `Environment::with_prefix("tt")`, but with the given variables
+        let mut environment = Config::new();
+        for (key, value) in vars {
+            if let Some(key) = key.to_lowercase().strip_prefix("tt_") {
+                // a name which isn't a valid key can't be a setting either
+                let _ = environment.set(key, value);
+            }
+        }
+        s.merge(environment)?;
 
         if let Some(config_file) = config_file {
             if !add_file_if_exists(&mut s, config_file)? {
@@ -91,16 +611,81 @@ impl Settings {
             }
         }
 
-        let daily_hours = s.get_int("time_goal.daily.hours")?;
-        s.set("time_goal.daily.hours", daily_hours.min(24))?;
-        let daily_minutes = s.get_int("time_goal.daily.minutes")?;
-        s.set("time_goal.daily.minutes", daily_minutes.min(59))?;
-        let weekly_hours = s.get_int("time_goal.weekly.hours")?;
-        s.set("time_goal.weekly.hours", weekly_hours.min(168))?;
-        let weekly_minutes = s.get_int("time_goal.weekly.minutes")?;
-        s.set("time_goal.weekly.minutes", weekly_minutes.min(59))?;
-
         // You can deserialize (and thus freeze) the entire configuration as
-        s.try_into()
+        let mut settings: Settings = s.try_into()?;
+        settings.time_goal.daily = settings.time_goal.daily.min(Duration::hours(24));
+        settings.time_goal.weekly = settings.time_goal.weekly.min(Duration::hours(168));
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_of_week() {
+        let thursday = NaiveDate::from_ymd(2021, 3, 4);
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 3, 1),
+            start_of_week(thursday, Weekday::Mon)
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 2, 28),
+            start_of_week(thursday, Weekday::Sun)
+        );
+        assert_eq!(thursday, start_of_week(thursday, Weekday::Thu));
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 2, 26),
+            start_of_week(thursday, Weekday::Fri)
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        let rounding = |mode| Rounding { interval: 15, mode };
+        let duration = Duration::minutes(22);
+        assert_eq!(
+            Duration::minutes(15),
+            rounding(RoundingMode::Nearest).apply(duration)
+        );
+        assert_eq!(
+            Duration::minutes(30),
+            rounding(RoundingMode::Up).apply(duration)
+        );
+        assert_eq!(
+            Duration::minutes(15),
+            rounding(RoundingMode::Down).apply(duration)
+        );
+        assert_eq!(
+            Duration::minutes(30),
+            rounding(RoundingMode::Nearest).apply(Duration::minutes(23))
+        );
+        assert_eq!(
+            duration,
+            Rounding {
+                interval: 0,
+                mode: RoundingMode::Up
+            }
+            .apply(duration)
+        );
+    }
+
+    #[test]
+    fn test_interval_limit() {
+        let limit = |value: &str| deserialize_split_intervals_longer_than(serde_json::json!(value));
+        assert_eq!(Some(Duration::hours(12)), limit("12h").unwrap());
+        assert_eq!(Some(Duration::seconds(90)), limit("1m 30s").unwrap());
+        assert_eq!(None, limit("").unwrap());
+        assert_eq!(None, limit("0").unwrap());
+        assert!(limit("soon").is_err());
+    }
+
+    #[test]
+    fn test_per_day_goals() {
+        let goals = deserialize_per_day(serde_json::json!({ "fri": "6h", "sat": "0" })).unwrap();
+        assert_eq!(Some(&Duration::hours(6)), goals.get(&Weekday::Fri));
+        assert_eq!(Some(&Duration::zero()), goals.get(&Weekday::Sat));
+        assert!(deserialize_per_day(serde_json::json!({ "fri": "short" })).is_err());
     }
 }
@@ -0,0 +1,238 @@
+//! A hook which stops time tracking when the computer goes to sleep, so the time it sleeps isn't
+//! tracked. The next call of tt after waking up asks whether to continue. The hook is a systemd
+//! service which runs before `sleep.target` on linux and a launchd agent running sleepwatcher on
+//! macOS, which both run `tt hook sleep`.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use crate::cron::{escape_xml, home_dir, run_command, systemd_quote};
+use crate::{TrackingData, TrackingEvent};
+
+/// the description of the stop event inserted by the hook
+const DESCRIPTION: &str = "sleep";
+
+/// the label of the launchd agent
+const LAUNCHD_LABEL: &str = "com.github.hardliner66.timetracking.sleep";
+
+/// exists from going to sleep until the question whether to continue was answered
+fn marker_path(data_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.sleep", data_path))
+}
+
+/// the command line of the hook: this executable with the global options of the current call
+fn hook_command(global_args: &[String]) -> Result<Vec<String>> {
+    let exe = std::env::current_exe()?;
+    let mut command = vec![exe.to_string_lossy().to_string()];
+    command.extend(global_args.iter().cloned());
+    command.extend(vec!["hook".to_string(), "sleep".to_string()]);
+    Ok(command)
+}
+
+/// the user the hook runs as. the systemd service has to be installed with sudo.
+fn user() -> Result<String> {
+    std::env::var("SUDO_USER")
+        .or_else(|_| std::env::var("USER"))
+        .map_err(|_| anyhow!("could not find out the user name"))
+}
+
+fn systemd_path(user: &str) -> PathBuf {
+    PathBuf::from(format!("/etc/systemd/system/tt-sleep-{}.service", user))
+}
+
+fn install_systemd(command: &[String]) -> Result<()> {
+    let user = user()?;
+    let path = systemd_path(&user);
+    let exec_start: Vec<_> = command.iter().map(|arg| systemd_quote(arg)).collect();
+    let unit = format!(
+        "[Unit]\nDescription=Stop time tracking before the system sleeps\nBefore=sleep.target\n\n[Service]\nType=oneshot\nUser={}\nExecStart={}\n\n[Install]\nWantedBy=sleep.target\n",
+        user,
+        exec_start.join(" ")
+    );
+    std::fs::write(&path, unit).map_err(|e| {
+        anyhow!(
+            "could not write \"{}\" ({}). The sleep hook has to be installed with sudo.",
+            path.display(),
+            e
+        )
+    })?;
+    run_command("systemctl", &["daemon-reload"])?;
+    run_command("systemctl", &["enable", &path.to_string_lossy()])
+}
+
+fn uninstall_systemd() -> Result<()> {
+    let path = systemd_path(&user()?);
+    if !path.exists() {
+        return Err(anyhow!("The sleep hook is not installed!"));
+    }
+    run_command("systemctl", &["disable", &path.to_string_lossy()])?;
+    std::fs::remove_file(&path)?;
+    run_command("systemctl", &["daemon-reload"])
+}
+
+fn launchd_path() -> Result<PathBuf> {
+    Ok(home_dir()?.join(format!("Library/LaunchAgents/{}.plist", LAUNCHD_LABEL)))
+}
+
+/// quotes an argument for the shell, which runs the command given to sleepwatcher
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn install_launchd(command: &[String]) -> Result<()> {
+    let output = std::process::Command::new("sh")
+        .args(["-c", "command -v sleepwatcher"])
+        .output()?;
+    let sleepwatcher = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if sleepwatcher.is_empty() {
+        return Err(anyhow!(
+            "Could not find sleepwatcher, please install it first, e.g. with `brew install sleepwatcher`."
+        ));
+    }
+    let path = launchd_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let command: Vec<_> = command.iter().map(|arg| shell_quote(arg)).collect();
+    std::fs::write(
+        &path,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>--sleep</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            LAUNCHD_LABEL,
+            escape_xml(&sleepwatcher),
+            escape_xml(&command.join(" "))
+        ),
+    )?;
+    run_command("launchctl", &["load", "-w", &path.to_string_lossy()])
+}
+
+fn uninstall_launchd() -> Result<()> {
+    let path = launchd_path()?;
+    if !path.exists() {
+        return Err(anyhow!("The sleep hook is not installed!"));
+    }
+    run_command("launchctl", &["unload", "-w", &path.to_string_lossy()])?;
+    Ok(std::fs::remove_file(path)?)
+}
+
+/// registers the hook with the system. `global_args` are passed on to the hook, so it uses the
+/// same config and data file.
+pub fn install(global_args: &[String]) -> Result<()> {
+    let command = hook_command(global_args)?;
+    if cfg!(windows) {
+        return Err(anyhow!("The sleep hook is not supported on windows!"));
+    } else if cfg!(target_os = "macos") {
+        install_launchd(&command)?;
+    } else {
+        install_systemd(&command)?;
+    }
+    println!("Installed the sleep hook. Time tracking stops when the computer goes to sleep.");
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    if cfg!(windows) {
+        return Err(anyhow!("The sleep hook is not supported on windows!"));
+    } else if cfg!(target_os = "macos") {
+        uninstall_launchd()?;
+    } else {
+        uninstall_systemd()?;
+    }
+    println!("Removed the sleep hook.");
+    Ok(())
+}
+
+/// stops time tracking before the computer sleeps. returns whether the data was changed.
+pub fn sleep(data: &mut Vec<TrackingEvent>, data_path: &str) -> Result<bool> {
+    if !matches!(data.last(), Some(event) if event.is_start()) {
+        return Ok(false);
+    }
+    data.push(TrackingEvent::Stop(TrackingData::new(
+        Some(DESCRIPTION.to_string()),
+        Local::now().into(),
+    )));
+    std::fs::write(marker_path(data_path), "")?;
+    Ok(true)
+}
+
+/// after waking up, asks whether to continue the entry which was stopped by the hook. commands
+/// which start or stop tracking on their own don't `ask`. returns whether the data was changed.
+pub fn check_wake(data: &mut Vec<TrackingEvent>, data_path: &str, ask: bool) -> Result<bool> {
+    let marker = marker_path(data_path);
+    if !marker.exists() {
+        return Ok(false);
+    }
+    let stop = match data.last() {
+        Some(TrackingEvent::Stop(stop)) if stop.description.as_deref() == Some(DESCRIPTION) => {
+            stop.time
+        }
+        _ => {
+            std::fs::remove_file(&marker)?;
+            return Ok(false);
+        }
+    };
+    if !ask {
+        std::fs::remove_file(&marker)?;
+        return Ok(false);
+    }
+    if !io::stdin().is_terminal() {
+        // asked by the next call in a terminal
+        return Ok(false);
+    }
+    let last_start = match data.iter().rev().find(|event| event.is_start()) {
+        Some(start) => start.data().clone(),
+        None => return Ok(false),
+    };
+    println!(
+        "Time tracking{} was stopped at {}, when the computer went to sleep. Continue it now? (y|n) [default: y]: ",
+        last_start
+            .description
+            .as_ref()
+            .map(|description| format!(" of \"{}\"", description))
+            .unwrap_or_default(),
+        stop.with_timezone(&Local).format("%H:%M")
+    );
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    std::fs::remove_file(&marker)?;
+    if matches!(input.trim(), "" | "y" | "yes") {
+        data.push(TrackingEvent::Start(
+            last_start.with_time(Local::now().into()),
+        ));
+        println!("Time tracking continues.");
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!("'tt'", shell_quote("tt"));
+        assert_eq!("'/it'\\''s/tt'", shell_quote("/it's/tt"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Statistics about the tracked time, like the average work day and the trend of the last weeks.
+
+use anyhow::Result;
+use chrono::{prelude::*, Duration};
+
+use crate::report::{sum_by_key, SummaryKey};
+use crate::settings::Settings;
+use crate::{
+    filter_events, format_duration, get_data_as_days, summarize_day, CalculationOptions,
+    FilterData, TrackingEvent,
+};
+
+/// the bars of the sparkline, from the lowest to the highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// a bar per value, scaled to the highest value
+fn sparkline(values: &[Duration]) -> String {
+    let max = values.iter().max().copied().unwrap_or_else(Duration::zero);
+    values
+        .iter()
+        .map(|value| {
+            if max <= Duration::zero() {
+                BARS[0]
+            } else {
+                let level = value.num_seconds() * (BARS.len() as i64 - 1) / max.num_seconds();
+                BARS[level.max(0) as usize]
+            }
+        })
+        .collect()
+}
+
+/// the median of the times of day
+fn median_time(mut times: Vec<NaiveTime>) -> Option<NaiveTime> {
+    times.sort();
+    let middle = times.len() / 2;
+    match times.len() {
+        0 => None,
+        length if length % 2 == 1 => Some(times[middle]),
+        _ => Some(times[middle - 1] + (times[middle] - times[middle - 1]) / 2),
+    }
+}
+
+/// the longest interval between a start and the following stop
+fn longest_session(
+    data: &[TrackingEvent],
+    include_seconds: bool,
+) -> Option<(Duration, &TrackingEvent)> {
+    let mut longest: Option<(Duration, &TrackingEvent)> = None;
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let stop = data_iterator
+            .find(|e| e.is_stop())
+            .map_or_else(Utc::now, |stop| stop.time(include_seconds));
+        let duration = stop - start.time(include_seconds);
+        match longest {
+            Some((longest, _)) if longest >= duration => {}
+            _ => longest = Some((duration, start)),
+        }
+    }
+    longest
+}
+
+/// the work time of each of the last `weeks` weeks, the current week last
+fn weekly_totals(
+    settings: &Settings,
+    days: &[(NaiveDate, Duration)],
+    weeks: usize,
+) -> Vec<Duration> {
+    let current_week = settings.start_of_week(Local::today().naive_local());
+    (0..weeks)
+        .rev()
+        .map(|week| {
+            let start = current_week - Duration::weeks(week as i64);
+            days.iter()
+                .filter(|(date, _)| settings.start_of_week(*date) == start)
+                .fold(Duration::zero(), |total, (_, work_time)| total + *work_time)
+        })
+        .collect()
+}
+
+pub fn stats(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    weeks: usize,
+    options: CalculationOptions,
+) -> Result<()> {
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let mut days = Vec::new();
+    let mut start_times = Vec::new();
+    for day in get_data_as_days(&filtered_data) {
+        let date = match day.first() {
+            Some(event) => event.local_date(),
+            None => continue,
+        };
+        let summary = summarize_day(settings, &day, options);
+        if summary.work_time <= Duration::zero() {
+            continue;
+        }
+        if let Some(first_start) = summary.first_start {
+            start_times.push(first_start.with_timezone(&Local).time());
+        }
+        days.push((date, summary.work_time));
+    }
+    if days.is_empty() {
+        println!("No tracked time.");
+        return Ok(());
+    }
+
+    let total = days
+        .iter()
+        .fold(Duration::zero(), |total, (_, work_time)| total + *work_time);
+    println!("Tracked Days:           {}", days.len());
+    println!(
+        "Average Daily Hours:    {}",
+        format_duration(total / days.len() as i32)
+    );
+    if let Some(median) = median_time(start_times) {
+        println!("Median Start Time:      {}", median.format("%H:%M"));
+    }
+    if let Some((duration, start)) = longest_session(&filtered_data, options.include_seconds) {
+        println!(
+            "Longest Session:        {} on {} ({})",
+            format_duration(duration),
+            start.local_date().format("%Y-%m-%d"),
+            start.description().unwrap_or_else(|| "-".to_string())
+        );
+    }
+    let totals = sum_by_key(settings, &filtered_data, SummaryKey::Description, options);
+    if let Some((description, duration, _)) = totals.first() {
+        println!(
+            "Most Tracked:           {} ({})",
+            description,
+            format_duration(*duration)
+        );
+    }
+    // the weekday with the most work on average, so days with many entries don't count twice
+    let busiest = std::iter::successors(Some(settings.week_starts_on), |day| Some(day.succ()))
+        .take(7)
+        .filter_map(|weekday| {
+            let work_times: Vec<_> = days
+                .iter()
+                .filter(|(date, _)| date.weekday() == weekday)
+                .collect();
+            let (date, _) = work_times.first()?;
+            let sum = work_times
+                .iter()
+                .fold(Duration::zero(), |total, (_, work_time)| total + *work_time);
+            Some((*date, sum / work_times.len() as i32))
+        })
+        .max_by_key(|(_, average)| *average);
+    if let Some((date, average)) = busiest {
+        println!(
+            "Busiest Weekday:        {} ({} on average)",
+            date.format("%A"),
+            format_duration(average)
+        );
+    }
+    if weeks > 0 {
+        let weekly = weekly_totals(settings, &days, weeks);
+        let max = weekly.iter().max().copied().unwrap_or_else(Duration::zero);
+        println!(
+            "{:<24}{} (max {})",
+            format!("Last {} Weeks:", weeks),
+            sparkline(&weekly),
+            format_duration(max)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        let hours = |hours: &[i64]| {
+            hours
+                .iter()
+                .map(|h| Duration::hours(*h))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!("▁▄█", sparkline(&hours(&[0, 20, 40])));
+        assert_eq!("▁▁", sparkline(&hours(&[0, 0])));
+        assert_eq!(
+            Some(NaiveTime::from_hms(8, 30, 0)),
+            median_time(vec![
+                NaiveTime::from_hms(9, 0, 0),
+                NaiveTime::from_hms(8, 0, 0),
+            ])
+        );
+    }
+}
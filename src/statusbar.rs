@@ -0,0 +1,182 @@
+//! Output for the custom modules of status bars, which poll `tt statusbar` every few seconds.
+//! Like `tt prompt`, it only reads the end of the data file.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde_json::json;
+
+use std::str::FromStr;
+
+use crate::settings::Settings;
+use crate::{eta, format_hours_minutes, CurrentSession, SessionState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusbarStyle {
+    Waybar,
+    I3blocks,
+    Polybar,
+}
+
+impl FromStr for StatusbarStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "waybar" => Ok(Self::Waybar),
+            "i3blocks" => Ok(Self::I3blocks),
+            "polybar" => Ok(Self::Polybar),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: waybar, i3blocks, polybar",
+                s
+            )),
+        }
+    }
+}
+
+fn color(settings: &Settings, state: SessionState) -> &str {
+    match state {
+        SessionState::Running => &settings.statusbar.running_color,
+        SessionState::Paused => &settings.statusbar.paused_color,
+        SessionState::Stopped => &settings.statusbar.stopped_color,
+    }
+}
+
+fn tooltip(session: &CurrentSession) -> String {
+    let mut lines = vec![format!("Today: {}", format_hours_minutes(session.today))];
+    if let Some(eta) = session.eta.filter(|eta| *eta > Utc::now()) {
+        lines.push(format!("Leave at: {}", eta::format(eta)));
+    }
+    if let Some(data) = &session.data {
+        if let Some(project) = &data.project {
+            lines.push(format!("Project: {}", project));
+        }
+        if !data.tags.is_empty() {
+            lines.push(format!("Tags: {}", data.tags.join(", ")));
+        }
+    }
+    lines.join("\n")
+}
+
+/// the output in the format the bar expects
+fn render(
+    settings: &Settings,
+    session: &CurrentSession,
+    style: StatusbarStyle,
+    format: &str,
+) -> String {
+    let text = session.format(format);
+    let color = color(settings, session.state);
+    match style {
+        // a json object per line, for a custom module with `"return-type": "json"`. the class
+        // can be used for styling in the css
+        StatusbarStyle::Waybar => json!({
+            "text": text,
+            "tooltip": tooltip(session),
+            "class": session.state.name(),
+            "alt": session.state.name(),
+        })
+        .to_string(),
+        // the full text, the short text and the color on separate lines
+        StatusbarStyle::I3blocks => format!(
+            "{}\n{}\n{}",
+            text,
+            session.format("{state} {elapsed}"),
+            color
+        ),
+        StatusbarStyle::Polybar => format!("%{{F{}}}{}%{{F-}}", color, text),
+    }
+}
+
+pub fn statusbar(
+    settings: &Settings,
+    path: &str,
+    style: StatusbarStyle,
+    format: Option<String>,
+) -> Result<()> {
+    let session = CurrentSession::read(settings, path);
+    let format = format.unwrap_or_else(|| settings.statusbar.format.clone());
+    println!("{}", render(settings, &session, style, &format));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::{Duration, Utc};
+
+    fn session(state: SessionState) -> CurrentSession {
+        let data = TrackingData {
+            project: Some("website".to_string()),
+            tags: vec!["bug".to_string()],
+            ..TrackingData::new(Some("review".to_string()), Utc::now())
+        };
+        let running = state == SessionState::Running;
+        CurrentSession {
+            state,
+            elapsed: Duration::minutes(if running { 75 } else { 0 }),
+            today: Duration::minutes(200),
+            data: Some(data).filter(|_| state != SessionState::Stopped),
+            eta: None,
+        }
+    }
+
+    #[test]
+    fn test_render_running() {
+        let settings = Settings::from_default_config();
+        let session = session(SessionState::Running);
+        let format = "{state} {elapsed} {description}";
+        assert_eq!(
+            json!({
+                "text": "▶ 01:15 review",
+                "tooltip": "Today: 03:20\nProject: website\nTags: bug",
+                "class": "running",
+                "alt": "running",
+            })
+            .to_string(),
+            render(&settings, &session, StatusbarStyle::Waybar, format)
+        );
+        assert_eq!(
+            format!(
+                "▶ 01:15 review\n▶ 01:15\n{}",
+                settings.statusbar.running_color
+            ),
+            render(&settings, &session, StatusbarStyle::I3blocks, format)
+        );
+        assert_eq!(
+            format!(
+                "%{{F{}}}▶ 01:15 review%{{F-}}",
+                settings.statusbar.running_color
+            ),
+            render(&settings, &session, StatusbarStyle::Polybar, format)
+        );
+    }
+
+    #[test]
+    fn test_render_paused_and_stopped() {
+        let settings = Settings::from_default_config();
+        let format = "{state} {description}";
+
+        let paused = session(SessionState::Paused);
+        assert_eq!(
+            format!("%{{F{}}}⏸ review%{{F-}}", settings.statusbar.paused_color),
+            render(&settings, &paused, StatusbarStyle::Polybar, format)
+        );
+
+        let stopped = session(SessionState::Stopped);
+        assert_eq!(
+            json!({
+                "text": "■",
+                "tooltip": "Today: 03:20",
+                "class": "stopped",
+                "alt": "stopped",
+            })
+            .to_string(),
+            render(&settings, &stopped, StatusbarStyle::Waybar, format)
+        );
+        assert_eq!(
+            format!("■\n■ 00:00\n{}", settings.statusbar.stopped_color),
+            render(&settings, &stopped, StatusbarStyle::I3blocks, format)
+        );
+    }
+}
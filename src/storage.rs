@@ -0,0 +1,1473 @@
+//! The data file is a log of events, so adding events only has to append to the end of the file
+//! instead of rewriting it. Only changes to existing events (e.g. cleanup or import) rewrite
+//! the whole file.
+//!
+//! With the binary feature, the file starts with a header, followed by bincode encoded events,
+//! each surrounded by its length. The header contains a version, because bincode can't read
+//! events which were written before fields were added. Repeating the length after the event allows reading the file
+//! backwards. Without it, the header is followed by one json encoded event per line.
+//!
+//! Files with an older version are migrated when they are read, and get written in the current
+//! version the next time the whole file is written. Files with a newer version are rejected, so
+//! an older tt can't misread them or append events in its own layout.
+//!
+//! Times are stored in milliseconds in the binary format and in seconds (with the milliseconds as
+//! fraction, if there are any) in the json lines, see the precision module.
+//!
+//! Every event is followed by a CRC32 checksum (a tab and eight hex digits in the json lines), so
+//! an event which was only partially written or got damaged is noticed when reading it. Rewriting
+//! the file writes a temporary file and renames it, so a crash leaves the old file intact.
+//!
+//! With encryption enabled, the whole file is encrypted (see the encryption module) and gets
+//! rewritten on every change.
+//!
+//! Reading a date range uses an index next to the data file, with the offset of the first event
+//! of every day. Since events are only appended, the index only has to be extended with the
+//! events which were added since it was last read.
+//!
+//! Files in the old formats (a single serialized vector of events) are still read and get
+//! converted the next time the file is written.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use fs2::FileExt;
+#[cfg(feature = "binary")]
+use iif::iif;
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::encryption;
+use crate::TrackingEvent;
+
+/// the start of the header, which is followed by the version and a newline
+#[cfg(feature = "binary")]
+const MAGIC: &[u8] = b"TTLOG";
+
+/// the version of the layout of the events. to change the layout, increase it, move the old
+/// layout into the legacy module and add its migration to `decode_version`
+#[cfg(feature = "binary")]
+const VERSION: u32 = 8;
+
+#[cfg(feature = "binary")]
+const HEADER: &[u8] = b"TTLOG8\n";
+
+#[cfg(feature = "binary")]
+const LENGTH_SIZE: usize = std::mem::size_of::<u32>();
+
+#[cfg(feature = "binary")]
+const CHECKSUM_SIZE: usize = std::mem::size_of::<u32>();
+
+#[cfg(not(feature = "binary"))]
+const MAGIC: &[u8] = b"TTJSON";
+
+/// the version of the json lines. files without a header are version 1. fields which were added
+/// later have defaults, so json lines only need a migration if the meaning of a field changes
+#[cfg(not(feature = "binary"))]
+const VERSION: u32 = 2;
+
+#[cfg(not(feature = "binary"))]
+const HEADER: &[u8] = b"TTJSON2\n";
+
+/// the path which stands for stdin or stdout when importing or exporting
+pub const STDIO_PATH: &str = "-";
+
+/// the first line of the index file, followed by the state of the index
+const INDEX_HEADER: &str = "TTIDX1";
+
+/// how often and how long to wait for the lock, before giving up
+const LOCK_RETRIES: u32 = 20;
+const LOCK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Event layout used before projects and tags were added. Bincode has no notion of missing
+/// fields, so files written in this format have to be read with the old types and converted.
+#[cfg(feature = "binary")]
+mod legacy {
+    use chrono::{serde::ts_seconds, DateTime, Utc};
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    /// Event layout used before the host and the user were added
+    pub mod v7 {
+        use chrono::{DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            id: Uuid,
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "crate::precision")]
+            time: DateTime<Utc>,
+
+            offset: Option<i32>,
+
+            billable: Option<bool>,
+
+            notes: Vec<crate::Note>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: data.id,
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: data.offset,
+                    billable: data.billable,
+                    notes: data.notes,
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    /// Event layout used before ids were added
+    pub mod v6 {
+        use chrono::{DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "crate::precision")]
+            time: DateTime<Utc>,
+
+            offset: Option<i32>,
+
+            billable: Option<bool>,
+
+            notes: Vec<crate::Note>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: Uuid::nil(),
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: data.offset,
+                    billable: data.billable,
+                    notes: data.notes,
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    /// Event layout used before times were stored in milliseconds, also used by files without
+    /// checksums
+    pub mod v5 {
+        use chrono::{serde::ts_seconds, DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "ts_seconds")]
+            time: DateTime<Utc>,
+
+            offset: Option<i32>,
+
+            billable: Option<bool>,
+
+            notes: Vec<crate::Note>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: Uuid::nil(),
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: data.offset,
+                    billable: data.billable,
+                    notes: data.notes,
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    /// Event layout used before notes were added
+    pub mod v3 {
+        use chrono::{serde::ts_seconds, DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "ts_seconds")]
+            time: DateTime<Utc>,
+
+            offset: Option<i32>,
+
+            billable: Option<bool>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: Uuid::nil(),
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: data.offset,
+                    billable: data.billable,
+                    notes: Vec::new(),
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    /// Event layout used before the billable flag was added
+    pub mod v2 {
+        use chrono::{serde::ts_seconds, DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "ts_seconds")]
+            time: DateTime<Utc>,
+
+            offset: Option<i32>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: Uuid::nil(),
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: data.offset,
+                    billable: None,
+                    notes: Vec::new(),
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    /// Event layout used before the timezone offset was added
+    pub mod v1 {
+        use chrono::{serde::ts_seconds, DateTime, Utc};
+        use serde::Deserialize;
+        use uuid::Uuid;
+
+        #[derive(Deserialize)]
+        pub struct TrackingData {
+            description: Option<String>,
+            project: Option<String>,
+            tags: Vec<String>,
+
+            #[serde(with = "ts_seconds")]
+            time: DateTime<Utc>,
+        }
+
+        #[derive(Deserialize)]
+        pub enum TrackingEvent {
+            Start(TrackingData),
+            Stop(TrackingData),
+            Pause(TrackingData),
+            Resume(TrackingData),
+        }
+
+        impl From<TrackingData> for crate::TrackingData {
+            fn from(data: TrackingData) -> Self {
+                Self {
+                    id: Uuid::nil(),
+                    description: data.description,
+                    project: data.project,
+                    tags: data.tags,
+                    time: data.time,
+                    offset: None,
+                    billable: None,
+                    notes: Vec::new(),
+                    host: None,
+                    user: None,
+                }
+            }
+        }
+
+        impl From<TrackingEvent> for crate::TrackingEvent {
+            fn from(event: TrackingEvent) -> Self {
+                match event {
+                    TrackingEvent::Start(data) => Self::Start(data.into()),
+                    TrackingEvent::Stop(data) => Self::Stop(data.into()),
+                    TrackingEvent::Pause(data) => Self::Pause(data.into()),
+                    TrackingEvent::Resume(data) => Self::Resume(data.into()),
+                }
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct TrackingData {
+        description: Option<String>,
+
+        #[serde(with = "ts_seconds")]
+        time: DateTime<Utc>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum TrackingEvent {
+        Start(TrackingData),
+        Stop(TrackingData),
+    }
+
+    impl From<TrackingData> for crate::TrackingData {
+        fn from(TrackingData { description, time }: TrackingData) -> Self {
+            Self {
+                offset: None,
+                id: Uuid::nil(),
+                ..Self::new(description, time)
+            }
+        }
+    }
+
+    impl From<TrackingEvent> for crate::TrackingEvent {
+        fn from(event: TrackingEvent) -> Self {
+            match event {
+                TrackingEvent::Start(data) => Self::Start(data.into()),
+                TrackingEvent::Stop(data) => Self::Stop(data.into()),
+            }
+        }
+    }
+}
+
+/// the version in the header at the start of `data` and the data after it. the oldest header has
+/// no version, it is version 1
+fn parse_header(data: &[u8]) -> Option<(u32, &[u8])> {
+    let rest = data.strip_prefix(MAGIC)?;
+    let end = rest.iter().position(|b| *b == b'\n')?;
+    let version = match &rest[..end] {
+        [] => 1,
+        version => std::str::from_utf8(version).ok()?.parse().ok()?,
+    };
+    Some((version, &rest[end + 1..]))
+}
+
+/// the error for files written by a newer version of tt, which might have changed the layout in
+/// a way this version doesn't know
+fn newer_version(version: u32) -> anyhow::Error {
+    anyhow!(
+        "The data file was written by a newer version of tt (format version {}, this version \
+         can read up to {}). Please update tt!",
+        version,
+        VERSION
+    )
+}
+
+#[cfg(feature = "binary")]
+fn is_legacy(data: &[u8]) -> bool {
+    parse_header(data).is_none()
+}
+
+#[cfg(not(feature = "binary"))]
+fn is_legacy(data: &[u8]) -> bool {
+    data.iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .map_or(false, |b| *b == b'[')
+}
+
+/// whether events can be appended to a file starting with `start`
+fn is_current_log(start: &[u8]) -> bool {
+    start.starts_with(HEADER)
+}
+
+#[cfg(feature = "binary")]
+fn read_legacy(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    use bincode::Options;
+
+    // reject trailing bytes, so data in the oldest format doesn't accidentally get parsed
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    match options.deserialize::<Vec<legacy::v1::TrackingEvent>>(data) {
+        Ok(events) => Ok(events.into_iter().map(Into::into).collect()),
+        Err(_) => {
+            let events: Vec<legacy::TrackingEvent> = options.deserialize(data)?;
+            Ok(events.into_iter().map(Into::into).collect())
+        }
+    }
+}
+
+#[cfg(not(feature = "binary"))]
+fn read_legacy(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    Ok(serde_json::from_slice(data)?)
+}
+
+fn checksum(payload: &[u8]) -> u32 {
+    crc32fast::hash(payload)
+}
+
+fn corrupted() -> anyhow::Error {
+    anyhow!("The data file is damaged, the checksum of an event doesn't match!")
+}
+
+#[cfg(feature = "binary")]
+fn encode_record(event: &TrackingEvent) -> Vec<u8> {
+    let payload = bincode::serialize(event).expect("could not serialize data");
+    let length = (payload.len() as u32).to_le_bytes();
+    let mut record = Vec::with_capacity(payload.len() + 2 * LENGTH_SIZE + CHECKSUM_SIZE);
+    record.extend_from_slice(&length);
+    record.extend_from_slice(&payload);
+    record.extend_from_slice(&checksum(&payload).to_le_bytes());
+    record.extend_from_slice(&length);
+    record
+}
+
+#[cfg(not(feature = "binary"))]
+fn encode_record(event: &TrackingEvent) -> Vec<u8> {
+    let mut record = serde_json::to_vec(event).expect("could not serialize data");
+    let checksum = format!("\t{:08x}\n", checksum(&record));
+    record.extend(checksum.into_bytes());
+    record
+}
+
+/// the payload of a record in the current format, after checking its checksum
+#[cfg(feature = "binary")]
+fn checked_payload(record: &[u8]) -> Result<&[u8]> {
+    let payload_end = record.len() - LENGTH_SIZE - CHECKSUM_SIZE;
+    let payload = &record[LENGTH_SIZE..payload_end];
+    if record[record.len() - LENGTH_SIZE..] != record[..LENGTH_SIZE]
+        || record[payload_end..record.len() - LENGTH_SIZE] != checksum(payload).to_le_bytes()
+    {
+        return Err(corrupted());
+    }
+    Ok(payload)
+}
+
+/// parses a line with a json encoded event and its checksum. lines which were written before
+/// checksums were added don't have one.
+#[cfg(not(feature = "binary"))]
+fn decode_line(line: &[u8]) -> Result<TrackingEvent> {
+    let line = line.trim_ascii_end();
+    let json = match line.len().checked_sub(9).map(|i| line.split_at(i)) {
+        Some((json, checksum_field))
+            if checksum_field[0] == b'\t'
+                && checksum_field[1..].iter().all(u8::is_ascii_hexdigit) =>
+        {
+            let expected = std::str::from_utf8(&checksum_field[1..])
+                .ok()
+                .and_then(|field| u32::from_str_radix(field, 16).ok());
+            if expected != Some(checksum(json)) {
+                return Err(corrupted());
+            }
+            json
+        }
+        _ => line,
+    };
+    Ok(serde_json::from_slice(json)?)
+}
+
+/// decodes the records of a log file. only files in the current format have checksums.
+#[cfg(feature = "binary")]
+fn decode_records<T>(mut data: &[u8], with_checksum: bool) -> Result<Vec<TrackingEvent>>
+where
+    T: serde::de::DeserializeOwned + Into<TrackingEvent>,
+{
+    use std::convert::TryInto;
+
+    let overhead = 2 * LENGTH_SIZE + iif!(with_checksum, CHECKSUM_SIZE, 0);
+    let mut events = Vec::new();
+    while !data.is_empty() {
+        let length = data
+            .get(..LENGTH_SIZE)
+            .map(|length| u32::from_le_bytes(length.try_into().unwrap()) as usize);
+        let record = length.and_then(|length| data.get(..length + overhead));
+        match (length, record) {
+            (Some(length), Some(record)) => {
+                data = &data[record.len()..];
+                let payload = if with_checksum {
+                    match checked_payload(record) {
+                        Ok(payload) => payload,
+                        // an interrupted write can leave a damaged record at the end
+                        Err(_) if data.is_empty() => {
+                            eprintln!("Ignoring incomplete event at the end of the data file!");
+                            break;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                } else if record[LENGTH_SIZE + length..] == record[..LENGTH_SIZE] {
+                    &record[LENGTH_SIZE..LENGTH_SIZE + length]
+                } else if data.is_empty() {
+                    eprintln!("Ignoring incomplete event at the end of the data file!");
+                    break;
+                } else {
+                    return Err(anyhow!(
+                        "The data file is damaged, the length of an event doesn't match!"
+                    ));
+                };
+                let event: T = bincode::deserialize(payload)?;
+                events.push(event.into());
+            }
+            _ => {
+                // an interrupted write can only leave an incomplete record at the end
+                eprintln!("Ignoring incomplete event at the end of the data file!");
+                break;
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(feature = "binary")]
+fn decode_log(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    let (version, records) =
+        parse_header(data).ok_or_else(|| anyhow!("The data file has no valid header!"))?;
+    decode_version(version, records)
+}
+
+/// the migrations of the older versions: bincode can only read events in the layout they were
+/// written with, so every version is read with its own types, which convert to the current ones
+#[cfg(feature = "binary")]
+fn decode_version(version: u32, records: &[u8]) -> Result<Vec<TrackingEvent>> {
+    match version {
+        1 => decode_records::<legacy::v1::TrackingEvent>(records, false),
+        2 => decode_records::<legacy::v2::TrackingEvent>(records, false),
+        3 => decode_records::<legacy::v3::TrackingEvent>(records, false),
+        // version 4 has the layout of version 5, without checksums
+        4 => decode_records::<legacy::v5::TrackingEvent>(records, false),
+        5 => decode_records::<legacy::v5::TrackingEvent>(records, true),
+        6 => decode_records::<legacy::v6::TrackingEvent>(records, true),
+        7 => decode_records::<legacy::v7::TrackingEvent>(records, true),
+        VERSION => decode_records::<TrackingEvent>(records, true),
+        version => Err(newer_version(version)),
+    }
+}
+
+#[cfg(not(feature = "binary"))]
+fn decode_log(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    let (version, data) = parse_header(data).unwrap_or((1, data));
+    if version > VERSION {
+        return Err(newer_version(version));
+    }
+    let mut events = Vec::new();
+    let mut lines = data
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .peekable();
+    while let Some(line) = lines.next() {
+        match decode_line(line) {
+            Ok(event) => events.push(event),
+            // an interrupted write can only leave an incomplete line at the end
+            Err(_) if lines.peek().is_none() => {
+                eprintln!("Ignoring incomplete event at the end of the data file!");
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(events)
+}
+
+/// reads exactly `buf.len()` bytes. returns false if the end of the file comes first.
+#[cfg(feature = "binary")]
+fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// reads the next record of a log file. returns `None` at the end of the file or if the last
+/// record is incomplete.
+#[cfg(feature = "binary")]
+fn read_record<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut record = vec![0; LENGTH_SIZE];
+    if !read_exact_or_eof(reader, &mut record)? {
+        return Ok(None);
+    }
+    let mut length = [0; LENGTH_SIZE];
+    length.copy_from_slice(&record);
+    record.resize(
+        u32::from_le_bytes(length) as usize + 2 * LENGTH_SIZE + CHECKSUM_SIZE,
+        0,
+    );
+    if !read_exact_or_eof(reader, &mut record[LENGTH_SIZE..])? {
+        return Ok(None);
+    }
+    Ok(Some(record))
+}
+
+#[cfg(not(feature = "binary"))]
+fn read_record<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut record = Vec::new();
+    reader.read_until(b'\n', &mut record)?;
+    if record.last() == Some(&b'\n') {
+        Ok(Some(record))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "binary")]
+fn decode_record(record: &[u8]) -> Result<Option<TrackingEvent>> {
+    Ok(Some(bincode::deserialize(checked_payload(record)?)?))
+}
+
+/// returns `None` for empty lines
+#[cfg(not(feature = "binary"))]
+fn decode_record(record: &[u8]) -> Result<Option<TrackingEvent>> {
+    if record.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    Ok(Some(decode_line(record)?))
+}
+
+/// reads the event at `offset` and returns it with the offset after it
+fn read_event_at(file: &mut File, offset: u64) -> Option<(u64, TrackingEvent)> {
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let record = read_record(&mut BufReader::new(file)).ok()??;
+    let event = decode_record(&record).ok()??;
+    Some((offset + record.len() as u64, event))
+}
+
+fn index_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.idx", path.to_string_lossy()))
+}
+
+/// offsets into a log file, so the events of a date range can be read without reading the
+/// whole file
+#[derive(Debug, PartialEq)]
+struct Index {
+    /// the length of the data file which is indexed
+    length: u64,
+    /// the number of indexed events
+    count: usize,
+    /// the offset and the time of the last indexed event, to notice when the data file was
+    /// changed by something else
+    last: Option<(u64, i64)>,
+    /// the UTC date, offset and number of the first event of every day
+    days: Vec<(NaiveDate, u64, usize)>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self {
+            length: HEADER.len() as u64,
+            count: 0,
+            last: None,
+            days: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (last_offset, last_time) = self.last.unwrap_or_default();
+        writeln!(
+            f,
+            "{} {} {} {} {}",
+            INDEX_HEADER, self.length, self.count, last_offset, last_time
+        )?;
+        for (date, offset, number) in &self.days {
+            writeln!(f, "{} {} {}", date, offset, number)?;
+        }
+        Ok(())
+    }
+}
+
+impl Index {
+    fn parse(s: &str) -> Option<Self> {
+        let mut lines = s.lines();
+        let header: Vec<_> = lines.next()?.split(' ').collect();
+        if header.len() != 5 || header[0] != INDEX_HEADER {
+            return None;
+        }
+        let count = header[2].parse().ok()?;
+        let last = (header[3].parse().ok()?, header[4].parse().ok()?);
+        let days = lines
+            .map(|line| {
+                let mut fields = line.split(' ');
+                Some((
+                    fields.next()?.parse().ok()?,
+                    fields.next()?.parse().ok()?,
+                    fields.next()?.parse().ok()?,
+                ))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            length: header[1].parse().ok()?,
+            count,
+            last: Some(last).filter(|_| count > 0),
+            days,
+        })
+    }
+
+    /// whether the index still describes the beginning of `file`
+    fn matches(&self, file: &mut File, size: u64) -> bool {
+        if self.length > size {
+            return false;
+        }
+        match self.last {
+            None => self.count == 0,
+            Some((offset, time)) => match read_event_at(file, offset) {
+                Some((end, event)) => end == self.length && event.time(true).timestamp() == time,
+                None => false,
+            },
+        }
+    }
+
+    /// the offset and number of the first event of the last day before `date`
+    fn before(&self, date: NaiveDate) -> (u64, usize) {
+        self.days
+            .iter()
+            .rev()
+            .find(|(day, _, _)| *day < date)
+            .map_or((HEADER.len() as u64, 0), |(_, offset, number)| {
+                (*offset, *number)
+            })
+    }
+}
+
+/// brings the index of the data file up to date. only the events which were added since the
+/// last update are read.
+fn update_index(path: &Path, file: &mut File) -> Result<Index> {
+    let size = file.seek(SeekFrom::End(0))?;
+    let index_path = index_path(path);
+    let mut index = std::fs::read_to_string(&index_path)
+        .ok()
+        .and_then(|index| Index::parse(&index))
+        .filter(|index| index.matches(file, size))
+        .unwrap_or_default();
+    if index.length == size {
+        return Ok(index);
+    }
+
+    file.seek(SeekFrom::Start(index.length))?;
+    let mut reader = BufReader::new(&mut *file);
+    let mut offset = index.length;
+    while let Some(record) = read_record(&mut reader)? {
+        // a damaged event isn't indexed, reading a range from there reads the whole file instead
+        let event = match decode_record(&record) {
+            Ok(event) => event,
+            Err(_) => break,
+        };
+        if let Some(event) = event {
+            let time = event.time(true);
+            let date = time.naive_utc().date();
+            if !matches!(index.days.last(), Some((day, _, _)) if *day >= date) {
+                index.days.push((date, offset, index.count));
+            }
+            index.last = Some((offset, time.timestamp()));
+            index.count += 1;
+        }
+        offset += record.len() as u64;
+    }
+    index.length = offset;
+    // the index only saves time, so failing to write it is not an error
+    let _ = std::fs::write(index_path, index.to_string());
+    Ok(index)
+}
+
+/// takes an exclusive lock on "<path>.lock", so concurrent invocations can't overwrite each
+/// other's changes. the lock is released when the returned file is dropped.
+pub fn lock<P: AsRef<Path>>(path: P) -> Result<File> {
+    let lock_path = format!("{}.lock", path.as_ref().to_string_lossy());
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+    for _ in 0..LOCK_RETRIES {
+        if file.try_lock_exclusive().is_ok() {
+            return Ok(file);
+        }
+        std::thread::sleep(LOCK_RETRY_INTERVAL);
+    }
+    Err(anyhow!(
+        "The data file is in use by another tt process. If this persists, check for a hanging tt process."
+    ))
+}
+
+pub fn read_data<P: AsRef<Path>>(path: P) -> Result<Vec<TrackingEvent>> {
+    decode_data(std::fs::read(&path)?)
+}
+
+/// like `read_data`, but a data file which doesn't exist yet has no events. other errors, like a
+/// damaged file, are returned, so the file doesn't get overwritten.
+pub fn read_data_if_exists<P: AsRef<Path>>(path: P) -> Result<Vec<TrackingEvent>> {
+    if !path.as_ref().exists() {
+        return Ok(Vec::new());
+    }
+    read_data(path)
+}
+
+fn decode_data(data: Vec<u8>) -> Result<Vec<TrackingEvent>> {
+    let data = encryption::decode(data)?;
+    let events = if is_legacy(&data) {
+        read_legacy(&data)?
+    } else {
+        decode_log(&data)?
+    };
+    Ok(with_legacy_ids(events, 0))
+}
+
+/// gives the events which were written without an id the id of their content and their position
+/// in the file, so equal events, like a start and a stop at the same time, don't share an id.
+/// `first` is the position of the first event.
+fn with_legacy_ids(mut events: Vec<TrackingEvent>, first: usize) -> Vec<TrackingEvent> {
+    for (position, event) in events.iter_mut().enumerate() {
+        let data = event.data_mut();
+        if data.id.is_nil() {
+            data.id = data.legacy_id(first + position);
+        }
+    }
+    events
+}
+
+/// reads events backwards from the end of the file until an event before `since` is found.
+/// returns `None` if the file is in a legacy format, can't be read backwards or has events without
+/// ids, whose ids depend on their position in the file.
+#[cfg(feature = "binary")]
+fn read_tail_records(path: &Path, since: DateTime<Utc>) -> Option<Vec<TrackingEvent>> {
+    let mut file = File::open(path).ok()?;
+    let mut header = vec![0; HEADER.len()];
+    file.read_exact(&mut header).ok()?;
+    if header != HEADER {
+        return None;
+    }
+
+    let mut events = Vec::new();
+    let mut end = file.seek(SeekFrom::End(0)).ok()?;
+    let mut length = [0; LENGTH_SIZE];
+    while end > HEADER.len() as u64 {
+        file.seek(SeekFrom::Start(end.checked_sub(LENGTH_SIZE as u64)?))
+            .ok()?;
+        file.read_exact(&mut length).ok()?;
+        let record_length = u32::from_le_bytes(length) as usize + 2 * LENGTH_SIZE + CHECKSUM_SIZE;
+        let start = end.checked_sub(record_length as u64)?;
+        if start < HEADER.len() as u64 {
+            return None;
+        }
+
+        let mut record = vec![0; record_length];
+        file.seek(SeekFrom::Start(start)).ok()?;
+        file.read_exact(&mut record).ok()?;
+        if record[..LENGTH_SIZE] != length {
+            return None;
+        }
+        let event: TrackingEvent = bincode::deserialize(checked_payload(&record).ok()?).ok()?;
+        if event.data().id.is_nil() {
+            return None;
+        }
+
+        let is_before = event.time(true) < since;
+        events.push(event);
+        if is_before {
+            break;
+        }
+        end = start;
+    }
+    events.reverse();
+    Some(events)
+}
+
+/// reads increasingly bigger chunks from the end of the file until an event before `since` is
+/// found. returns `None` if the file is in a legacy format, can't be read backwards or has events
+/// without ids, whose ids depend on their position in the file.
+#[cfg(not(feature = "binary"))]
+fn read_tail_records(path: &Path, since: DateTime<Utc>) -> Option<Vec<TrackingEvent>> {
+    let mut file = File::open(path).ok()?;
+    let size = file.seek(SeekFrom::End(0)).ok()?;
+    let mut chunk_size = 4096;
+    loop {
+        let start = size.saturating_sub(chunk_size);
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(start)).ok()?;
+        file.read_to_end(&mut data).ok()?;
+        let records = if start == 0 {
+            data.strip_prefix(HEADER)?
+        } else {
+            &data[..]
+        };
+        let mut lines = records.split(|b| *b == b'\n');
+        if start > 0 {
+            // the first line might only be partially read
+            lines.next();
+        }
+        let lines: Vec<_> = lines
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .collect();
+
+        let mut events = Vec::new();
+        let mut found_start = start == 0;
+        for line in lines.into_iter().rev() {
+            let event = decode_line(line).ok()?;
+            if event.data().id.is_nil() {
+                return None;
+            }
+            let is_before = event.time(true) < since;
+            events.push(event);
+            if is_before {
+                found_start = true;
+                break;
+            }
+        }
+        if found_start {
+            events.reverse();
+            return Some(events);
+        }
+        chunk_size *= 2;
+    }
+}
+
+/// reads only the events at the end of the data file, starting with the last event before
+/// `since`. this is a lot faster than reading the whole file for large histories.
+pub fn read_tail<P: AsRef<Path>>(path: P, since: DateTime<Utc>) -> Result<Vec<TrackingEvent>> {
+    // encrypted files can only be read as a whole
+    let tail = if is_log_file(&path) {
+        read_tail_records(path.as_ref(), since)
+    } else {
+        None
+    };
+    match tail {
+        Some(events) => Ok(events),
+        None => {
+            let data = read_data(path)?;
+            let first = data
+                .iter()
+                .rposition(|event| event.time(true) < since)
+                .unwrap_or(0);
+            Ok(data[first..].to_vec())
+        }
+    }
+}
+
+/// reads the events from the last one before `since` up to the first start after `until`, which
+/// is enough to calculate everything between them. also returns the number of events before the
+/// first returned one, so they can be numbered like in the whole file, and whether the events go
+/// up to the end of the file, so changes after the last one can be saved.
+///
+/// only files which changes get appended to are read partially, using the index to start at the
+/// day before `since`. this way, new events can still be saved by `save_data`. all other files are
+/// returned as a whole, like files with a damaged or incomplete event, which `read_data` handles.
+pub fn read_range<P: AsRef<Path>>(
+    path: P,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<(usize, Vec<TrackingEvent>, bool)> {
+    let path = path.as_ref();
+    if (since.is_none() && until.is_none()) || !can_append(path) {
+        return Ok((0, read_data(path)?, true));
+    }
+
+    let mut file = File::open(path)?;
+    let index = update_index(path, &mut file)?;
+    let size = file.seek(SeekFrom::End(0))?;
+    let (mut offset, mut first) = since.map_or((HEADER.len() as u64, 0), |since| {
+        index.before(since.naive_utc().date())
+    });
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut events = Vec::new();
+    while let Some(record) = read_record(&mut reader)? {
+        offset += record.len() as u64;
+        let event = match decode_record(&record) {
+            Ok(Some(event)) => event,
+            Ok(None) => continue,
+            // reading the whole file ignores a damaged event at the end or fails
+            Err(_) => return Ok((0, read_data(path)?, true)),
+        };
+        if event.is_start() && matches!(until, Some(until) if event.time(true) > until) {
+            return Ok((first, with_legacy_ids(events, first), false));
+        }
+        if matches!(since, Some(since) if event.time(true) < since) {
+            // only the last event before `since` is kept
+            first += events.len();
+            events.clear();
+        }
+        events.push(event);
+    }
+    if offset != size {
+        // the last event is incomplete
+        return Ok((0, read_data(path)?, true));
+    }
+    Ok((first, with_legacy_ids(events, first), true))
+}
+
+/// reads the file at `path` or stdin, if the path is "-"
+pub fn read_input<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    if path == Path::new(STDIO_PATH) {
+        let mut data = Vec::new();
+        io::stdin().read_to_end(&mut data)?;
+        Ok(data)
+    } else {
+        std::fs::read(path).with_context(|| format!("could not read \"{}\"", path.display()))
+    }
+}
+
+/// writes to the file at `path` or to stdout, if the path is "-"
+pub fn write_output<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    if path.as_ref() == Path::new(STDIO_PATH) {
+        let mut stdout = io::stdout();
+        match stdout
+            .write_all(contents.as_ref())
+            .and_then(|_| stdout.flush())
+        {
+            // the reading end of a pipe can stop early, e.g. `tt export - | head`
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+            _ => Ok(()),
+        }
+    } else {
+        Ok(write_with_flush(path, contents)?)
+    }
+}
+
+/// parses the data exported with `tt export`
+#[cfg(feature = "binary")]
+pub fn parse_json_data(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    Ok(with_legacy_ids(serde_json::from_slice(data)?, 0))
+}
+
+/// parses a data file, which is json without the binary feature
+#[cfg(not(feature = "binary"))]
+pub fn parse_json_data(data: &[u8]) -> Result<Vec<TrackingEvent>> {
+    decode_data(data.to_vec())
+}
+
+fn write_with_flush<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let mut f = File::create(path)?;
+    f.write_all(contents.as_ref())?;
+    f.flush()?;
+    Ok(())
+}
+
+/// syncs the directory, so a rename in it survives a power loss
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// directories can't be opened on windows, renames are synced by the file system there
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// writes the data to "<path>.tmp" and renames it to `path`, so a crash while writing leaves
+/// either the old or the new file, but never a partially written one
+pub fn write_atomic<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let temp_path = PathBuf::from(format!("{}.tmp", path.to_string_lossy()));
+    let mut file = File::create(&temp_path)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp_path, path)?;
+    sync_dir(path)?;
+    Ok(())
+}
+
+#[cfg(feature = "binary")]
+pub fn write_json_data<P: AsRef<Path>>(
+    path: P,
+    data: &[TrackingEvent],
+    pretty: bool,
+) -> Result<()> {
+    let data = iif!(
+        pretty,
+        serde_json::to_string_pretty(data),
+        serde_json::to_string(data)
+    )
+    .expect("could not serialize data");
+    write_output(path, data)
+}
+
+/// rewrites the whole data file
+pub fn write_data<P: AsRef<Path>>(path: P, data: &[TrackingEvent]) -> Result<()> {
+    let mut contents = HEADER.to_vec();
+    for event in data {
+        contents.extend(encode_record(event));
+    }
+    // the offsets in the index are wrong after a rewrite, it gets rebuilt on the next read
+    let _ = std::fs::remove_file(index_path(path.as_ref()));
+    write_atomic(path, &encryption::encode(contents)?)
+}
+
+fn append_data<P: AsRef<Path>>(path: P, events: &[TrackingEvent]) -> Result<()> {
+    let mut contents = Vec::new();
+    for event in events {
+        contents.extend(encode_record(event));
+    }
+    let mut f = OpenOptions::new().append(true).open(path)?;
+    f.write_all(&contents)?;
+    f.sync_data()?;
+    Ok(())
+}
+
+fn is_log_file<P: AsRef<Path>>(path: P) -> bool {
+    let mut start = Vec::new();
+    match File::open(path) {
+        Ok(f) => f.take(64).read_to_end(&mut start).is_ok() && is_current_log(&start),
+        Err(_) => false,
+    }
+}
+
+/// whether new events can be appended to the data file instead of rewriting it
+fn can_append<P: AsRef<Path>>(path: P) -> bool {
+    !encryption::is_enabled() && is_log_file(path)
+}
+
+/// whether the file ends with `last`, the last event which was read from it. otherwise, the end
+/// of the file was ignored when reading it (e.g. an incomplete event), so appending after it
+/// would lose the new events.
+fn ends_with<P: AsRef<Path>>(path: P, last: Option<&TrackingEvent>) -> bool {
+    let expected = last.map_or_else(|| HEADER.to_vec(), encode_record);
+    let read_end = || -> io::Result<bool> {
+        let mut file = File::open(path)?;
+        let size = file.seek(SeekFrom::End(0))?;
+        if last.is_none() {
+            return Ok(size == HEADER.len() as u64);
+        }
+        if size < (HEADER.len() + expected.len()) as u64 {
+            return Ok(false);
+        }
+        let mut end = vec![0; expected.len()];
+        file.seek(SeekFrom::Start(size - end.len() as u64))?;
+        file.read_exact(&mut end)?;
+        Ok(end == expected)
+    };
+    read_end().unwrap_or(false)
+}
+
+/// writes the changes from `old` to `new` to the data file. if events were only added at the
+/// end and the file doesn't get encrypted, they get appended, otherwise the whole file gets
+/// rewritten.
+pub fn save_data<P: AsRef<Path>>(
+    path: P,
+    old: &[TrackingEvent],
+    new: &[TrackingEvent],
+) -> Result<()> {
+    if new.starts_with(old) && can_append(&path) && ends_with(&path, old.last()) {
+        append_data(path, &new[old.len()..])
+    } else {
+        write_data(path, new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_decode_records_ignores_incomplete_last_event() {
+        let events = vec![
+            TrackingEvent::Start(TrackingData::new(
+                Some("a".to_string()),
+                Utc.ymd(2021, 4, 1).and_hms(8, 0, 0),
+            )),
+            TrackingEvent::Stop(TrackingData::new(
+                None,
+                Utc.ymd(2021, 4, 1).and_hms(9, 0, 0),
+            )),
+        ];
+        let mut data = HEADER.to_vec();
+        data.extend(events.iter().flat_map(encode_record));
+        let complete = data.len();
+        data.extend(encode_record(&events[0]));
+        data.truncate(complete + 5);
+
+        assert_eq!(events, decode_log(&data).unwrap());
+    }
+
+    #[test]
+    fn test_decode_log_detects_damaged_events() {
+        let event = |description: &str, hour| {
+            TrackingEvent::Start(TrackingData::new(
+                Some(description.to_string()),
+                Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0),
+            ))
+        };
+        let events = vec![event("first", 8), event("second", 9)];
+        let mut data = HEADER.to_vec();
+        data.extend(events.iter().flat_map(encode_record));
+        assert_eq!(events, decode_log(&data).unwrap());
+
+        let replace = |data: &[u8], from: &[u8], to: &[u8]| {
+            let mut data = data.to_vec();
+            let i = data.windows(from.len()).position(|w| w == from).unwrap();
+            data[i..i + to.len()].copy_from_slice(to);
+            data
+        };
+        assert!(decode_log(&replace(&data, b"first", b"frost")).is_err());
+        // a damaged last event is treated like an incomplete one
+        assert_eq!(
+            events[..1],
+            decode_log(&replace(&data, b"second", b"secant")).unwrap()[..]
+        );
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn test_decode_records_without_offset() {
+        let time = Utc.ymd(2021, 4, 1).and_hms(8, 0, 0);
+        // the variant index and the fields of a start event before the offset was added
+        let payload = bincode::serialize(&(
+            0u32,
+            Some("a"),
+            Some("website"),
+            vec!["bug"],
+            time.timestamp(),
+        ))
+        .unwrap();
+        let length = (payload.len() as u32).to_le_bytes();
+        let mut data = b"TTLOG\n".to_vec();
+        data.extend_from_slice(&length);
+        data.extend(payload);
+        data.extend_from_slice(&length);
+
+        let event = TrackingEvent::Start(TrackingData {
+            project: Some("website".to_string()),
+            tags: vec!["bug".to_string()],
+            offset: None,
+            ..TrackingData::new(Some("a".to_string()), time)
+        });
+        assert_eq!(vec![event], decode_log(&data).unwrap());
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn test_decode_records_without_billable() {
+        let time = Utc.ymd(2021, 4, 1).and_hms(8, 0, 0);
+        let payload = bincode::serialize(&(
+            0u32,
+            Some("a"),
+            None::<String>,
+            Vec::<String>::new(),
+            time.timestamp(),
+            Some(3600),
+        ))
+        .unwrap();
+        let length = (payload.len() as u32).to_le_bytes();
+        let mut data = b"TTLOG2\n".to_vec();
+        data.extend_from_slice(&length);
+        data.extend(payload);
+        data.extend_from_slice(&length);
+
+        let event = TrackingEvent::Start(TrackingData {
+            offset: Some(3600),
+            ..TrackingData::new(Some("a".to_string()), time)
+        });
+        assert_eq!(vec![event], decode_log(&data).unwrap());
+    }
+
+    #[test]
+    fn test_save_data_after_incomplete_last_event() {
+        let path = std::env::temp_dir().join(format!("tt-test-incomplete-{}", std::process::id()));
+        let event = |hour| {
+            TrackingEvent::Start(TrackingData::new(
+                None,
+                Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0),
+            ))
+        };
+        let events = [event(8), event(9), event(10)];
+        write_data(&path, &events[..1]).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&encode_record(&events[1])[..5]).unwrap();
+        drop(file);
+
+        let old = read_data(&path).unwrap();
+        assert_eq!(&events[..1], &old[..]);
+        save_data(&path, &old, &[&old[..], &events[2..]].concat()).unwrap();
+        assert_eq!(vec![event(8), event(10)], read_data(&path).unwrap());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_decode_data_rejects_newer_versions() {
+        assert_eq!(Some((VERSION, &b""[..])), parse_header(HEADER));
+        let mut data = MAGIC.to_vec();
+        data.extend(format!("{}\n", VERSION + 1).as_bytes());
+        let error = decode_data(data).unwrap_err();
+        assert!(error.to_string().contains("newer version of tt"));
+    }
+
+    #[test]
+    fn test_read_range() {
+        let path = std::env::temp_dir().join(format!("tt-test-range-{}", std::process::id()));
+        let event = |day, hour, start| {
+            let data = TrackingData::new(None, Utc.ymd(2021, 4, day).and_hms(hour, 0, 0));
+            if start {
+                TrackingEvent::Start(data)
+            } else {
+                TrackingEvent::Stop(data)
+            }
+        };
+        let data: Vec<_> = (1..=20)
+            .flat_map(|day| vec![event(day, 8, true), event(day, 16, false)])
+            .collect();
+        write_data(&path, &data[..30]).unwrap();
+
+        let since = Utc.ymd(2021, 4, 10).and_hms(0, 0, 0);
+        let until = Utc.ymd(2021, 4, 12).and_hms(0, 0, 0);
+        let (first, events, to_end) = read_range(&path, Some(since), Some(until)).unwrap();
+        assert_eq!(17, first);
+        assert_eq!(&data[17..22], &events[..]);
+        assert!(!to_end);
+        assert!(index_path(&path).exists());
+
+        // the index gets extended with appended events
+        save_data(&path, &data[..30], &data).unwrap();
+        let since = Utc.ymd(2021, 4, 20).and_hms(0, 0, 0);
+        let (first, events, to_end) = read_range(&path, Some(since), None).unwrap();
+        assert_eq!(37, first);
+        assert_eq!(&data[37..], &events[..]);
+        assert!(to_end);
+
+        // an incomplete event at the end reads the whole file, so nothing gets lost when the
+        // changes can't be appended
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&encode_record(&data[0])[..5]).unwrap();
+        drop(file);
+        let (first, events, to_end) = read_range(&path, Some(since), None).unwrap();
+        assert_eq!(0, first);
+        assert_eq!(data, events);
+        assert!(to_end);
+
+        let _ = std::fs::remove_file(index_path(&path));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
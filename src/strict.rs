@@ -0,0 +1,184 @@
+//! Strict mode for environments where the tracked time has to be trustworthy: times can only be
+//! set a limited time into the past, entries can't be deleted and every change is written to an
+//! audit log next to the data file ("<data_file>.audit"), as well as the break reminders.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+use serde::Serialize;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::encryption;
+use crate::journal::difference;
+use crate::settings::Settings;
+use crate::storage::write_atomic;
+use crate::TrackingEvent;
+
+/// a line of the audit log
+#[derive(Serialize)]
+struct AuditEntry {
+    time: String,
+    user: String,
+    /// the arguments of tt
+    command: Vec<String>,
+    removed: Vec<TrackingEvent>,
+    added: Vec<TrackingEvent>,
+    /// what tt told the user, like a break reminder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notice: Option<String>,
+}
+
+pub fn audit_path(data_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.audit", data_path))
+}
+
+fn user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn is_too_old(at: DateTime<Utc>, max_hours_in_past: u32) -> bool {
+    at < Utc::now() - Duration::hours(i64::from(max_hours_in_past))
+}
+
+/// fails if strict mode doesn't allow changing the data from `old` to `new`. an event which is
+/// replaced by one at the same time (e.g. by annotate) counts as changed, every other removed
+/// event as deleted. added and changed events can't be further in the past than allowed.
+pub fn check_change(
+    settings: &Settings,
+    old: &[TrackingEvent],
+    new: &[TrackingEvent],
+) -> Result<()> {
+    if !settings.strict_mode.enabled {
+        return Ok(());
+    }
+    let added = difference(new, old);
+    let mut replacements = added.clone();
+    for removed in difference(old, new) {
+        match replacements
+            .iter()
+            .position(|event| event.time(true) == removed.time(true))
+        {
+            Some(i) => {
+                replacements.remove(i);
+            }
+            None => return Err(anyhow!("Deleting entries is not allowed in strict mode!")),
+        }
+    }
+    let max_hours = settings.strict_mode.max_hours_in_past;
+    if added
+        .iter()
+        .any(|event| is_too_old(event.time(true), max_hours))
+    {
+        return Err(anyhow!(
+            "Times more than {} hours in the past are not allowed in strict mode!",
+            max_hours
+        ));
+    }
+    Ok(())
+}
+
+/// appends who changed what and when to the audit log. the log contains events as well, so it gets
+/// encrypted like the data file.
+pub fn record(
+    settings: &Settings,
+    data_path: &str,
+    old: &[TrackingEvent],
+    new: &[TrackingEvent],
+) -> Result<()> {
+    if !settings.strict_mode.enabled {
+        return Ok(());
+    }
+    let entry = AuditEntry {
+        time: Local::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        user: user(),
+        command: settings.args.clone(),
+        removed: difference(old, new),
+        added: difference(new, old),
+        notice: None,
+    };
+    if entry.removed.is_empty() && entry.added.is_empty() {
+        return Ok(());
+    }
+    append(data_path, &entry)
+}
+
+/// appends something tt told the user to the audit log, e.g. that it's time for a break
+pub fn record_notice(settings: &Settings, data_path: &str, notice: &str) -> Result<()> {
+    if !settings.strict_mode.enabled {
+        return Ok(());
+    }
+    let entry = AuditEntry {
+        time: Local::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        user: user(),
+        command: settings.args.clone(),
+        removed: Vec::new(),
+        added: Vec::new(),
+        notice: Some(notice.to_string()),
+    };
+    append(data_path, &entry)
+}
+
+fn append(data_path: &str, entry: &AuditEntry) -> Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    let path = audit_path(data_path);
+    if encryption::is_enabled() {
+        // the whole log is encrypted at once, so it has to be rewritten
+        let mut log = read_log(&path)?;
+        log.extend(line);
+        write_atomic(path, &encryption::encode(log)?)
+    } else {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(&line)?;
+        Ok(file.flush()?)
+    }
+}
+
+fn read_log(path: &Path) -> Result<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(log) => encryption::decode(log),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    #[test]
+    fn test_is_too_old() {
+        assert!(!is_too_old(Utc::now() - Duration::hours(1), 2));
+        assert!(is_too_old(Utc::now() - Duration::hours(3), 2));
+        assert!(!is_too_old(Utc::now() + Duration::hours(1), 0));
+    }
+
+    #[test]
+    fn test_check_change() {
+        let mut settings = Settings::from_default_config();
+        settings.strict_mode.enabled = true;
+        let now = Utc::now().with_nanosecond(0).unwrap();
+        let event = |description: &str, hours_ago| {
+            TrackingEvent::Start(TrackingData::new(
+                Some(description.to_string()),
+                now - Duration::hours(hours_ago),
+            ))
+        };
+        let old = vec![event("a", 48), event("b", 1)];
+
+        let added = [&old[..], &[event("c", 0)]].concat();
+        assert!(check_change(&settings, &old, &added).is_ok());
+        let backdated = [&old[..], &[event("c", 30)]].concat();
+        assert!(check_change(&settings, &old, &backdated).is_err());
+
+        // changing the description of a recent entry is allowed, but not of an old one
+        assert!(check_change(&settings, &old, &[event("a", 48), event("c", 1)]).is_ok());
+        assert!(check_change(&settings, &old, &[event("c", 48), event("b", 1)]).is_err());
+        assert!(check_change(&settings, &old, &old[1..]).is_err());
+    }
+}
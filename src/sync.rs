@@ -0,0 +1,197 @@
+//! Synchronization of the data file between machines through a git remote.
+//!
+//! tt keeps a clone of the remote next to the data file ("<data_file>.sync"). The version of the
+//! data file in that clone is the state of the last sync, which is used as the base of a three-way
+//! merge between the local data file and the version on the remote.
+
+use anyhow::{anyhow, Result};
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::cleanup::{cleanup, has_conflicts};
+use crate::hook::git;
+use crate::settings::Settings;
+use crate::storage::read_data_if_exists;
+use crate::{write_changes, TrackingEvent};
+
+fn clone_dir(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.sync", path))
+}
+
+/// the name of the data file in the clone, which is the same on all machines
+#[cfg(feature = "binary")]
+const SYNCED_FILE: &str = "timetracking.bin";
+#[cfg(not(feature = "binary"))]
+const SYNCED_FILE: &str = "timetracking.json";
+
+fn ensure_clone(settings: &Settings, path: &str) -> Result<PathBuf> {
+    if settings.sync.remote.is_empty() {
+        return Err(anyhow!("Please set sync.remote in the config first!"));
+    }
+    let dir = clone_dir(path);
+    if !dir.join(".git").exists() {
+        let dir_name = dir.to_string_lossy().to_string();
+        git(Path::new("."), &["clone", &settings.sync.remote, &dir_name])?;
+    }
+    Ok(dir)
+}
+
+/// three-way merge of the event lists. events added on either side are kept, events which are
+/// in the base but were removed on either side are dropped.
+fn merge(
+    base: &[TrackingEvent],
+    local: &[TrackingEvent],
+    remote: &[TrackingEvent],
+) -> Vec<TrackingEvent> {
+    let base: HashSet<_> = base.iter().collect();
+    let local_set: HashSet<_> = local.iter().collect();
+    let remote_set: HashSet<_> = remote.iter().collect();
+    let mut merged: Vec<_> = local
+        .iter()
+        .chain(remote.iter())
+        .filter(|event| {
+            !base.contains(event) || (local_set.contains(event) && remote_set.contains(event))
+        })
+        .cloned()
+        .collect();
+    merged.sort_by_key(|e| e.time(true));
+    merged.dedup();
+    merged
+}
+
+#[cfg(feature = "rest-sync")]
+fn sync_with_server(settings: &Settings, path: &str, url: &str) -> Result<()> {
+    crate::rest_sync::sync(settings, path, url)
+}
+
+#[cfg(not(feature = "rest-sync"))]
+fn sync_with_server(_settings: &Settings, _path: &str, _url: &str) -> Result<()> {
+    Err(anyhow!(
+        "Syncing with a sync server needs the rest-sync feature, see the README!"
+    ))
+}
+
+/// fetches the remote and merges its changes into the data file
+pub fn pull(settings: &Settings, path: &str) -> Result<()> {
+    if !settings.sync.url.is_empty() {
+        // the server exchanges the changes of both sides at once
+        return sync_with_server(settings, path, &settings.sync.url);
+    }
+    let dir = ensure_clone(settings, path)?;
+    let synced_file = dir.join(SYNCED_FILE);
+    let remote_branch = format!("origin/{}", settings.sync.branch);
+
+    git(&dir, &["fetch", "origin"])?;
+    if git(&dir, &["rev-parse", "--verify", "--quiet", &remote_branch]).is_err() {
+        // nothing was pushed yet
+        return Ok(());
+    }
+    let base = read_data_if_exists(&synced_file)?;
+    git(
+        &dir,
+        &[
+            "checkout",
+            "--quiet",
+            "--force",
+            "-B",
+            &settings.sync.branch,
+            &remote_branch,
+        ],
+    )?;
+    let remote = read_data_if_exists(&synced_file)?;
+    let local = read_data_if_exists(path)?;
+
+    let mut merged = merge(&base, &local, &remote);
+    if merged != local && has_conflicts(&merged) {
+        println!("The local and the remote changes conflict, please clean them up.");
+        merged = cleanup(&merged, None);
+    }
+    if merged != local {
+        write_changes(settings, path, &local, &mut merged, true)?;
+        println!("Merged the changes from the remote.");
+    }
+    Ok(())
+}
+
+/// commits the data file and pushes it to the remote
+pub fn push(settings: &Settings, path: &str) -> Result<()> {
+    if !settings.sync.url.is_empty() {
+        return sync_with_server(settings, path, &settings.sync.url);
+    }
+    let dir = ensure_clone(settings, path)?;
+    let synced_file = dir.join(SYNCED_FILE);
+    if !Path::new(path).exists() {
+        return Ok(());
+    }
+    std::fs::copy(path, &synced_file)?;
+
+    git(&dir, &["add", SYNCED_FILE])?;
+    if git(&dir, &["status", "--porcelain"])?.is_empty() {
+        return Ok(());
+    }
+    git(
+        &dir,
+        &["commit", "--quiet", "-m", "Update time tracking data"],
+    )?;
+    git(
+        &dir,
+        &[
+            "push",
+            "--quiet",
+            "origin",
+            &format!("HEAD:{}", settings.sync.branch),
+        ],
+    )?;
+    println!("Pushed the changes to the remote.");
+    Ok(())
+}
+
+/// merges the remote changes and pushes the result. with a url, the data is synced with that
+/// sync server instead of the remotes from the config
+pub fn sync(settings: &Settings, path: &str, url: Option<&str>) -> Result<()> {
+    if let Some(url) = url {
+        return sync_with_server(settings, path, url);
+    }
+    if !settings.sync.url.is_empty() {
+        return sync_with_server(settings, path, &settings.sync.url);
+    }
+    pull(settings, path)?;
+    push(settings, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    fn start(h: u32) -> TrackingEvent {
+        TrackingEvent::Start(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+        ))
+    }
+
+    fn stop(h: u32) -> TrackingEvent {
+        TrackingEvent::Stop(TrackingData::new(
+            None,
+            Utc.ymd(2021, 4, 1).and_hms(h, 0, 0),
+        ))
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = vec![start(8), stop(9)];
+        // added a new interval locally
+        let local = vec![start(8), stop(9), start(10), stop(11)];
+        // deleted the first interval and added another one on the remote
+        let remote = vec![start(12), stop(13)];
+        let merged = merge(&base, &local, &remote);
+        assert_eq!(vec![start(10), stop(11), start(12), stop(13)], merged);
+        assert!(!has_conflicts(&merged));
+
+        let merged = merge(&[], &[start(8), stop(10)], &[start(9), stop(11)]);
+        assert!(has_conflicts(&merged));
+    }
+}
@@ -0,0 +1,284 @@
+//! Aligned columns for `tt list`, `tt report` and `tt summary`. If the table is wider than the
+//! terminal, the columns with free text, like the descriptions, are cut off with an ellipsis,
+//! unless `--wide` is given. Output which is not shown in a terminal is never cut off.
+
+use iif::iif;
+use terminal_size::{terminal_size, Width};
+
+use crate::color::{paint, Style};
+
+const SEPARATOR: &str = "  ";
+
+/// columns are never cut below this width, even if the table doesn't fit then
+const MIN_TRUNCATED_WIDTH: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+}
+
+pub struct Column {
+    header: String,
+    align: Align,
+    truncate: bool,
+}
+
+impl Column {
+    pub fn left(header: &str) -> Self {
+        Self {
+            header: header.to_string(),
+            align: Align::Left,
+            truncate: false,
+        }
+    }
+
+    pub fn right(header: &str) -> Self {
+        Self {
+            align: Align::Right,
+            ..Self::left(header)
+        }
+    }
+
+    /// a column with free text, which is cut off if the table doesn't fit
+    pub fn truncated(self) -> Self {
+        Self {
+            truncate: true,
+            ..self
+        }
+    }
+}
+
+pub struct Cell {
+    text: String,
+    style: Option<Style>,
+}
+
+impl Cell {
+    /// a cell with its own color. the color is applied after the padding, so the escape codes
+    /// don't count as characters
+    pub fn styled(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style: Some(style),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self { text, style: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+enum Line {
+    Row(Vec<Cell>, Option<Style>),
+    /// a line which is not part of the columns, like the notes of an entry
+    Text(String),
+}
+
+pub struct Table {
+    columns: Vec<Column>,
+    show_header: bool,
+    lines: Vec<Line>,
+}
+
+/// the text cut to `width` characters, with an ellipsis if it was longer
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// the width of the terminal stdout is shown in
+pub fn terminal_width() -> Option<usize> {
+    terminal_size().map(|(Width(width), _)| usize::from(width))
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            show_header: true,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn without_header(self) -> Self {
+        Self {
+            show_header: false,
+            ..self
+        }
+    }
+
+    pub fn add_row(&mut self, cells: Vec<Cell>) {
+        self.lines.push(Line::Row(cells, None));
+    }
+
+    /// a row which is colored completely, like a total
+    pub fn add_styled_row(&mut self, cells: Vec<Cell>, style: Option<Style>) {
+        self.lines.push(Line::Row(cells, style));
+    }
+
+    pub fn add_text(&mut self, text: String) {
+        self.lines.push(Line::Text(text));
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &Vec<Cell>> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Row(cells, _) => Some(cells),
+            Line::Text(_) => None,
+        })
+    }
+
+    /// the width of every column, `None` for the columns without any text, which are left out
+    fn widths(&self, max_width: Option<usize>) -> Vec<Option<usize>> {
+        let mut widths: Vec<_> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let cells = self
+                    .rows()
+                    .filter_map(|cells| cells.get(i))
+                    .map(|cell| cell.text.chars().count());
+                let header = iif!(self.show_header, column.header.chars().count(), 0);
+                cells
+                    .chain(std::iter::once(header))
+                    .max()
+                    .filter(|w| *w > 0)
+            })
+            .collect();
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => return widths,
+        };
+        let shown = widths.iter().flatten().count();
+        let mut total =
+            widths.iter().flatten().sum::<usize>() + SEPARATOR.len() * shown.saturating_sub(1);
+        // the widest truncated column gives up one character at a time, so the others stay as wide
+        // as possible
+        while total > max_width {
+            let widest = widths
+                .iter_mut()
+                .zip(&self.columns)
+                .filter(|(_, column)| column.truncate)
+                .filter_map(|(width, _)| width.as_mut())
+                .filter(|width| **width > MIN_TRUNCATED_WIDTH)
+                .max_by_key(|width| **width);
+            match widest {
+                Some(width) => *width -= 1,
+                None => break,
+            }
+            total -= 1;
+        }
+        widths
+    }
+
+    fn render_cells(&self, cells: &[Cell], widths: &[Option<usize>]) -> String {
+        let empty = Cell::from("");
+        let mut parts = Vec::new();
+        for (i, (column, width)) in self.columns.iter().zip(widths).enumerate() {
+            let width = match width {
+                Some(width) => *width,
+                None => continue,
+            };
+            let cell = cells.get(i).unwrap_or(&empty);
+            let text = iif!(
+                column.truncate,
+                truncate(&cell.text, width),
+                cell.text.clone()
+            );
+            let padded = match column.align {
+                Align::Left => format!("{:<width$}", text, width = width),
+                Align::Right => format!("{:>width$}", text, width = width),
+            };
+            parts.push(match cell.style {
+                // the padding of a left aligned cell is not colored
+                Some(style) => {
+                    let padding = padded.chars().count() - text.chars().count();
+                    match column.align {
+                        Align::Left => format!("{}{}", paint(&text, style), " ".repeat(padding)),
+                        Align::Right => format!("{}{}", " ".repeat(padding), paint(&text, style)),
+                    }
+                }
+                None => padded,
+            });
+        }
+        parts.join(SEPARATOR).trim_end().to_string()
+    }
+
+    /// the lines of the table, with the columns cut to `max_width`
+    fn render_with_width(&self, max_width: Option<usize>) -> Vec<String> {
+        let widths = self.widths(max_width);
+        let mut lines = Vec::new();
+        if self.show_header {
+            let headers: Vec<Cell> = self
+                .columns
+                .iter()
+                .map(|column| column.header.as_str().into())
+                .collect();
+            lines.push(paint(self.render_cells(&headers, &widths), Style::Header));
+        }
+        for line in &self.lines {
+            lines.push(match line {
+                Line::Row(cells, Some(style)) => paint(self.render_cells(cells, &widths), *style),
+                Line::Row(cells, None) => self.render_cells(cells, &widths),
+                Line::Text(text) => text.clone(),
+            });
+        }
+        lines
+    }
+
+    /// the lines of the table, cut to the width of the terminal unless `wide` is set
+    pub fn render(&self, wide: bool) -> Vec<String> {
+        self.render_with_width(iif!(wide, None, terminal_width()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut table = Table::new(vec![
+            Column::left("Name"),
+            Column::right("Hours"),
+            Column::left("Description").truncated(),
+            Column::left("Tags"),
+        ]);
+        table.add_row(vec!["a".into(), "1:30".into(), "a long description".into()]);
+        table.add_row(vec!["bb".into(), "10:00".into(), "short".into()]);
+        assert_eq!(
+            vec![
+                "Name  Hours  Description         Tags",
+                "a      1:30  a long description",
+                "bb    10:00  short",
+            ],
+            table.render_with_width(None)
+        );
+        assert_eq!(
+            vec![
+                "Name  Hours  Description  Tags",
+                "a      1:30  a long des…",
+                "bb    10:00  short",
+            ],
+            table.render_with_width(Some(30))
+        );
+        // without the header, the empty column is left out
+        assert_eq!(
+            vec!["a    1:30  a long description", "bb  10:00  short"],
+            table.without_header().render_with_width(Some(30))
+        );
+    }
+}
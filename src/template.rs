@@ -0,0 +1,210 @@
+//! Reports rendered from a handlebars template, e.g. to generate a timesheet in the format a
+//! company requires. See the README for the data which is available in the template.
+
+use anyhow::{Context, Result};
+use chrono::{prelude::*, Duration};
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use std::path::Path;
+
+use crate::settings::Settings;
+use crate::timesheet::format_delta;
+use crate::{
+    filter_events, format_hours_minutes, get_data_as_days, summarize_day, CalculationOptions,
+    FilterData, TrackingEvent,
+};
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    start: String,
+    stop: Option<String>,
+    duration: String,
+    minutes: i64,
+    description: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    billable: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Day {
+    date: String,
+    weekday: String,
+    entries: Vec<Entry>,
+    work_time: String,
+    work_minutes: i64,
+    break_time: String,
+    break_minutes: i64,
+    goal: String,
+    goal_minutes: i64,
+    goal_delta: String,
+    goal_met: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    from: Option<String>,
+    to: Option<String>,
+    days: Vec<Day>,
+    work_time: String,
+    work_minutes: i64,
+    break_time: String,
+    break_minutes: i64,
+    goal: String,
+    goal_minutes: i64,
+    goal_delta: String,
+}
+
+fn to_local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+fn collect_entries(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<Entry> {
+    let include_seconds = options.include_seconds;
+    let mut entries = Vec::new();
+    let mut events = data.iter();
+    while let Some(start) = events.find(|e| e.is_start()) {
+        let stop = events.find(|e| e.is_stop());
+        let end = stop.map_or_else(Utc::now, |stop| stop.time(include_seconds));
+        let mut duration = match options.limit_interval(settings, end - start.time(include_seconds))
+        {
+            Some(duration) => duration,
+            None => continue,
+        };
+        if !options.no_round {
+            duration = settings.rounding.apply(duration);
+        }
+        let data = start.data();
+        entries.push(Entry {
+            start: to_local_time(start.time(include_seconds)),
+            stop: stop.map(|stop| to_local_time(stop.time(include_seconds))),
+            duration: format_hours_minutes(duration),
+            minutes: duration.num_minutes(),
+            description: data.description.clone(),
+            project: data.project.clone(),
+            tags: data.tags.clone(),
+            billable: data.is_billable(settings),
+        });
+    }
+    entries
+}
+
+fn collect_days(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Vec<Day> {
+    let mut days = Vec::new();
+    for day in get_data_as_days(data) {
+        let date = match day.first() {
+            Some(event) => event.local_date(),
+            None => continue,
+        };
+        let summary = summarize_day(settings, &day, options);
+        let goal = settings.goal_for_date(date);
+        days.push(Day {
+            date: date.format("%Y-%m-%d").to_string(),
+            weekday: date.format("%A").to_string(),
+            entries: collect_entries(settings, &day, options),
+            work_time: format_hours_minutes(summary.work_time),
+            work_minutes: summary.work_time.num_minutes(),
+            break_time: format_hours_minutes(summary.break_time()),
+            break_minutes: summary.break_time().num_minutes(),
+            goal: format_hours_minutes(goal),
+            goal_minutes: goal.num_minutes(),
+            goal_delta: format_delta(summary.work_time - goal),
+            goal_met: summary.work_time >= goal,
+        });
+    }
+    days
+}
+
+fn build_context(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    options: CalculationOptions,
+) -> Report {
+    let days = collect_days(settings, data, options);
+    let work_minutes = days.iter().map(|day| day.work_minutes).sum();
+    let break_minutes = days.iter().map(|day| day.break_minutes).sum();
+    let goal_minutes = days.iter().map(|day| day.goal_minutes).sum();
+    Report {
+        from: days.first().map(|day| day.date.clone()),
+        to: days.last().map(|day| day.date.clone()),
+        work_time: format_hours_minutes(Duration::minutes(work_minutes)),
+        work_minutes,
+        break_time: format_hours_minutes(Duration::minutes(break_minutes)),
+        break_minutes,
+        goal: format_hours_minutes(Duration::minutes(goal_minutes)),
+        goal_minutes,
+        goal_delta: format_delta(Duration::minutes(work_minutes - goal_minutes)),
+        days,
+    }
+}
+
+/// renders the template. placeholders which don't exist in the report are an error
+fn render(template: &str, context: &Report) -> Result<String> {
+    let mut handlebars = Handlebars::new();
+    // the output is plain text, templates for html have to escape on their own
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.set_strict_mode(true);
+    handlebars
+        .render_template(template, context)
+        .context("could not render template")
+}
+
+/// prints the filtered data rendered with the handlebars template in `template`
+pub fn report(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    filter: &FilterData,
+    template: &Path,
+    options: CalculationOptions,
+) -> Result<()> {
+    let template = std::fs::read_to_string(template)
+        .with_context(|| format!("could not read template \"{}\"", template.display()))?;
+    let filtered_data = filter_events(data, filter, settings.week_starts_on)?;
+    let context = build_context(settings, &filtered_data, options);
+    print!("{}", render(&template, &context)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+
+    fn context() -> Report {
+        let at = |hour| Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0);
+        let data = vec![
+            TrackingEvent::Start(TrackingData::new(Some("<review>".to_string()), at(9))),
+            TrackingEvent::Stop(TrackingData::new(None, at(11))),
+        ];
+        let options = CalculationOptions {
+            include_seconds: true,
+            no_round: true,
+            ..CalculationOptions::default()
+        };
+        build_context(&Settings::from_default_config(), &data, options)
+    }
+
+    #[test]
+    fn test_render() {
+        let template = "{{#each days}}{{date}}: {{#each entries}}{{description}} \
+                        {{minutes}}{{/each}}{{/each}}, total {{work_minutes}}";
+        assert_eq!(
+            "2021-04-01: <review> 120, total 120",
+            render(template, &context()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_render_unknown_placeholder() {
+        assert!(render("{{hours}}", &context()).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Export in the timeclock format of hledger and ledger, so the tracked time can be used for plain
+//! text accounting. Every interval is an `i` line with the account, followed by an `o` line.
+
+use std::fmt::Write;
+
+use crate::TrackingEvent;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// the account of an interval: the project and the description as sub account. two spaces end the
+/// account name in timeclock files, so whitespace gets collapsed.
+fn account(event: &TrackingEvent) -> String {
+    let data = event.data();
+    let account = match (&data.project, &data.description) {
+        (Some(project), Some(description)) => format!("{}:{}", project, description),
+        (Some(name), None) | (None, Some(name)) => name.clone(),
+        (None, None) => "timetracking".to_string(),
+    };
+    account.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// the timeclock entries for the intervals in `data`. a running interval only gets an `i` line,
+/// which hledger counts up to now.
+pub fn render(data: &[TrackingEvent]) -> String {
+    let mut out = String::new();
+    let mut data_iterator = data.iter();
+    while let Some(start) = data_iterator.find(|e| e.is_start()) {
+        let tags = start
+            .data()
+            .tags
+            .iter()
+            .map(|tag| format!("{}:", tag))
+            .collect::<Vec<_>>();
+        write!(
+            out,
+            "i {} {}",
+            start.local_time().format(TIME_FORMAT),
+            account(start)
+        )
+        .unwrap();
+        if !tags.is_empty() {
+            // tags are written as hledger tags in a comment
+            write!(out, "  ; {}", tags.join(", ")).unwrap();
+        }
+        writeln!(out).unwrap();
+        if let Some(stop) = data_iterator.find(|e| e.is_stop()) {
+            writeln!(out, "o {}", stop.local_time().format(TIME_FORMAT)).unwrap();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackingData;
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_render() {
+        let data = |hour| TrackingData {
+            offset: Some(3600),
+            ..TrackingData::new(None, Utc.ymd(2021, 4, 1).and_hms(hour, 0, 0))
+        };
+        let events = vec![
+            TrackingEvent::Start(TrackingData {
+                description: Some("fix  bug".to_string()),
+                project: Some("website".to_string()),
+                tags: vec!["urgent".to_string()],
+                ..data(8)
+            }),
+            TrackingEvent::Stop(data(9)),
+            TrackingEvent::Start(data(10)),
+        ];
+        assert_eq!(
+            "i 2021-04-01 09:00:00 website:fix bug  ; urgent:\no 2021-04-01 10:00:00\ni 2021-04-01 11:00:00 timetracking\n",
+            render(&events)
+        );
+    }
+}
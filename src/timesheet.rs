@@ -0,0 +1,290 @@
+//! Weekly timesheets in Markdown or HTML, meant to be pasted into a status report or email, and the
+//! other export formats (see `timeclock`).
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::settings::Settings;
+use crate::storage::write_output;
+use crate::timeclock::render;
+use crate::{
+    filter_events, format_hours_minutes, get_data_as_days, parse_date_or_date_time, summarize_day,
+    CalculationOptions, DateOrDateTime, FilterData, TrackingEvent,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Timeclock,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "timeclock" => Ok(Self::Timeclock),
+            _ => Err(anyhow!(
+                "invalid value \"{}\", expected one of: md, html, timeclock",
+                s
+            )),
+        }
+    }
+}
+
+struct Row {
+    start: String,
+    stop: String,
+    duration: String,
+    description: String,
+}
+
+struct Day {
+    title: String,
+    rows: Vec<Row>,
+    work_time: Duration,
+    goal_delta: Duration,
+}
+
+/// formats a duration with a leading sign, e.g. "+01:30" or "-00:45"
+pub(crate) fn format_delta(delta: Duration) -> String {
+    if delta < Duration::zero() {
+        format!("-{}", format_hours_minutes(-delta))
+    } else {
+        format!("+{}", format_hours_minutes(delta))
+    }
+}
+
+fn describe(event: &TrackingEvent) -> String {
+    let data = event.data();
+    let mut description = data.description.clone().unwrap_or_default();
+    if let Some(project) = &data.project {
+        description = format!("{} [{}]", description, project);
+    }
+    for tag in &data.tags {
+        description = format!("{} #{}", description, tag);
+    }
+    description.trim().to_string()
+}
+
+fn to_local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+fn collect_days(settings: &Settings, data: &[TrackingEvent]) -> Vec<Day> {
+    let options = CalculationOptions::default();
+    let mut days = Vec::new();
+    for day in get_data_as_days(data) {
+        let date = match day.first() {
+            Some(event) => event.local_date(),
+            None => continue,
+        };
+        let mut rows = Vec::new();
+        let mut events = day.iter();
+        while let Some(start) = events.find(|e| e.is_start()) {
+            let stop = events.find(|e| e.is_stop());
+            let end = stop.map_or_else(Utc::now, |stop| stop.time(false));
+            rows.push(Row {
+                start: to_local_time(start.time(false)),
+                stop: stop.map_or_else(
+                    || "running".to_string(),
+                    |stop| to_local_time(stop.time(false)),
+                ),
+                duration: format_hours_minutes(settings.rounding.apply(end - start.time(false))),
+                description: describe(start),
+            });
+        }
+        let work_time = summarize_day(settings, &day, options).work_time;
+        days.push(Day {
+            title: date.format("%A, %Y-%m-%d").to_string(),
+            rows,
+            work_time,
+            goal_delta: work_time - settings.goal_for_date(date),
+        });
+    }
+    days
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_markdown(title: &str, days: &[Day], total: Duration, weekly_delta: Duration) -> String {
+    let mut out = String::new();
+    writeln!(out, "# {}", title).unwrap();
+    for day in days {
+        writeln!(out).unwrap();
+        writeln!(out, "## {}", day.title).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "| Start | Stop | Duration | Description |").unwrap();
+        writeln!(out, "|-------|------|---------:|-------------|").unwrap();
+        for row in &day.rows {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                row.start,
+                row.stop,
+                row.duration,
+                escape_markdown(&row.description)
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Total:** {} ({} to goal)",
+            format_hours_minutes(day.work_time),
+            format_delta(day.goal_delta)
+        )
+        .unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "**Week total:** {} ({} to goal)",
+        format_hours_minutes(total),
+        format_delta(weekly_delta)
+    )
+    .unwrap();
+    out
+}
+
+fn render_html(title: &str, days: &[Day], total: Duration, weekly_delta: Duration) -> String {
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html>").unwrap();
+    writeln!(
+        out,
+        "<head><meta charset=\"utf-8\"><title>{}</title></head>",
+        title
+    )
+    .unwrap();
+    writeln!(out, "<body>").unwrap();
+    writeln!(out, "<h1>{}</h1>", title).unwrap();
+    for day in days {
+        writeln!(out, "<h2>{}</h2>", day.title).unwrap();
+        writeln!(out, "<table>").unwrap();
+        writeln!(
+            out,
+            "<tr><th>Start</th><th>Stop</th><th>Duration</th><th>Description</th></tr>"
+        )
+        .unwrap();
+        for row in &day.rows {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                row.start,
+                row.stop,
+                row.duration,
+                escape_html(&row.description)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</table>").unwrap();
+        writeln!(
+            out,
+            "<p><strong>Total:</strong> {} ({} to goal)</p>",
+            format_hours_minutes(day.work_time),
+            format_delta(day.goal_delta)
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "<p><strong>Week total:</strong> {} ({} to goal)</p>",
+        format_hours_minutes(total),
+        format_delta(weekly_delta)
+    )
+    .unwrap();
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    out
+}
+
+/// writes the timesheet of the week containing `week` (or the current week) to `path`. timeclock
+/// entries are written for everything, unless a week is given.
+/// the date given with --week
+pub fn parse_week(week: &str) -> Result<NaiveDate> {
+    Ok(match parse_date_or_date_time(week)? {
+        DateOrDateTime::Date(date) => date,
+        DateOrDateTime::DateTime(date_time) => date_time.date(),
+    })
+}
+
+/// the first and the last day of the week containing `date`
+pub fn week_of(settings: &Settings, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = settings.start_of_week(date);
+    (start, start + Duration::days(6))
+}
+
+/// the events from the first to the last day of a week
+pub fn filter_week(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    (start, end): (NaiveDate, NaiveDate),
+) -> Result<Vec<TrackingEvent>> {
+    let filter = FilterData {
+        from: Some(start.format("%Y-%m-%d").to_string()),
+        to: Some(end.format("%Y-%m-%d").to_string()),
+        ..FilterData::default()
+    };
+    filter_events(data, &filter, settings.week_starts_on)
+}
+
+pub fn export(
+    settings: &Settings,
+    data: &[TrackingEvent],
+    format: ExportFormat,
+    week: Option<String>,
+    path: &str,
+) -> Result<()> {
+    let date = match week {
+        Some(week) => parse_week(&week)?,
+        None if format == ExportFormat::Timeclock => return write_output(path, render(data)),
+        None => Local::today().naive_local(),
+    };
+    let (start, end) = week_of(settings, date);
+    let filtered_data = filter_week(settings, data, (start, end))?;
+    let days = collect_days(settings, &filtered_data);
+    let total = days
+        .iter()
+        .fold(Duration::zero(), |total, day| total + day.work_time);
+    let weekly_delta = total - settings.weekly_goal_for_date(start);
+    let title = format!(
+        "Timesheet {} - {}",
+        start.format("%Y-%m-%d"),
+        end.format("%Y-%m-%d")
+    );
+
+    let content = match format {
+        ExportFormat::Markdown => render_markdown(&title, &days, total, weekly_delta),
+        ExportFormat::Html => render_html(&title, &days, total, weekly_delta),
+        ExportFormat::Timeclock => render(&filtered_data),
+    };
+    write_output(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(Duration::minutes(90)), "+01:30");
+        assert_eq!(format_delta(Duration::minutes(-45)), "-00:45");
+        assert_eq!(format_delta(Duration::zero()), "+00:00");
+    }
+}
@@ -0,0 +1,85 @@
+//! Vacation days, which have no goal. They are stored with the other absences (see the absence
+//! module), so every profile has its own.
+
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::absence::{self, AbsenceKind};
+
+#[derive(Debug, StructOpt)]
+pub enum VacationCommand {
+    /// add vacation days
+    Add {
+        /// the first day of the vacation
+        from: String,
+        /// the last day of the vacation [default: same as from]
+        to: Option<String>,
+    },
+
+    /// remove vacation days
+    Remove {
+        /// the first day to remove
+        from: String,
+        /// the last day to remove [default: same as from]
+        to: Option<String>,
+    },
+
+    /// list all vacation days
+    List,
+}
+
+pub fn vacation(path: &str, command: VacationCommand) -> Result<()> {
+    let mut absences = absence::read(path)?;
+    match command {
+        VacationCommand::Add { from, to } => {
+            let range = absence::date_range(&from, to.as_deref())?;
+            absence::add(&mut absences, AbsenceKind::Vacation, &range);
+            println!("Added {} vacation days.", range.len());
+        }
+        VacationCommand::Remove { from, to } => {
+            let range = absence::date_range(&from, to.as_deref())?;
+            let count = absences.len();
+            absences.retain(|a| !(a.kind == AbsenceKind::Vacation && range.contains(&a.date)));
+            println!("Removed {} vacation days.", count - absences.len());
+        }
+        VacationCommand::List => {
+            for absence in absences.iter().filter(|a| a.kind == AbsenceKind::Vacation) {
+                println!("{}", absence.date.format("%a %Y-%m-%d"));
+            }
+            return Ok(());
+        }
+    }
+    absence::write(path, &mut absences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_vacation_reduces_goals() {
+        let path = std::env::temp_dir()
+            .join(format!("tt-test-vacation-{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let add = VacationCommand::Add {
+            from: "2021-04-01".to_string(),
+            to: Some("2021-04-02".to_string()),
+        };
+        vacation(&path, add).unwrap();
+
+        let mut settings = Settings::from_default_config();
+        settings.absences = absence::read(&path).unwrap();
+        let (wednesday, thursday) = (
+            NaiveDate::from_ymd(2021, 3, 31),
+            NaiveDate::from_ymd(2021, 4, 1),
+        );
+        assert_eq!(Duration::hours(8), settings.goal_for_date(wednesday));
+        assert_eq!(Duration::zero(), settings.goal_for_date(thursday));
+        assert_eq!(Duration::hours(24), settings.weekly_goal_for_date(thursday));
+
+        let _ = std::fs::remove_file(format!("{}.absences", path));
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{prelude::*, Duration};
+
+use std::io;
+
+use crate::auto_stop;
+use crate::break_reminder;
+use crate::event_hooks;
+use crate::nag;
+use crate::settings::Settings;
+use crate::storage::{lock, read_data_if_exists, read_tail};
+use crate::{write_correction, TrackingData, TrackingEvent};
+
+/// seconds between two idle checks
+const POLL_INTERVAL: u64 = 10;
+
+fn run_idle_command(program: &str, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("could not run \"{}\"", program))?;
+    if !output.status.success() {
+        return Err(anyhow!("\"{}\" failed: {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_milliseconds(output: &str) -> Result<Duration> {
+    let milliseconds = output
+        .trim()
+        .parse()
+        .with_context(|| format!("could not parse idle time \"{}\"", output.trim()))?;
+    Ok(Duration::milliseconds(milliseconds))
+}
+
+/// reads HIDIdleTime (in nanoseconds) from the output of `ioreg -c IOHIDSystem`
+fn parse_ioreg(output: &str) -> Result<Duration> {
+    output
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|nanoseconds| nanoseconds.trim().parse().ok())
+        .map(Duration::nanoseconds)
+        .ok_or_else(|| anyhow!("could not find HIDIdleTime in the output of ioreg"))
+}
+
+fn get_idle_time(settings: &Settings) -> Result<Duration> {
+    if !settings.watch.idle_command.is_empty() {
+        let output = if cfg!(windows) {
+            run_idle_command("cmd", &["/C", &settings.watch.idle_command])?
+        } else {
+            run_idle_command("sh", &["-c", &settings.watch.idle_command])?
+        };
+        parse_milliseconds(&output)
+    } else if cfg!(target_os = "macos") {
+        parse_ioreg(&run_idle_command("ioreg", &["-c", "IOHIDSystem"])?)
+    } else if cfg!(unix) {
+        parse_milliseconds(&run_idle_command("xprintidle", &[])?)
+    } else {
+        Err(anyhow!(
+            "idle time can't be detected on this platform, please set watch.idle_command"
+        ))
+    }
+}
+
+fn ask_keep_idle_time(since: DateTime<Utc>) -> bool {
+    let since = since.with_timezone(&Local);
+    println!(
+        "Welcome back! You were idle since {:02}:{:02}. Keep the idle time as work time? (y|n) [default: n]: ",
+        since.hour(),
+        since.minute()
+    );
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(_) => matches!(input.trim(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
+
+/// stops the running session once `auto_stop_at` has passed
+fn check_auto_stop(settings: &Settings, path: &str) -> Result<()> {
+    if settings.auto_stop_at.trim().is_empty() {
+        return Ok(());
+    }
+    let _lock = lock(path)?;
+    let mut data = read_data_if_exists(path)?;
+    let original_data = data.clone();
+    if auto_stop::check(settings, &mut data)? {
+        write_correction(settings, path, &original_data, &mut data)?;
+    }
+    Ok(())
+}
+
+/// reminds to start tracking once per `remind_after` while nothing is tracked. returns when the
+/// last reminder was sent
+fn check_reminder(
+    settings: &Settings,
+    path: &str,
+    last_reminder: Option<DateTime<Utc>>,
+) -> Result<Option<DateTime<Utc>>> {
+    let remind_after = match settings.watch.remind_after {
+        Some(remind_after) => remind_after,
+        None => return Ok(None),
+    };
+    let now = Utc::now();
+    let data = read_tail(path, now)?;
+    match nag::untracked_for(settings, &data, now.with_timezone(&Local).naive_local()) {
+        Some(untracked) if last_reminder.is_none_or(|last| now - last >= remind_after) => {
+            nag::remind(untracked);
+            Ok(Some(now))
+        }
+        Some(_) => Ok(last_reminder),
+        None => Ok(None),
+    }
+}
+
+pub fn watch(settings: &Settings, path: &str, idle_minutes: Option<u32>) -> Result<()> {
+    let idle_limit = Duration::minutes(i64::from(
+        idle_minutes.unwrap_or(settings.watch.idle_minutes),
+    ));
+    // the stop event inserted by the last idle period, until the user returns
+    let mut idle_stop: Option<TrackingEvent> = None;
+    let mut last_reminder = None;
+
+    println!(
+        "Watching for {} minutes of inactivity. Press Ctrl-C to quit.",
+        idle_limit.num_minutes()
+    );
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(POLL_INTERVAL));
+        check_auto_stop(settings, path)?;
+        event_hooks::check_goal(settings, path)?;
+
+        let idle_time = get_idle_time(settings)?;
+        // nobody is there to start tracking while the computer is idle
+        if idle_time < idle_limit {
+            last_reminder = check_reminder(settings, path, last_reminder)?;
+            break_reminder::check(settings, path)?;
+        }
+
+        match idle_stop.take() {
+            None if idle_time >= idle_limit => {
+                let _lock = lock(path)?;
+                let mut data = read_data_if_exists(path)?;
+                let original_data = data.clone();
+                if matches!(data.last(), Some(event) if event.is_start()) {
+                    // the data file only stores whole seconds, so the event can be found again
+                    let since = (Utc::now() - idle_time)
+                        .with_nanosecond(0)
+                        .expect("could not set nanoseconds to zero");
+                    let stop =
+                        TrackingEvent::Stop(TrackingData::new(Some("idle".to_string()), since));
+                    data.push(stop.clone());
+                    write_correction(settings, path, &original_data, &mut data)?;
+                    println!(
+                        "Stopped time tracking after {} minutes of inactivity.",
+                        idle_time.num_minutes()
+                    );
+                    idle_stop = Some(stop);
+                }
+            }
+            Some(stop) if idle_time < idle_limit => {
+                if settings.watch.ask_on_return && ask_keep_idle_time(stop.time(true)) {
+                    // the data is read after asking, so the lock isn't held while waiting
+                    let _lock = lock(path)?;
+                    let mut data = read_data_if_exists(path)?;
+                    let original_data = data.clone();
+                    if let Some(index) = data.iter().rposition(|event| *event == stop) {
+                        data.remove(index);
+                        write_correction(settings, path, &original_data, &mut data)?;
+                        println!("Idle time is kept, time tracking continues.");
+                    }
+                } else {
+                    println!("Idle time is discarded, time tracking stays stopped.");
+                }
+            }
+            still_idle => idle_stop = still_idle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg() {
+        let output = r#"
+    | |   "HIDIdleTime" = 1234567890
+    | |   "HIDParameters" = {}
+"#;
+        assert_eq!(
+            Duration::nanoseconds(1_234_567_890),
+            parse_ioreg(output).unwrap()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Clipping the tracked time to a time of day, e.g. to see how much of it was within or outside of
+//! the core hours.
+
+use anyhow::{anyhow, Result};
+use chrono::{prelude::*, Duration};
+
+use std::str::FromStr;
+
+use crate::natural_time::parse_time;
+use crate::{TrackingData, TrackingEvent};
+
+/// a time of day range. a window which ends before it starts wraps around midnight, so
+/// "17:00-09:00" is everything outside of "09:00-17:00".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl FromStr for DailyWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let error = || {
+            anyhow!(
+                "invalid daily window \"{}\", expected a time range like \"09:00-17:00\"",
+                s
+            )
+        };
+        let (start, end) = s.split_once('-').ok_or_else(error)?;
+        let start = parse_time(start.trim()).ok_or_else(error)?;
+        let end = parse_time(end.trim()).ok_or_else(error)?;
+        if start == end {
+            return Err(error());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl DailyWindow {
+    /// the parts of the window on `date`
+    fn ranges(&self, date: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let midnight = NaiveTime::from_hms(0, 0, 0);
+        if self.start < self.end {
+            vec![(date.and_time(self.start), date.and_time(self.end))]
+        } else {
+            vec![
+                (date.and_time(midnight), date.and_time(self.end)),
+                (
+                    date.and_time(self.start),
+                    (date + Duration::days(1)).and_time(midnight),
+                ),
+            ]
+        }
+    }
+
+    /// when the window containing `time` opened, or `None` if `time` is outside of the window. a
+    /// window which wraps around midnight opened the day before in the morning hours
+    pub fn opened_at(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let (date, time) = (time.date(), time.time());
+        if self.start < self.end {
+            (self.start <= time && time < self.end).then(|| date.and_time(self.start))
+        } else if time >= self.start {
+            Some(date.and_time(self.start))
+        } else if time < self.end {
+            Some((date - Duration::days(1)).and_time(self.start))
+        } else {
+            None
+        }
+    }
+
+    /// the events with every interval cut to the parts within the window. the days are the ones
+    /// of the timezone the interval was started in.
+    pub fn clip(&self, data: &[TrackingEvent]) -> Vec<TrackingEvent> {
+        let mut clipped = Vec::new();
+        let mut data_iterator = data.iter();
+        while let Some(start) = data_iterator.find(|e| e.is_start()) {
+            let stop = data_iterator.find(|e| e.is_stop());
+            let begin = start.local_time();
+            let offset = *begin.offset();
+            let end = stop
+                .map_or_else(Utc::now, |stop| stop.time(true))
+                .with_timezone(&offset);
+            let mut date = begin.date().naive_local();
+            while date <= end.date().naive_local() {
+                for (from, to) in self.ranges(date) {
+                    let from = begin.max(offset.from_local_datetime(&from).unwrap());
+                    let to = end.min(offset.from_local_datetime(&to).unwrap());
+                    if from >= to {
+                        continue;
+                    }
+                    clipped.push(TrackingEvent::Start(TrackingData {
+                        time: from.with_timezone(&Utc),
+                        ..start.data().clone()
+                    }));
+                    // a running interval keeps running, if the window doesn't end it
+                    if stop.is_some() || to < end {
+                        clipped.push(TrackingEvent::Stop(TrackingData {
+                            offset: start.data().offset,
+                            ..TrackingData::new(None, to.with_timezone(&Utc))
+                        }));
+                    }
+                }
+                date = date.succ();
+            }
+        }
+        clipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(start: bool, day: u32, h: u32) -> TrackingEvent {
+        let data = TrackingData {
+            offset: Some(0),
+            ..TrackingData::new(None, Utc.ymd(2021, 4, day).and_hms(h, 0, 0))
+        };
+        if start {
+            TrackingEvent::Start(data)
+        } else {
+            TrackingEvent::Stop(data)
+        }
+    }
+
+    #[test]
+    fn test_clip() {
+        let data = vec![event(true, 1, 7), event(false, 1, 19)];
+        let core_hours: DailyWindow = "09:00-17:00".parse().unwrap();
+        assert_eq!(
+            vec![event(true, 1, 9), event(false, 1, 17)],
+            core_hours.clip(&data)
+        );
+        let outside: DailyWindow = "17:00-9".parse().unwrap();
+        assert_eq!(
+            vec![
+                event(true, 1, 7),
+                event(false, 1, 9),
+                event(true, 1, 17),
+                event(false, 1, 19)
+            ],
+            outside.clip(&data)
+        );
+        let overnight = vec![event(true, 1, 22), event(false, 2, 10)];
+        assert_eq!(
+            vec![event(true, 2, 9), event(false, 2, 10)],
+            core_hours.clip(&overnight)
+        );
+        assert!("09:00".parse::<DailyWindow>().is_err());
+    }
+
+    #[test]
+    fn test_opened_at() {
+        let at = |day, h, m| NaiveDate::from_ymd(2021, 4, day).and_hms(h, m, 0);
+        let core_hours: DailyWindow = "09:00-17:00".parse().unwrap();
+        assert_eq!(Some(at(1, 9, 0)), core_hours.opened_at(at(1, 12, 30)));
+        assert_eq!(None, core_hours.opened_at(at(1, 17, 0)));
+        let night: DailyWindow = "22:00-06:00".parse().unwrap();
+        assert_eq!(Some(at(1, 22, 0)), night.opened_at(at(1, 23, 0)));
+        assert_eq!(Some(at(1, 22, 0)), night.opened_at(at(2, 5, 0)));
+        assert_eq!(None, night.opened_at(at(2, 12, 0)));
+    }
+}